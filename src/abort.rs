@@ -0,0 +1,30 @@
+//! Aborting functionality, notably [`SharedFile::abort`] and [`SharedFileWriter::abort`].
+
+use crate::{FailureReason, SharedFile, SharedFileWriter};
+use std::error::Error;
+use std::sync::Arc;
+
+impl<T> SharedFile<T> {
+    /// Aborts writing the file, e.g. when an upload was cancelled.
+    ///
+    /// The file is marked as failed and all readers are woken up. Reads then fail with
+    /// [`ReadError::Aborted`](crate::prelude::ReadError::Aborted) containing the `reason`.
+    pub fn abort<E>(&self, reason: E)
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.sentinel
+            .fail_with(FailureReason::Aborted(Arc::from(reason.into())));
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Aborts writing the file, see [`SharedFile::abort`].
+    pub fn abort<E>(self, reason: E)
+    where
+        E: Into<Box<dyn Error + Send + Sync>>,
+    {
+        self.sentinel()
+            .fail_with(FailureReason::Aborted(Arc::from(reason.into())));
+    }
+}
@@ -0,0 +1,58 @@
+//! Append sessions, notably [`SharedFile::append_writer`].
+
+use crate::errors::OpenWriterError;
+use crate::{SharedFile, SharedFileType, SharedFileWriter, WriteState};
+use std::io::SeekFrom;
+use tokio::io::{AsyncSeek, AsyncSeekExt};
+
+impl<T> SharedFile<T>
+where
+    T: SharedFileType,
+    T::WriteHandle: AsyncSeek + Unpin,
+{
+    /// Creates a writer appending to the end of the file.
+    ///
+    /// If a previous writer already completed the file, the file is reopened for writing
+    /// so that it can be extended in multiple sessions, e.g. by log-style producers.
+    /// Readers that already reached the end of the file wait for the appended data
+    /// instead of finishing, unless their read already returned.
+    ///
+    /// The same considerations as for [`writer`](Self::writer) apply; in particular,
+    /// only one writer must be active at a time.
+    pub async fn append_writer(
+        &self,
+    ) -> Result<SharedFileWriter<T, T::WriteHandle>, OpenWriterError<T::OpenError>> {
+        let mut file = self
+            .sentinel
+            .original
+            .open_rw()
+            .await
+            .map_err(OpenWriterError::Open)?;
+
+        let end = loop {
+            match self.sentinel.state.load() {
+                WriteState::Pending(_committed, written) => break written,
+                WriteState::Completed(count) => {
+                    let reopened = self.sentinel.state.compare_exchange(
+                        WriteState::Completed(count),
+                        WriteState::Pending(count, count),
+                    );
+                    if reopened.is_ok() {
+                        self.sentinel.clear_digest();
+                        self.sentinel.expected_len.store(None);
+                        break count;
+                    }
+                }
+                WriteState::Failed => return Err(OpenWriterError::FileWritingFailed),
+            }
+        };
+
+        if let Err(e) = file.seek(SeekFrom::Start(end)).await {
+            self.sentinel.fail_with_error(&e);
+            return Err(OpenWriterError::Io(e));
+        }
+
+        self.sentinel.wake_readers();
+        Ok(SharedFileWriter::new_at(file, self.sentinel.clone(), end))
+    }
+}
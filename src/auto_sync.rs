@@ -0,0 +1,89 @@
+//! Automatic commits of written data, notably the [`AutoSyncPolicy`] type.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A policy for automatically committing written data and waking up readers.
+///
+/// Data is committed by flushing the writer as soon as either threshold is exceeded.
+/// The policy is evaluated whenever data is written; an idle writer does not commit
+/// in the background. Note that committing does not sync the file to disk; use
+/// [`SharedFileWriter::sync_data`](crate::SharedFileWriter::sync_data) for durability.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct AutoSyncPolicy {
+    /// Commits once at least this many bytes were written since the last commit.
+    pub bytes: Option<u64>,
+    /// Commits once uncommitted data is older than this duration.
+    pub interval: Option<Duration>,
+}
+
+impl AutoSyncPolicy {
+    /// Creates a new auto-sync policy.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes` - The number of uncommitted bytes after which to commit, if any.
+    /// * `interval` - The maximum age of uncommitted data, if any.
+    pub const fn new(bytes: Option<u64>, interval: Option<Duration>) -> Self {
+        Self { bytes, interval }
+    }
+
+    /// Creates a policy committing every `bytes` bytes written.
+    pub const fn every_bytes(bytes: u64) -> Self {
+        Self::new(Some(bytes), None)
+    }
+
+    /// Creates a policy committing data once it is older than `interval`.
+    pub const fn every_interval(interval: Duration) -> Self {
+        Self::new(None, Some(interval))
+    }
+
+    /// Determines whether a commit is due.
+    ///
+    /// ## Arguments
+    ///
+    /// * `uncommitted` - The number of bytes written since the last commit.
+    /// * `since` - The time the oldest uncommitted data was written, if any.
+    pub(crate) fn is_due(&self, uncommitted: u64, since: Option<Instant>) -> bool {
+        if uncommitted == 0 {
+            return false;
+        }
+
+        let bytes_due = self.bytes.map_or(false, |bytes| uncommitted >= bytes);
+        let interval_due = match (self.interval, since) {
+            (Some(interval), Some(since)) => since.elapsed() >= interval,
+            _ => false,
+        };
+        bytes_due || interval_due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bytes() {
+        let policy = AutoSyncPolicy::every_bytes(100);
+        assert!(!policy.is_due(0, None));
+        assert!(!policy.is_due(99, None));
+        assert!(policy.is_due(100, None));
+    }
+
+    #[test]
+    fn test_interval() {
+        let now = Instant::now();
+        let policy = AutoSyncPolicy::every_interval(Duration::from_secs(60));
+        assert!(!policy.is_due(1, Some(now)));
+
+        let policy = AutoSyncPolicy::every_interval(Duration::ZERO);
+        assert!(policy.is_due(1, Some(now)));
+        assert!(!policy.is_due(0, Some(now)));
+    }
+
+    #[test]
+    fn test_disabled() {
+        let policy = AutoSyncPolicy::default();
+        assert!(!policy.is_due(u64::MAX, Some(Instant::now())));
+    }
+}
@@ -0,0 +1,75 @@
+//! Blocking writing functionality, notably the [`BlockingWriter`] type.
+
+use crate::errors::CompleteWritingError;
+use crate::{SharedFileType, SharedFileWriter};
+use std::io;
+use std::io::Write;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::runtime::Handle;
+
+/// A writer adapter implementing [`std::io::Write`], see [`SharedFileWriter::into_blocking`].
+///
+/// This allows synchronous producers, e.g. an encoder running in
+/// [`spawn_blocking`](tokio::task::spawn_blocking), to write the file while readers consume it
+/// asynchronously. Every operation blocks the current thread until it completes, so the
+/// writer must not be used from within an asynchronous context.
+pub struct BlockingWriter<T, W = T> {
+    /// The writer to write to.
+    writer: SharedFileWriter<T, W>,
+    /// The runtime to drive the writer on.
+    handle: Handle,
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Converts the writer into a [`BlockingWriter`] implementing [`std::io::Write`].
+    ///
+    /// ## Panics
+    ///
+    /// Panics if not called from within a Tokio runtime.
+    pub fn into_blocking(self) -> BlockingWriter<T, W> {
+        BlockingWriter {
+            writer: self,
+            handle: Handle::current(),
+        }
+    }
+}
+
+impl<T, W> BlockingWriter<T, W> {
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &SharedFileWriter<T, W> {
+        &self.writer
+    }
+
+    /// Converts the writer back into the underlying asynchronous writer.
+    pub fn into_inner(self) -> SharedFileWriter<T, W> {
+        self.writer
+    }
+}
+
+impl<T, W> BlockingWriter<T, W>
+where
+    W: SharedFileType + AsyncWrite + Unpin,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Commits all written data and completes the writing operation, blocking the current thread.
+    ///
+    /// See [`SharedFileWriter::complete`].
+    pub fn complete(self) -> Result<(), CompleteWritingError> {
+        let Self { writer, handle } = self;
+        handle.block_on(writer.complete())
+    }
+}
+
+impl<T, W> Write for BlockingWriter<T, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.handle.block_on(self.writer.write(buf))
+    }
+
+    /// Flushes the file and commits all written data, waking up readers.
+    fn flush(&mut self) -> io::Result<()> {
+        self.handle.block_on(self.writer.flush())
+    }
+}
@@ -0,0 +1,168 @@
+//! Buffered writing functionality, notably the [`BufferedWriter`] type.
+
+use crate::errors::CompleteWritingError;
+use crate::{FailureReason, SharedFileType, SharedFileWriter};
+use pin_project::{pin_project, pinned_drop};
+use std::io::{Error, ErrorKind};
+use std::pin::{pin, Pin};
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// A writer adapter batching small writes in memory, see [`SharedFileWriter::buffered`].
+///
+/// The buffered data is written and committed, waking up readers, whenever the buffer is full
+/// and when the writer is flushed. Writes at least as large as the buffer bypass it.
+///
+/// ## Dropping the writer
+///
+/// If the writer is dropped with buffered data, the data is lost and the file is marked as
+/// failed, such that readers fail with
+/// [`ReadError::UncommittedData`](crate::prelude::ReadError::UncommittedData) instead of
+/// mistaking the data written so far for the complete file. Call [`complete`](Self::complete)
+/// or flush the writer before dropping it.
+#[pin_project(PinnedDrop)]
+pub struct BufferedWriter<T, W = T> {
+    /// The writer to write to.
+    #[pin]
+    writer: SharedFileWriter<T, W>,
+    /// The buffered data.
+    buffer: Vec<u8>,
+    /// The capacity of the buffer.
+    capacity: usize,
+    /// The number of buffered bytes already written to the file.
+    written: usize,
+    /// Whether written data still needs to be committed.
+    commit_pending: bool,
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Converts the writer into a [`BufferedWriter`] batching writes of up to `capacity` bytes.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub fn buffered(self, capacity: usize) -> BufferedWriter<T, W> {
+        assert_ne!(capacity, 0, "The capacity must not be zero");
+        BufferedWriter {
+            writer: self,
+            buffer: Vec::with_capacity(capacity),
+            capacity,
+            written: 0,
+            commit_pending: false,
+        }
+    }
+}
+
+impl<T, W> BufferedWriter<T, W> {
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &SharedFileWriter<T, W> {
+        &self.writer
+    }
+
+    /// Gets the buffered data not yet written to the file.
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer[self.written..]
+    }
+
+    /// Gets the capacity of the buffer.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+}
+
+impl<T, W> BufferedWriter<T, W>
+where
+    W: SharedFileType + AsyncWrite + Unpin,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Writes and commits all buffered data, then completes the writing operation.
+    ///
+    /// See [`SharedFileWriter::complete`].
+    pub async fn complete(self) -> Result<(), CompleteWritingError> {
+        let mut this = pin!(self);
+        this.flush().await?;
+        this.project().writer.complete_pinned().await
+    }
+}
+
+impl<T, W> BufferedWriter<T, W>
+where
+    W: AsyncWrite,
+{
+    /// Writes all buffered data to the file and commits it.
+    fn poll_flush_buf(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        while *this.written < this.buffer.len() {
+            let written = ready!(this
+                .writer
+                .as_mut()
+                .poll_write(cx, &this.buffer[*this.written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+            }
+            *this.written += written;
+        }
+
+        if !this.buffer.is_empty() {
+            this.buffer.clear();
+            *this.written = 0;
+            *this.commit_pending = true;
+        }
+
+        if *this.commit_pending {
+            ready!(this.writer.poll_flush(cx))?;
+            *this.commit_pending = false;
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[pinned_drop]
+impl<T, W> PinnedDrop for BufferedWriter<T, W> {
+    fn drop(self: Pin<&mut Self>) {
+        if !self.buffer.is_empty() {
+            self.writer
+                .sentinel()
+                .fail_with(FailureReason::UncommittedData);
+        }
+    }
+}
+
+impl<T, W> AsyncWrite for BufferedWriter<T, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        if self.buffer.len() + buf.len() > self.capacity {
+            ready!(self.as_mut().poll_flush_buf(cx))?;
+        }
+
+        let this = self.project();
+        if buf.len() >= *this.capacity {
+            let poll = this.writer.poll_write(cx, buf);
+            if let Poll::Ready(Ok(written)) = poll {
+                *this.commit_pending |= written > 0;
+            }
+            return poll;
+        }
+
+        this.buffer.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush_buf(cx))?;
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush_buf(cx))?;
+        self.project().writer.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,207 @@
+//! Configuration of shared files, notably the [`SharedFileBuilder`] type.
+
+use crate::notifier::Notifier;
+use crate::retention::Retention;
+use crate::{
+    AsyncNewFile, AutoSyncPolicy, FilePath, RateLimit, ReaderNotifier, RetentionPolicy, Sentinel,
+    SharedFile,
+};
+use std::fmt::{Debug, Formatter};
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// A builder collecting the options of a [`SharedFile`], see [`SharedFile::builder`].
+///
+/// Writer options apply to every writer created for the file, e.g. using
+/// [`SharedFile::writer`], and can still be overridden on the writer itself.
+pub struct SharedFileBuilder<T> {
+    /// The options applied to all writers.
+    writer_options: WriterOptions,
+    /// The expected total size of the file, if known in advance.
+    expected_len: Option<u64>,
+    /// The retention policy, along with a function determining the path it applies to.
+    retention: Option<(RetentionPolicy, PathFn<T>)>,
+    /// The watchdog timeout, along with a function spawning the watchdog.
+    watchdog: Option<(Duration, SpawnWatchdogFn<T>)>,
+    /// The strategy waking up readers, if not the default.
+    notifier: Option<Notifier>,
+    /// The ID of the file, if not generated.
+    id: Option<Uuid>,
+}
+
+/// Determines the path of a backing file.
+type PathFn<T> = fn(&T) -> PathBuf;
+
+/// Spawns a watchdog for a file.
+type SpawnWatchdogFn<T> = fn(&SharedFile<T>, Duration);
+
+/// The options applied to all writers of a file, see [`SharedFileBuilder`].
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct WriterOptions {
+    /// The policy for automatically committing written data, if any.
+    pub(crate) auto_sync: Option<AutoSyncPolicy>,
+    /// The maximum number of bytes a writer may be ahead of the slowest reader, if bounded.
+    pub(crate) max_lag: Option<u64>,
+    /// The rate limit of writers, if any.
+    pub(crate) rate_limit: Option<RateLimit>,
+    /// The capacity of the tail cache, if enabled.
+    pub(crate) tail_cache: Option<usize>,
+}
+
+impl<T> SharedFile<T> {
+    /// Creates a builder to configure a shared file.
+    pub fn builder() -> SharedFileBuilder<T> {
+        SharedFileBuilder::default()
+    }
+}
+
+impl<T> SharedFileBuilder<T> {
+    /// Sets the policy for automatically committing written data for all writers,
+    /// see [`SharedFileWriter::with_auto_sync`](crate::SharedFileWriter::with_auto_sync).
+    pub fn auto_sync(mut self, policy: AutoSyncPolicy) -> Self {
+        self.writer_options.auto_sync = Some(policy);
+        self
+    }
+
+    /// Bounds the number of bytes writers may be ahead of the slowest active reader,
+    /// see [`SharedFileWriter::with_max_lag`](crate::SharedFileWriter::with_max_lag).
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `max_lag` is zero.
+    pub fn max_lag(mut self, max_lag: u64) -> Self {
+        assert_ne!(max_lag, 0, "The maximum lag must not be zero");
+        self.writer_options.max_lag = Some(max_lag);
+        self
+    }
+
+    /// Limits the rate at which writers write data,
+    /// see [`SharedFileWriter::with_rate_limit`](crate::SharedFileWriter::with_rate_limit).
+    pub fn rate_limit(mut self, limit: RateLimit) -> Self {
+        self.writer_options.rate_limit = Some(limit);
+        self
+    }
+
+    /// Retains up to `capacity` bytes of the most recently written data in memory,
+    /// see [`SharedFileWriter::with_tail_cache`](crate::SharedFileWriter::with_tail_cache).
+    pub fn tail_cache(mut self, capacity: usize) -> Self {
+        self.writer_options.tail_cache = Some(capacity);
+        self
+    }
+
+    /// Sets the expected total size of the file, which readers see as
+    /// [`FileSize::AtLeastWithTotal`](crate::FileSize::AtLeastWithTotal).
+    ///
+    /// Unlike [`SharedFileWriter::set_expected_len`](crate::SharedFileWriter::set_expected_len),
+    /// this does not preallocate the file.
+    pub fn expected_len(mut self, len: u64) -> Self {
+        self.expected_len = Some(len);
+        self
+    }
+
+    /// Sets what happens to the file once it is no longer used,
+    /// see [`SharedFile::set_retention_policy`].
+    pub fn retention_policy(mut self, policy: RetentionPolicy) -> Self
+    where
+        T: FilePath,
+    {
+        self.retention = Some((policy, |file| file.file_path().clone()));
+        self
+    }
+
+    /// Fails the file if the writer makes no progress for `timeout`,
+    /// see [`SharedFile::spawn_watchdog`].
+    ///
+    /// The watchdog is spawned when building the file, which must then happen
+    /// from within a Tokio runtime.
+    pub fn watchdog(mut self, timeout: Duration) -> Self
+    where
+        T: Send + Sync + 'static,
+    {
+        self.watchdog = Some((timeout, |file, timeout| {
+            file.spawn_watchdog(timeout);
+        }));
+        self
+    }
+
+    /// Sets the strategy waking up readers waiting for data, e.g. to batch wakeups.
+    ///
+    /// By default, readers are woken up using a [`WakerMap`](crate::WakerMap).
+    pub fn notifier<N>(mut self, notifier: N) -> Self
+    where
+        N: ReaderNotifier + 'static,
+    {
+        self.notifier = Some(Notifier(Box::new(notifier)));
+        self
+    }
+
+    /// Assigns the ID of the file instead of generating one, see [`SharedFile::id`].
+    pub fn id(mut self, id: Uuid) -> Self {
+        self.id = Some(id);
+        self
+    }
+
+    /// Creates the shared file from the specified backing file.
+    pub fn build(self, file: T) -> SharedFile<T> {
+        let retention = self.retention.map(|(policy, path)| Retention {
+            policy,
+            path: path(&file),
+        });
+
+        let mut sentinel = Sentinel::new(file);
+        sentinel.writer_options = self.writer_options;
+        sentinel.expected_len.store(self.expected_len);
+        sentinel.retention = Mutex::new(retention);
+        if let Some(id) = self.id {
+            sentinel.id = id;
+        }
+        if let Some(notifier) = self.notifier {
+            sentinel.notifier = notifier;
+        }
+
+        let file = SharedFile {
+            sentinel: Arc::new(sentinel),
+        };
+        if let Some((timeout, spawn)) = self.watchdog {
+            spawn(&file, timeout);
+        }
+        file
+    }
+
+    /// Asynchronously creates a new backing file and the shared file from it.
+    pub async fn build_async(self) -> Result<SharedFile<T>, T::Error>
+    where
+        T: AsyncNewFile<Target = T>,
+    {
+        let file = T::new_async().await?;
+        Ok(self.build(file))
+    }
+}
+
+impl<T> Default for SharedFileBuilder<T> {
+    fn default() -> Self {
+        Self {
+            writer_options: WriterOptions::default(),
+            expected_len: None,
+            retention: None,
+            watchdog: None,
+            notifier: None,
+            id: None,
+        }
+    }
+}
+
+impl<T> Debug for SharedFileBuilder<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedFileBuilder")
+            .field("writer_options", &self.writer_options)
+            .field("expected_len", &self.expected_len)
+            .field("retention", &self.retention.map(|(policy, _)| policy))
+            .field("watchdog", &self.watchdog.map(|(timeout, _)| timeout))
+            .field("notifier", &self.notifier)
+            .field("id", &self.id)
+            .finish()
+    }
+}
@@ -0,0 +1,88 @@
+//! Named checkpoints, notably [`SharedFileWriter::checkpoint`] and
+//! [`SharedFileReader::await_checkpoint`].
+
+use crate::errors::ReadError;
+use crate::{Sentinel, SharedFile, SharedFileReader, SharedFileWriter, WriteState};
+use std::io::{Error, ErrorKind};
+use tokio::io;
+use tokio::io::AsyncWrite;
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Commits all written data and publishes a named checkpoint at the current write position,
+    /// e.g. once the header of the file was written.
+    ///
+    /// Readers can wait for the checkpoint using [`SharedFileReader::await_checkpoint`] instead
+    /// of guessing byte offsets. Publishing a checkpoint again moves it to the current position.
+    ///
+    /// Returns the offset of the checkpoint.
+    pub async fn checkpoint(&mut self, name: impl Into<String>) -> io::Result<u64> {
+        self.commit().await?;
+        let offset = self.position();
+        self.sentinel()
+            .checkpoints
+            .lock()
+            .expect("failed to lock checkpoints for writing")
+            .insert(name.into(), offset);
+        self.sentinel().wake_readers();
+        Ok(offset)
+    }
+}
+
+impl<T> SharedFile<T> {
+    /// Gets the offset of the named checkpoint, if it was published.
+    ///
+    /// See [`SharedFileWriter::checkpoint`].
+    pub fn checkpoint(&self, name: &str) -> Option<u64> {
+        self.sentinel.checkpoint(name)
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Waits until the named checkpoint was published and all data before it was committed.
+    ///
+    /// Returns the offset of the checkpoint. Fails with [`ReadError::CheckpointNotFound`] if
+    /// the file is completed without publishing the checkpoint, or if writing the file failed.
+    pub async fn await_checkpoint(&self, name: &str) -> io::Result<u64> {
+        let sentinel = self.sentinel();
+        loop {
+            // Register for notifications before checking to avoid missing an update.
+            let notified = sentinel.committed.notified();
+            match (sentinel.checkpoint(name), sentinel.state.load()) {
+                (_, WriteState::Failed) => return Err(sentinel.read_error()),
+                (Some(offset), _) => {
+                    self.wait_for_range(0..offset).await?;
+                    return Ok(offset);
+                }
+                (None, WriteState::Completed(_)) => {
+                    return Err(Error::new(
+                        ErrorKind::NotFound,
+                        ReadError::CheckpointNotFound(name.to_string()),
+                    ));
+                }
+                (None, WriteState::Pending(_, _)) => notified.await,
+            }
+        }
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Gets the offset of the named checkpoint, if it was published.
+    fn checkpoint(&self, name: &str) -> Option<u64> {
+        self.checkpoints
+            .lock()
+            .expect("failed to lock checkpoints for reading")
+            .get(name)
+            .copied()
+    }
+
+    /// Discards checkpoints after `len`, e.g. when the file was truncated.
+    pub(crate) fn truncate_checkpoints(&self, len: u64) {
+        self.checkpoints
+            .lock()
+            .expect("failed to lock checkpoints for writing")
+            .retain(|_name, offset| *offset <= len);
+    }
+}
@@ -0,0 +1,336 @@
+//! Per-block integrity checks, notably the [`ChecksummedFile`] type.
+
+use crate::errors::ReadError;
+use crate::{Crc32, FilePath, SharedFileType};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The default maximum number of bytes stored in a single block.
+pub const CHECKSUM_BLOCK_SIZE: usize = 64 * 1024;
+
+/// The length of the big-endian length prefix of each block.
+const LEN_PREFIX_LEN: usize = 4;
+
+/// The length of the big-endian CRC-32 checksum following each block.
+const CHECKSUM_LEN: usize = 4;
+
+/// A file storing a CRC-32 checksum with every block of data written to it and verifying
+/// it when the block is read.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(ChecksummedFile::new(file))`. Every block is stored as its length,
+/// its data and the checksum of its data. The number of written and committed bytes, as well
+/// as the data seen by readers, refer to the data only.
+///
+/// Readers fail with [`ReadError::CorruptBlock`] instead of returning data that does not match
+/// its checksum. Committing data by flushing the writer ends the current block, such that
+/// commits are aligned to block boundaries; frequent commits therefore increase the overhead
+/// of 8 bytes per block.
+#[pin_project]
+pub struct ChecksummedFile<T> {
+    /// The file to read from or write to.
+    #[pin]
+    file: T,
+    /// The maximum number of bytes stored in a single block.
+    block_size: usize,
+    /// The state of writing or reading blocks, depending on how the file was opened.
+    mode: Mode,
+}
+
+/// The mode of a [`ChecksummedFile`], depending on how the file was opened.
+enum Mode {
+    /// The original file, which is neither read nor written.
+    Original,
+    /// The file was opened for writing.
+    Writing(BlockWriter),
+    /// The file was opened for reading.
+    Reading(BlockReader),
+}
+
+/// The state of writing blocks.
+#[derive(Default)]
+struct BlockWriter {
+    /// The data of the current block.
+    data: Vec<u8>,
+    /// The encoded blocks not yet written to the file.
+    encoded: Vec<u8>,
+    /// The number of bytes of `encoded` already written to the file.
+    encoded_written: usize,
+}
+
+/// The state of reading blocks.
+#[derive(Default)]
+struct BlockReader {
+    /// The index of the next block.
+    block: u64,
+    /// The encoded bytes of the current block read so far.
+    encoded: Vec<u8>,
+    /// The verified data of the current block.
+    data: Vec<u8>,
+    /// The number of bytes of `data` already read.
+    data_read: usize,
+}
+
+impl<T> ChecksummedFile<T> {
+    /// Wraps the file, storing blocks of up to [`CHECKSUM_BLOCK_SIZE`] bytes.
+    pub fn new(file: T) -> Self {
+        Self::with_block_size(file, CHECKSUM_BLOCK_SIZE)
+    }
+
+    /// Wraps the file, storing blocks of up to `block_size` bytes.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `block_size` is zero or exceeds `u32::MAX`.
+    pub fn with_block_size(file: T, block_size: usize) -> Self {
+        assert_ne!(block_size, 0, "The block size must not be zero");
+        assert!(
+            u32::try_from(block_size).is_ok(),
+            "The block size must not exceed u32::MAX"
+        );
+        Self {
+            file,
+            block_size,
+            mode: Mode::Original,
+        }
+    }
+
+    /// Gets the maximum number of bytes stored in a single block.
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &T {
+        &self.file
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for ChecksummedFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + AsyncRead + AsyncWrite + Send + Sync,
+{
+    type ReadHandle = ChecksummedFile<T>;
+    type WriteHandle = ChecksummedFile<T>;
+    type OpenError = T::OpenError;
+    type SyncError = T::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(Self {
+            file: self.file.open_ro().await?,
+            block_size: self.block_size,
+            mode: Mode::Reading(BlockReader::default()),
+        })
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(Self {
+            file: self.file.open_rw().await?,
+            block_size: self.block_size,
+            mode: Mode::Writing(BlockWriter {
+                data: Vec::with_capacity(self.block_size),
+                ..BlockWriter::default()
+            }),
+        })
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.file.diagnostic_path()
+    }
+}
+
+impl<T> FilePath for ChecksummedFile<T>
+where
+    T: FilePath,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.file.file_path()
+    }
+}
+
+/// Computes the CRC-32 checksum of the data.
+fn checksum(data: &[u8]) -> u32 {
+    let mut crc = Crc32::new();
+    crc.update(data);
+    crc.value()
+}
+
+impl BlockWriter {
+    /// Encodes the current block, if any data was written to it.
+    fn end_block(&mut self) {
+        if self.data.is_empty() {
+            return;
+        }
+
+        let len = u32::try_from(self.data.len()).expect("the block size is bounded");
+        self.encoded.extend_from_slice(&len.to_be_bytes());
+        self.encoded.extend_from_slice(&self.data);
+        self.encoded
+            .extend_from_slice(&checksum(&self.data).to_be_bytes());
+        self.data.clear();
+    }
+
+    /// Writes all encoded blocks to the file.
+    fn poll_drain<T>(&mut self, mut file: Pin<&mut T>, cx: &mut Context<'_>) -> Poll<io::Result<()>>
+    where
+        T: AsyncWrite,
+    {
+        while self.encoded_written < self.encoded.len() {
+            let written = ready!(file
+                .as_mut()
+                .poll_write(cx, &self.encoded[self.encoded_written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+            }
+            self.encoded_written += written;
+        }
+
+        self.encoded.clear();
+        self.encoded_written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl BlockReader {
+    /// Gets the number of encoded bytes needed to verify the current block.
+    fn needed(&self, block_size: usize) -> io::Result<usize> {
+        let Some(prefix) = self.encoded.get(..LEN_PREFIX_LEN) else {
+            return Ok(LEN_PREFIX_LEN);
+        };
+        let len = u32::from_be_bytes(prefix.try_into().expect("the prefix has four bytes"));
+        if len == 0 || len as usize > block_size {
+            return Err(self.corrupt());
+        }
+        Ok(LEN_PREFIX_LEN + len as usize + CHECKSUM_LEN)
+    }
+
+    /// Creates the error returned for the current block if it is corrupt.
+    fn corrupt(&self) -> Error {
+        Error::new(ErrorKind::InvalidData, ReadError::CorruptBlock(self.block))
+    }
+
+    /// Verifies the block read, once all of its bytes were read.
+    fn verify(&mut self) -> io::Result<()> {
+        let (data, expected) = self.encoded[LEN_PREFIX_LEN..]
+            .split_at(self.encoded.len() - LEN_PREFIX_LEN - CHECKSUM_LEN);
+        let expected =
+            u32::from_be_bytes(expected.try_into().expect("the checksum has four bytes"));
+        if checksum(data) != expected {
+            return Err(self.corrupt());
+        }
+
+        self.data.clear();
+        self.data.extend_from_slice(data);
+        self.data_read = 0;
+        self.encoded.clear();
+        self.block += 1;
+        Ok(())
+    }
+}
+
+impl<T> AsyncRead for ChecksummedFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let Mode::Reading(reader) = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for reading",
+            )));
+        };
+
+        let mut file = this.file;
+        loop {
+            if reader.data_read < reader.data.len() {
+                let data = &reader.data[reader.data_read..];
+                let len = data.len().min(buf.remaining());
+                buf.put_slice(&data[..len]);
+                reader.data_read += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            let read = reader.encoded.len();
+            let needed = reader.needed(*this.block_size)?;
+            if read == needed {
+                reader.verify()?;
+                continue;
+            }
+
+            reader.encoded.resize(needed, 0);
+            let mut encoded = ReadBuf::new(&mut reader.encoded[read..]);
+            let poll = file.as_mut().poll_read(cx, &mut encoded);
+            let filled = encoded.filled().len();
+            reader.encoded.truncate(read + filled);
+            ready!(poll)?;
+
+            // Nothing is returned until a block is complete, e.g. at the end of the file.
+            if filled == 0 {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for ChecksummedFile<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let Mode::Writing(writer) = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for writing",
+            )));
+        };
+
+        ready!(writer.poll_drain(this.file, cx))?;
+        let len = buf.len().min(*this.block_size - writer.data.len());
+        writer.data.extend_from_slice(&buf[..len]);
+        if writer.data.len() == *this.block_size {
+            writer.end_block();
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    /// Ends the current block and writes it to the file, such that all data written
+    /// so far can be verified.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let mut file = this.file;
+        if let Mode::Writing(writer) = this.mode {
+            writer.end_block();
+            ready!(writer.poll_drain(file.as_mut(), cx))?;
+        }
+        file.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        self.project().file.poll_shutdown(cx)
+    }
+}
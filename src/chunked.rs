@@ -0,0 +1,144 @@
+//! Chunked reading functionality, notably the [`ChunkedReader`] type.
+
+use crate::SharedFileReader;
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// A reader adapter yielding chunks of a stable, aligned size.
+///
+/// Partial commits of the writer are buffered internally until a full chunk
+/// is available; only the last chunk of a completed file may be shorter.
+/// See [`SharedFileReader::chunked`].
+pub struct ChunkedReader<T, R = T> {
+    /// The reader to read from.
+    reader: SharedFileReader<T, R>,
+    /// The size of each chunk, a multiple of the alignment.
+    chunk_size: usize,
+    /// The alignment of the chunk boundaries.
+    alignment: usize,
+    /// The chunk currently being filled.
+    buffer: Vec<u8>,
+    /// The number of bytes of the current chunk filled so far.
+    filled: usize,
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Converts the reader into a [`ChunkedReader`] yielding chunks of a stable size.
+    ///
+    /// The chunk size is rounded up to the next multiple of `alignment`. If the reader is
+    /// not positioned at an aligned offset, the first chunk is shortened such that all
+    /// following chunk boundaries are aligned.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chunk_size` - The target size of each chunk, e.g. 64 KiB.
+    /// * `alignment` - The alignment of the chunk boundaries in the file. Use `1` for no alignment.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if either `chunk_size` or `alignment` is zero.
+    pub fn chunked(self, chunk_size: usize, alignment: usize) -> ChunkedReader<T, R> {
+        assert_ne!(chunk_size, 0, "The chunk size must not be zero");
+        assert_ne!(alignment, 0, "The alignment must not be zero");
+        ChunkedReader {
+            reader: self,
+            chunk_size: align_up(chunk_size, alignment),
+            alignment,
+            buffer: Vec::new(),
+            filled: 0,
+        }
+    }
+}
+
+impl<T, R> ChunkedReader<T, R> {
+    /// Gets the size of the regular chunks.
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// Gets a reference to the underlying reader.
+    pub fn get_ref(&self) -> &SharedFileReader<T, R> {
+        &self.reader
+    }
+
+    /// Consumes this adapter, returning the underlying reader.
+    ///
+    /// Any partially buffered chunk is discarded.
+    pub fn into_inner(self) -> SharedFileReader<T, R> {
+        self.reader
+    }
+}
+
+impl<T, R> ChunkedReader<T, R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Reads the next chunk, waiting for the writer to commit enough data.
+    ///
+    /// Returns [`None`] once the file was read completely.
+    ///
+    /// ## Cancel safety
+    ///
+    /// This method is cancel safe; data read into a partially filled chunk
+    /// is retained and returned by the next call.
+    pub async fn next_chunk(&mut self) -> io::Result<Option<Vec<u8>>> {
+        if self.filled == 0 {
+            let len = chunk_len_at(self.reader.position(), self.chunk_size, self.alignment);
+            self.buffer.resize(len, 0);
+        }
+
+        while self.filled < self.buffer.len() {
+            let read = self.reader.read(&mut self.buffer[self.filled..]).await?;
+            if read == 0 {
+                break;
+            }
+            self.filled += read;
+        }
+
+        if self.filled == 0 {
+            return Ok(None);
+        }
+
+        let mut chunk = std::mem::take(&mut self.buffer);
+        chunk.truncate(self.filled);
+        self.filled = 0;
+        Ok(Some(chunk))
+    }
+}
+
+/// Rounds the value up to the next multiple of the alignment.
+fn align_up(value: usize, alignment: usize) -> usize {
+    ((value + alignment - 1) / alignment) * alignment
+}
+
+/// Determines the length of the chunk starting at the specified offset.
+fn chunk_len_at(offset: u64, chunk_size: usize, alignment: usize) -> usize {
+    let misalignment = (offset % alignment as u64) as usize;
+    if misalignment == 0 {
+        chunk_size
+    } else {
+        alignment - misalignment
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_align_up() {
+        assert_eq!(align_up(1, 65536), 65536);
+        assert_eq!(align_up(65536, 65536), 65536);
+        assert_eq!(align_up(100_000, 65536), 131_072);
+        assert_eq!(align_up(42, 1), 42);
+    }
+
+    #[test]
+    fn test_chunk_len_at() {
+        assert_eq!(chunk_len_at(0, 128, 64), 128);
+        assert_eq!(chunk_len_at(128, 128, 64), 128);
+        assert_eq!(chunk_len_at(10, 128, 64), 54);
+        assert_eq!(chunk_len_at(64, 128, 64), 128);
+        assert_eq!(chunk_len_at(7, 7, 1), 7);
+    }
+}
@@ -0,0 +1,32 @@
+//! Conveniences for the [`tokio-util`](tokio_util) crate, notably [`ReaderStream`] and [`FramedRead`].
+
+use crate::SharedFileReader;
+use tokio::io::AsyncRead;
+use tokio_util::codec::{Decoder, FramedRead};
+use tokio_util::io::ReaderStream;
+
+impl<T, R> SharedFileReader<T, R>
+where
+    R: AsyncRead,
+{
+    /// Converts the reader into a [`ReaderStream`] yielding chunks of at most `chunk_size` bytes.
+    ///
+    /// ## Arguments
+    ///
+    /// * `chunk_size` - The capacity of the buffer used for reading.
+    pub fn into_reader_stream(self, chunk_size: usize) -> ReaderStream<Self> {
+        ReaderStream::with_capacity(self, chunk_size)
+    }
+
+    /// Converts the reader into a [`FramedRead`] decoding frames with the specified codec.
+    ///
+    /// ## Arguments
+    ///
+    /// * `codec` - The [`Decoder`] used to decode the frames.
+    pub fn framed<D>(self, codec: D) -> FramedRead<Self, D>
+    where
+        D: Decoder,
+    {
+        FramedRead::new(self, codec)
+    }
+}
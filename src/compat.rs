@@ -0,0 +1,50 @@
+//! [`futures_io`] support for readers and writers, e.g. for use with async-std or smol.
+
+use crate::{SharedFileReader, SharedFileWriter};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// Reads committed data from the file, waiting for the writer as needed.
+///
+/// This is equivalent to [`tokio::io::AsyncRead`], which remains the primary interface.
+impl<T, R> futures_io::AsyncRead for SharedFileReader<T, R>
+where
+    R: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let mut buf = ReadBuf::new(buf);
+        ready!(AsyncRead::poll_read(self, cx, &mut buf))?;
+        Poll::Ready(Ok(buf.filled().len()))
+    }
+}
+
+/// Writes data into the file.
+///
+/// Flushing the writer commits the written data and wakes up readers; closing it
+/// behaves like [`AsyncWrite::poll_shutdown`], completing the file by default.
+impl<T, W> futures_io::AsyncWrite for SharedFileWriter<T, W>
+where
+    W: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        AsyncWrite::poll_write(self, cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_flush(self, cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        AsyncWrite::poll_shutdown(self, cx)
+    }
+}
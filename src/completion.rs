@@ -0,0 +1,71 @@
+//! Waiting for files to be completed, notably the [`Completion`] future.
+
+use crate::reader::NODE_ID;
+use crate::{ReaderPriority, Sentinel, SharedFile, WriteState};
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tokio::io;
+use uuid::Uuid;
+
+/// A future resolving once the writer completed a file, see [`SharedFile::completion`].
+///
+/// Resolves to the size of the file in bytes, or the error readers would see if writing
+/// the file failed. The future is [`Unpin`] and can be stored in structs or polled
+/// repeatedly by reference, e.g. in
+/// [`tokio::select!`](https://docs.rs/tokio/latest/tokio/macro.select.html).
+pub struct Completion<T> {
+    /// The ID the waker of the future is registered with.
+    id: Uuid,
+    /// The sentinel of the file.
+    sentinel: Arc<Sentinel<T>>,
+}
+
+impl<T> SharedFile<T> {
+    /// Creates a future resolving once the writer completed the file, without reading it.
+    ///
+    /// This is a named alternative to [`wait_until_complete`](Self::wait_until_complete).
+    pub fn completion(&self) -> Completion<T> {
+        Completion {
+            id: Uuid::now_v1(NODE_ID),
+            sentinel: self.sentinel.clone(),
+        }
+    }
+}
+
+impl<T> Future for Completion<T> {
+    type Output = io::Result<u64>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let sentinel = &self.sentinel;
+        match sentinel.state.load() {
+            WriteState::Pending(committed, _written) => {
+                sentinel.register_reader_waker(self.id, ReaderPriority::default(), cx.waker());
+
+                // The writer may have committed data before the waker was registered.
+                if !sentinel.state.load().is_pending_with(committed) {
+                    cx.waker().wake_by_ref();
+                }
+                Poll::Pending
+            }
+            WriteState::Completed(len) => Poll::Ready(Ok(len)),
+            WriteState::Failed => Poll::Ready(Err(sentinel.read_error())),
+        }
+    }
+}
+
+impl<T> Drop for Completion<T> {
+    fn drop(&mut self) {
+        self.sentinel.remove_reader_waker(&self.id);
+    }
+}
+
+impl<T> Debug for Completion<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Completion")
+            .field("id", &self.id)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,202 @@
+//! Transparent compression, notably the [`CompressedFile`] type.
+
+use crate::{FilePath, SharedFileType};
+use async_compression::tokio::bufread::{GzipDecoder, ZstdDecoder};
+use async_compression::tokio::write::{GzipEncoder, ZstdEncoder};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, BufReader, ReadBuf};
+
+/// The compression format of a [`CompressedFile`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// The gzip format.
+    Gzip,
+    /// The Zstandard format.
+    #[default]
+    Zstd,
+}
+
+/// A file compressing all data written to it and decompressing all data read from it.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(CompressedFile::new(file, Compression::Zstd))`. Data is compressed
+/// before it hits the disk, while the number of written and committed bytes, as well as
+/// the data seen by readers, refer to the uncompressed data.
+///
+/// Committing data by flushing the writer also flushes the compressor, so that readers
+/// can decompress all committed data; frequent commits therefore reduce the compression
+/// ratio. Shut the writer down (see [`ShutdownBehavior`](crate::ShutdownBehavior)) to
+/// terminate the compressed stream on disk.
+#[pin_project]
+pub struct CompressedFile<T> {
+    /// The compressing or decompressing stream.
+    #[pin]
+    stream: Stream<T>,
+    /// The compression format.
+    compression: Compression,
+}
+
+/// The stream of a [`CompressedFile`], depending on how the file was opened.
+#[pin_project(project = StreamProjection)]
+enum Stream<T> {
+    /// The original file, which is neither read nor written.
+    Original(#[pin] T),
+    /// A file opened for writing in the gzip format.
+    GzipEncoder(#[pin] GzipEncoder<T>),
+    /// A file opened for writing in the Zstandard format.
+    ZstdEncoder(#[pin] ZstdEncoder<T>),
+    /// A file opened for reading in the gzip format.
+    GzipDecoder(#[pin] GzipDecoder<BufReader<T>>),
+    /// A file opened for reading in the Zstandard format.
+    ZstdDecoder(#[pin] ZstdDecoder<BufReader<T>>),
+}
+
+impl<T> CompressedFile<T> {
+    /// Wraps the file, compressing it using the specified format.
+    pub fn new(file: T, compression: Compression) -> Self {
+        Self {
+            stream: Stream::Original(file),
+            compression,
+        }
+    }
+
+    /// Gets the compression format.
+    pub fn compression(&self) -> Compression {
+        self.compression
+    }
+}
+
+impl<T> CompressedFile<T>
+where
+    T: AsyncRead,
+{
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &T {
+        match &self.stream {
+            Stream::Original(file) => file,
+            Stream::GzipEncoder(encoder) => encoder.get_ref(),
+            Stream::ZstdEncoder(encoder) => encoder.get_ref(),
+            Stream::GzipDecoder(decoder) => decoder.get_ref().get_ref(),
+            Stream::ZstdDecoder(decoder) => decoder.get_ref().get_ref(),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for CompressedFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + AsyncRead + AsyncWrite + Send + Sync,
+{
+    type ReadHandle = CompressedFile<T>;
+    type WriteHandle = CompressedFile<T>;
+    type OpenError = T::OpenError;
+    type SyncError = T::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        let file = BufReader::new(self.get_ref().open_ro().await?);
+        let stream = match self.compression {
+            Compression::Gzip => Stream::GzipDecoder(GzipDecoder::new(file)),
+            Compression::Zstd => Stream::ZstdDecoder(ZstdDecoder::new(file)),
+        };
+        Ok(Self {
+            stream,
+            compression: self.compression,
+        })
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        let file = self.get_ref().open_rw().await?;
+        let stream = match self.compression {
+            Compression::Gzip => Stream::GzipEncoder(GzipEncoder::new(file)),
+            Compression::Zstd => Stream::ZstdEncoder(ZstdEncoder::new(file)),
+        };
+        Ok(Self {
+            stream,
+            compression: self.compression,
+        })
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.get_ref().sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.get_ref().sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.get_ref().diagnostic_path()
+    }
+}
+
+impl<T> FilePath for CompressedFile<T>
+where
+    T: FilePath + AsyncRead,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.get_ref().file_path()
+    }
+}
+
+impl<T> AsyncRead for CompressedFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.project().stream.project() {
+            StreamProjection::GzipDecoder(decoder) => decoder.poll_read(cx, buf),
+            StreamProjection::ZstdDecoder(decoder) => decoder.poll_read(cx, buf),
+            _ => Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for reading",
+            ))),
+        }
+    }
+}
+
+impl<T> AsyncWrite for CompressedFile<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.project().stream.project() {
+            StreamProjection::GzipEncoder(encoder) => encoder.poll_write(cx, buf),
+            StreamProjection::ZstdEncoder(encoder) => encoder.poll_write(cx, buf),
+            _ => Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for writing",
+            ))),
+        }
+    }
+
+    /// Flushes the compressor, such that all data written so far can be decompressed.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.project().stream.project() {
+            StreamProjection::GzipEncoder(encoder) => encoder.poll_flush(cx),
+            StreamProjection::ZstdEncoder(encoder) => encoder.poll_flush(cx),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Terminates the compressed stream.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.project().stream.project() {
+            StreamProjection::GzipEncoder(encoder) => encoder.poll_shutdown(cx),
+            StreamProjection::ZstdEncoder(encoder) => encoder.poll_shutdown(cx),
+            _ => Poll::Ready(Ok(())),
+        }
+    }
+}
@@ -0,0 +1,113 @@
+//! Copying functionality, notably [`SharedFileWriter::copy_from`] and [`SharedFile::spool_from`].
+
+use crate::errors::CompleteWritingError;
+use crate::{SharedFile, SharedFileType, SharedFileWriter};
+use std::io::ErrorKind;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+use tokio::task::JoinHandle;
+
+/// The maximum size of the buffer used for copying.
+const COPY_BUFFER_SIZE: usize = 64 * 1024;
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    W: SharedFileType + AsyncWrite + Unpin,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Copies all data from `src` into the file and completes it.
+    ///
+    /// The copied data is committed, waking up readers, every `commit_every` bytes.
+    /// If reading from `src` fails, the file is marked as failed so that readers
+    /// do not mistake the partial data for the complete file.
+    ///
+    /// Returns the number of bytes copied.
+    ///
+    /// ## Arguments
+    ///
+    /// * `src` - The source to copy from.
+    /// * `commit_every` - The number of bytes after which to commit.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `commit_every` is zero.
+    pub async fn copy_from<R>(
+        mut self,
+        src: &mut R,
+        commit_every: u64,
+    ) -> Result<u64, CompleteWritingError>
+    where
+        R: AsyncRead + Unpin + ?Sized,
+    {
+        assert_ne!(commit_every, 0, "The commit interval must not be zero");
+
+        let buffer_size = usize::try_from(commit_every)
+            .map_or(COPY_BUFFER_SIZE, |size| size.min(COPY_BUFFER_SIZE));
+        let mut buffer = vec![0; buffer_size];
+        let mut copied: u64 = 0;
+        let mut uncommitted: u64 = 0;
+        loop {
+            let read = match src.read(&mut buffer).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                Err(e) => {
+                    self.fail(&e);
+                    return Err(e.into());
+                }
+            };
+
+            self.write_all(&buffer[..read]).await?;
+            copied += read as u64;
+            uncommitted += read as u64;
+
+            if uncommitted >= commit_every {
+                self.flush().await?;
+                uncommitted = 0;
+            }
+        }
+
+        self.complete().await?;
+        Ok(copied)
+    }
+}
+
+impl<T> SharedFile<T>
+where
+    T: SharedFileType + Send + Sync + 'static,
+    T::WriteHandle: SharedFileType + AsyncWrite + Unpin + Send + Sync + 'static,
+    <T::WriteHandle as SharedFileType>::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Spools all data from `src` into the file in a background task.
+    ///
+    /// This returns as soon as the writer was created, so that readers can be created
+    /// immediately and consume the data while it is being written. The returned task
+    /// resolves to the result of [`SharedFileWriter::copy_from`].
+    ///
+    /// To spool a stream of [`Bytes`](bytes::Bytes), e.g. an incoming request body,
+    /// convert it into a reader using `tokio_util::io::StreamReader` first.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// ## Arguments
+    ///
+    /// * `src` - The source to copy from.
+    /// * `commit_every` - The number of bytes after which to commit.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `commit_every` is zero.
+    pub async fn spool_from<R>(
+        &self,
+        mut src: R,
+        commit_every: u64,
+    ) -> Result<JoinHandle<Result<u64, CompleteWritingError>>, T::OpenError>
+    where
+        R: AsyncRead + Unpin + Send + 'static,
+    {
+        assert_ne!(commit_every, 0, "The commit interval must not be zero");
+        let writer = self.writer().await?;
+        Ok(tokio::spawn(async move {
+            writer.copy_from(&mut src, commit_every).await
+        }))
+    }
+}
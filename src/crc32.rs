@@ -0,0 +1,96 @@
+//! A CRC-32 (IEEE 802.3) checksum implementation, notably the [`Crc32`] type.
+
+use crate::Digest;
+
+/// The reflected CRC-32 polynomial.
+const POLYNOMIAL: u32 = 0xEDB8_8320;
+
+/// The lookup table for byte-wise processing.
+static TABLE: [u32; 256] = make_table();
+
+const fn make_table() -> [u32; 256] {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ POLYNOMIAL
+            } else {
+                crc >> 1
+            };
+            bit += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+}
+
+/// An incrementally updated CRC-32 (IEEE 802.3) checksum.
+///
+/// As a [`Digest`], the checksum finalizes to its big-endian byte representation.
+#[derive(Debug, Clone, Copy)]
+pub struct Crc32 {
+    /// The current (inverted) checksum state.
+    state: u32,
+}
+
+impl Crc32 {
+    /// Creates a new checksum.
+    pub const fn new() -> Self {
+        Self { state: !0 }
+    }
+
+    /// Updates the checksum with the specified bytes.
+    pub fn update(&mut self, data: &[u8]) {
+        let mut crc = self.state;
+        for &byte in data {
+            crc = TABLE[((crc ^ byte as u32) & 0xFF) as usize] ^ (crc >> 8);
+        }
+        self.state = crc;
+    }
+
+    /// Gets the checksum of all bytes processed so far.
+    pub const fn value(&self) -> u32 {
+        !self.state
+    }
+}
+
+impl Default for Crc32 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Digest for Crc32 {
+    fn update(&mut self, data: &[u8]) {
+        Crc32::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        self.value().to_be_bytes().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_value() {
+        let mut crc = Crc32::new();
+        crc.update(b"123456789");
+        assert_eq!(crc.value(), 0xCBF4_3926);
+    }
+
+    #[test]
+    fn test_incremental() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.value(), 0xCBF4_3926);
+        assert_eq!(Crc32::new().value(), 0);
+    }
+}
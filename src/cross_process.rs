@@ -0,0 +1,296 @@
+//! Sharing the state of a file with other processes, notably [`SharedFile::publish_state`]
+//! and [`SharedFile::spawn_state_follower`].
+
+use crate::{FailureReason, SharedFile, WriteState, WriteStatus};
+use std::fs::File;
+use std::os::fd::AsRawFd;
+use std::os::unix::fs::FileExt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io;
+use tokio::io::ErrorKind;
+use tokio::task::JoinHandle;
+
+/// The magic bytes identifying a state file.
+const MAGIC: &[u8; 4] = b"SHFS";
+
+/// The version of the state file format.
+const VERSION: u8 = 1;
+
+/// The length of the header in the state file.
+///
+/// The header consists of the magic bytes, the version, the state (`0` pending, `1` completed,
+/// `2` failed), two reserved bytes, the committed length as a little-endian `u64`, the ID of
+/// the writing process as a little-endian `u32` and four reserved bytes.
+const HEADER_LEN: usize = 24;
+
+/// The state of writing a file as stored in a state file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SharedState {
+    /// The file is still being written. Contains the number of committed bytes.
+    Pending(u64),
+    /// The file was completed. Contains the size of the file.
+    Completed(u64),
+    /// Writing the file failed.
+    Failed,
+}
+
+impl<T> SharedFile<T> {
+    /// Publishes the state of the file to a state file at the specified path, e.g. a sidecar
+    /// file next to it, such that readers in other processes can follow it using
+    /// [`spawn_state_follower`](Self::spawn_state_follower).
+    ///
+    /// The state file is created if needed, overwritten right away and updated whenever data is
+    /// committed, the file is completed or writing it fails. Updates are protected by an exclusive `flock(2)`;
+    /// errors updating the state file are ignored. The state file is not removed automatically.
+    ///
+    /// Updates are written by a spawned task on the blocking thread pool, such that followers
+    /// holding the lock do not stall the writer. This must be called from within a Tokio runtime.
+    pub async fn publish_state<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref().to_path_buf();
+        // The header is overwritten right away; truncating would expose an empty state file.
+        let file = tokio::task::spawn_blocking(move || {
+            std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(false)
+                .open(path)
+        })
+        .await??;
+
+        let publisher = Arc::new(StatePublisher {
+            file,
+            pid: std::process::id(),
+        });
+        let mut status = self.subscribe();
+        let mut published = SharedState::from(*status.borrow_and_update());
+        let publish = Arc::clone(&publisher);
+        tokio::task::spawn_blocking(move || publish.publish(published)).await??;
+
+        tokio::spawn(async move {
+            // The task ends once the file is completed, failed or dropped.
+            while !published.is_final() && status.changed().await.is_ok() {
+                let state = SharedState::from(*status.borrow_and_update());
+                if state == published {
+                    continue;
+                }
+                let publish = Arc::clone(&publisher);
+                let _ = tokio::task::spawn_blocking(move || publish.publish(state)).await;
+                published = state;
+            }
+        });
+        Ok(())
+    }
+}
+
+impl<T> SharedFile<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Spawns a task following the state file published by a writer in another process using
+    /// [`publish_state`](Self::publish_state), such that readers of this file observe the data
+    /// committed by that writer as if it was written in this process.
+    ///
+    /// The state file is read every `poll_interval` under a shared `flock(2)`. If the writing
+    /// process exits before completing the file, or the state file cannot be read, the file
+    /// is marked as failed. The task ends once the file is completed, failed or dropped.
+    ///
+    /// No writer must be created for this file. This must be called from within a Tokio runtime.
+    pub async fn spawn_state_follower<P: AsRef<Path>>(
+        &self,
+        path: P,
+        poll_interval: Duration,
+    ) -> io::Result<JoinHandle<()>> {
+        let path = path.as_ref().to_path_buf();
+        let file = Arc::new(tokio::task::spawn_blocking(move || File::open(path)).await??);
+
+        // The follower must not keep the file alive.
+        let sentinel = Arc::downgrade(&self.sentinel);
+        Ok(tokio::spawn(async move {
+            loop {
+                let read = Arc::clone(&file);
+                let result = match tokio::task::spawn_blocking(move || read_header(&read)).await {
+                    Ok(result) => result,
+                    Err(e) => Err(io::Error::from(e)),
+                };
+
+                let Some(sentinel) = sentinel.upgrade() else {
+                    return;
+                };
+                let state = match result {
+                    // The writer did not publish its state yet.
+                    Ok(None) => SharedState::Pending(0),
+                    Ok(Some((state, pid))) if !process_exists(pid) => match state {
+                        SharedState::Pending(_) => {
+                            let error = io::Error::new(
+                                ErrorKind::BrokenPipe,
+                                "The writing process exited before completing the file",
+                            );
+                            sentinel.fail_with(FailureReason::Io(Arc::new(error)));
+                            return;
+                        }
+                        state => state,
+                    },
+                    Ok(Some((state, _pid))) => state,
+                    Err(e) => {
+                        sentinel.fail_with_error(&e);
+                        return;
+                    }
+                };
+
+                match state {
+                    SharedState::Pending(committed) => {
+                        if !sentinel.state.load().is_pending_with(committed) {
+                            sentinel
+                                .state
+                                .store(WriteState::Pending(committed, committed));
+                            sentinel.wake_readers();
+                        }
+                    }
+                    SharedState::Completed(len) => {
+                        sentinel.state.store(WriteState::Completed(len));
+                        sentinel.wake_readers();
+                        return;
+                    }
+                    SharedState::Failed => {
+                        let error = io::Error::new(
+                            ErrorKind::Other,
+                            "Writing the file failed in another process",
+                        );
+                        sentinel.fail_with(FailureReason::Io(Arc::new(error)));
+                        return;
+                    }
+                }
+
+                drop(sentinel);
+                tokio::time::sleep(poll_interval).await;
+            }
+        }))
+    }
+}
+
+impl SharedState {
+    /// Determines whether the state no longer changes.
+    fn is_final(self) -> bool {
+        !matches!(self, SharedState::Pending(_))
+    }
+}
+
+impl From<WriteStatus> for SharedState {
+    fn from(status: WriteStatus) -> Self {
+        match status {
+            WriteStatus::Pending { committed, .. } => SharedState::Pending(committed),
+            WriteStatus::Completed { len } => SharedState::Completed(len),
+            WriteStatus::Failed => SharedState::Failed,
+        }
+    }
+}
+
+/// Writes the state of a file to a state file.
+#[derive(Debug)]
+struct StatePublisher {
+    /// The state file.
+    file: File,
+    /// The ID of this process.
+    pid: u32,
+}
+
+impl StatePublisher {
+    /// Writes the header with the specified state under an exclusive lock.
+    fn publish(&self, state: SharedState) -> io::Result<()> {
+        let (tag, len) = match state {
+            SharedState::Pending(committed) => (0, committed),
+            SharedState::Completed(len) => (1, len),
+            SharedState::Failed => (2, 0),
+        };
+        let mut header = [0; HEADER_LEN];
+        header[..4].copy_from_slice(MAGIC);
+        header[4] = VERSION;
+        header[5] = tag;
+        header[8..16].copy_from_slice(&len.to_le_bytes());
+        header[16..20].copy_from_slice(&self.pid.to_le_bytes());
+
+        let _lock = FileLock::acquire(&self.file, libc::LOCK_EX)?;
+        self.file.write_all_at(&header, 0)
+    }
+}
+
+/// Reads the header of a state file under a shared lock, returning the state and the ID of
+/// the writing process, or [`None`] if the header was not written yet.
+fn read_header(file: &File) -> io::Result<Option<(SharedState, u32)>> {
+    let mut header = [0; HEADER_LEN];
+    let mut read = 0;
+    {
+        let _lock = FileLock::acquire(file, libc::LOCK_SH)?;
+        while read < HEADER_LEN {
+            match file.read_at(&mut header[read..], read as u64) {
+                Ok(0) => return Ok(None),
+                Ok(count) => read += count,
+                Err(e) if e.kind() == ErrorKind::Interrupted => {}
+                Err(e) => return Err(e),
+            }
+        }
+    }
+
+    if &header[..4] != MAGIC || header[4] != VERSION {
+        return Err(io::Error::new(
+            ErrorKind::InvalidData,
+            "The state file has an unsupported format",
+        ));
+    }
+    let len = u64::from_le_bytes(header[8..16].try_into().expect("invalid slice length"));
+    let pid = u32::from_le_bytes(header[16..20].try_into().expect("invalid slice length"));
+    let state = match header[5] {
+        0 => SharedState::Pending(len),
+        1 => SharedState::Completed(len),
+        2 => SharedState::Failed,
+        _ => {
+            return Err(io::Error::new(
+                ErrorKind::InvalidData,
+                "The state file contains an invalid state",
+            ))
+        }
+    };
+    Ok(Some((state, pid)))
+}
+
+/// Determines whether the process with the specified ID still exists.
+fn process_exists(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // SAFETY: Sending no signal only checks whether the process exists.
+    let result = unsafe { libc::kill(pid, 0) };
+    result == 0 || io::Error::last_os_error().raw_os_error() != Some(libc::ESRCH)
+}
+
+/// An advisory lock on a file, released when dropped.
+struct FileLock<'a> {
+    /// The locked file.
+    file: &'a File,
+}
+
+impl<'a> FileLock<'a> {
+    /// Acquires a lock of the specified kind, waiting for conflicting locks to be released.
+    fn acquire(file: &'a File, operation: libc::c_int) -> io::Result<Self> {
+        loop {
+            // SAFETY: The file descriptor is valid for the lifetime of the file.
+            if unsafe { libc::flock(file.as_raw_fd(), operation) } == 0 {
+                return Ok(Self { file });
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+}
+
+impl Drop for FileLock<'_> {
+    fn drop(&mut self) {
+        // SAFETY: The file descriptor is valid for the lifetime of the file.
+        unsafe { libc::flock(self.file.as_raw_fd(), libc::LOCK_UN) };
+    }
+}
@@ -0,0 +1,145 @@
+//! Digest functionality, see [`SharedFileReader::verify_digest`] and [`SharedFileWriter::with_digest`].
+
+use crate::{Digest, Sentinel, SharedFile, SharedFileReader, SharedFileWriter, WriteState};
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Enables computing a digest of the written data, e.g. for use as an ETag.
+    ///
+    /// The digest is finalized when the writer completes and is then available via
+    /// [`SharedFile::digest`]. Like the checksum of [`with_checksum`](Self::with_checksum),
+    /// it only covers sequentially written data and is discarded when data is written
+    /// anywhere but at the end of the file.
+    ///
+    /// This must be called before any data is written.
+    ///
+    /// ## Arguments
+    ///
+    /// * `digest` - The digest to compute, e.g. [`Crc32`](crate::Crc32).
+    pub fn with_digest<D>(mut self, digest: D) -> Self
+    where
+        D: Digest + 'static,
+    {
+        debug_assert!(
+            matches!(self.sentinel().state.load(), WriteState::Pending(0, 0)),
+            "The digest must be tracked before any data is written"
+        );
+        self.set_digest(Box::new(digest));
+        self
+    }
+}
+
+impl<T> SharedFile<T> {
+    /// Gets the digest of the written data, if the writer tracked one using
+    /// [`SharedFileWriter::with_digest`] and completed.
+    pub fn digest(&self) -> Option<Vec<u8>> {
+        self.sentinel
+            .digest
+            .lock()
+            .expect("failed to lock digest for reading")
+            .clone()
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Publishes the finalized digest of the written data.
+    pub(crate) fn set_digest(&self, digest: Vec<u8>) {
+        *self
+            .digest
+            .lock()
+            .expect("failed to lock digest for writing") = Some(digest);
+    }
+
+    /// Discards the digest, e.g. after the file was modified.
+    pub(crate) fn clear_digest(&self) {
+        *self
+            .digest
+            .lock()
+            .expect("failed to lock digest for writing") = None;
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Enables verification of the data read against an expected digest.
+    ///
+    /// The digest is updated incrementally with all data read and compared with the
+    /// expected value when the reader reaches the end of the file. A mismatch is reported
+    /// as an [`InvalidData`](std::io::ErrorKind::InvalidData) error instead of the end of the file.
+    ///
+    /// This must be called before any data is read. Forked readers do not verify the digest.
+    ///
+    /// ## Arguments
+    ///
+    /// * `digest` - The digest to compute, e.g. [`Crc32`](crate::Crc32).
+    /// * `expected` - The expected value of the finalized digest.
+    pub fn verify_digest<D>(mut self, digest: D, expected: impl Into<Vec<u8>>) -> Self
+    where
+        D: Digest + 'static,
+    {
+        debug_assert_eq!(
+            self.position(),
+            0,
+            "The digest must be verified from the start"
+        );
+        self.set_digest_check(DigestCheck::new(Box::new(digest), expected.into()));
+        self
+    }
+}
+
+/// The state of a reader verifying a digest.
+pub(crate) struct DigestCheck {
+    /// The digest being computed, until it is finalized.
+    digest: Option<Box<dyn Digest>>,
+    /// The expected value of the digest.
+    expected: Vec<u8>,
+    /// Whether the finalized digest matched the expected value.
+    matched: bool,
+}
+
+impl DigestCheck {
+    fn new(digest: Box<dyn Digest>, expected: Vec<u8>) -> Self {
+        Self {
+            digest: Some(digest),
+            expected,
+            matched: false,
+        }
+    }
+
+    /// Updates the digest with the specified bytes.
+    pub(crate) fn update(&mut self, data: &[u8]) {
+        if let Some(digest) = self.digest.as_mut() {
+            digest.update(data);
+        }
+    }
+
+    /// Finalizes the digest and determines whether it matches the expected value.
+    pub(crate) fn verify(&mut self) -> bool {
+        if let Some(digest) = self.digest.take() {
+            self.matched = digest.finalize() == self.expected;
+        }
+        self.matched
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "sha2")))]
+#[cfg(feature = "sha2")]
+impl Digest for sha2::Sha256 {
+    fn update(&mut self, data: &[u8]) {
+        sha2::Digest::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        sha2::Digest::finalize(*self).to_vec()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "blake3")))]
+#[cfg(feature = "blake3")]
+impl Digest for blake3::Hasher {
+    fn update(&mut self, data: &[u8]) {
+        blake3::Hasher::update(self, data);
+    }
+
+    fn finalize(self: Box<Self>) -> Vec<u8> {
+        blake3::Hasher::finalize(&self).as_bytes().to_vec()
+    }
+}
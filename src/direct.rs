@@ -0,0 +1,379 @@
+//! Direct I/O bypassing the page cache, notably the [`DirectFile`] type.
+
+use crate::{FilePath, SharedFile, SharedFileType};
+use std::alloc::{alloc_zeroed, dealloc, handle_alloc_error, Layout};
+use std::future::Future;
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::fs::OpenOptions;
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::task::JoinHandle;
+
+/// The alignment of the offsets, lengths and buffers of direct I/O.
+pub const DIRECT_IO_ALIGNMENT: usize = 4096;
+
+/// The size of the buffer of each handle, a multiple of [`DIRECT_IO_ALIGNMENT`].
+const BUFFER_SIZE: usize = 64 * 1024;
+
+/// A type alias for a [`SharedFile`] wrapping a [`DirectFile`].
+pub type SharedDirectFile = SharedFile<DirectFile>;
+
+/// A file opened with `O_DIRECT`, usable as a backend of a [`SharedFile`] for workloads
+/// that must bypass the page cache, see `open(2)`.
+///
+/// Direct I/O requires offsets, lengths and buffers to be aligned to the logical block size
+/// of the device. Each handle therefore reads and writes whole blocks of a buffer aligned to
+/// [`DIRECT_IO_ALIGNMENT`] bytes, using positional I/O on a blocking thread.
+///
+/// Committing data by flushing the writer writes the last partial block padded with zeros,
+/// which readers do not see; [`SharedFileType::sync_all`], e.g. when completing the writer,
+/// truncates the file to the written length. Requires a file system supporting `O_DIRECT`,
+/// which excludes tmpfs.
+#[derive(Debug)]
+pub struct DirectFile {
+    /// The state shared by all handles.
+    inner: Arc<Inner>,
+    /// The buffer, unless an operation using it is in progress.
+    buf: Option<AlignedBuf>,
+    /// The offset of the buffer in the file, a multiple of the alignment.
+    offset: u64,
+    /// The number of valid bytes in the buffer.
+    filled: usize,
+    /// The number of bytes of the buffer written to the file.
+    flushed: usize,
+    /// The position of this handle when reading.
+    position: u64,
+    /// The operation in progress, if any.
+    op: Option<(Op, OpTask)>,
+}
+
+/// The state shared by all handles of a [`DirectFile`].
+#[derive(Debug)]
+struct Inner {
+    /// The file opened with `O_DIRECT`.
+    file: std::fs::File,
+    /// The path of the file.
+    path: PathBuf,
+    /// The number of bytes written to the file, excluding padding.
+    len: AtomicU64,
+}
+
+/// A blocking task performing an operation, returning the buffer and the number of bytes.
+type OpTask = JoinHandle<(AlignedBuf, io::Result<usize>)>;
+
+/// An operation of a [`DirectFile`] handle in progress.
+#[derive(Debug, Clone, Copy)]
+enum Op {
+    /// Writing the full buffer, after which the next block is buffered.
+    WriteFull,
+    /// Writing the partially filled buffer, padded to the alignment.
+    WritePartial,
+    /// Reading into the buffer at the offset, when at most `len` bytes were written.
+    Read { offset: u64, len: u64 },
+}
+
+/// A heap buffer aligned to [`DIRECT_IO_ALIGNMENT`].
+struct AlignedBuf {
+    /// The start of the buffer.
+    ptr: NonNull<u8>,
+}
+
+// SAFETY: The buffer exclusively owns its memory.
+unsafe impl Send for AlignedBuf {}
+
+// SAFETY: The buffer is only mutated through exclusive references.
+unsafe impl Sync for AlignedBuf {}
+
+impl AlignedBuf {
+    /// The layout of the buffer.
+    const LAYOUT: Layout = match Layout::from_size_align(BUFFER_SIZE, DIRECT_IO_ALIGNMENT) {
+        Ok(layout) => layout,
+        Err(_) => panic!("invalid buffer layout"),
+    };
+
+    /// Allocates a zeroed buffer.
+    fn new() -> Self {
+        // SAFETY: The layout has a non-zero size.
+        let ptr = unsafe { alloc_zeroed(Self::LAYOUT) };
+        let Some(ptr) = NonNull::new(ptr) else {
+            handle_alloc_error(Self::LAYOUT)
+        };
+        Self { ptr }
+    }
+
+    /// Gets the contents of the buffer.
+    fn as_slice(&self) -> &[u8] {
+        // SAFETY: The buffer owns `BUFFER_SIZE` initialized bytes.
+        unsafe { std::slice::from_raw_parts(self.ptr.as_ptr(), BUFFER_SIZE) }
+    }
+
+    /// Gets the contents of the buffer for writing.
+    fn as_mut_slice(&mut self) -> &mut [u8] {
+        // SAFETY: The buffer owns `BUFFER_SIZE` initialized bytes.
+        unsafe { std::slice::from_raw_parts_mut(self.ptr.as_ptr(), BUFFER_SIZE) }
+    }
+}
+
+impl Drop for AlignedBuf {
+    fn drop(&mut self) {
+        // SAFETY: The buffer was allocated with the same layout.
+        unsafe { dealloc(self.ptr.as_ptr(), Self::LAYOUT) }
+    }
+}
+
+impl std::fmt::Debug for AlignedBuf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AlignedBuf").finish_non_exhaustive()
+    }
+}
+
+/// Rounds the length up to the alignment.
+fn align_up(len: usize) -> usize {
+    (len + DIRECT_IO_ALIGNMENT - 1) / DIRECT_IO_ALIGNMENT * DIRECT_IO_ALIGNMENT
+}
+
+impl DirectFile {
+    /// Creates the file at the specified path with `O_DIRECT`, truncating it if it
+    /// already exists.
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .custom_flags(libc::O_DIRECT)
+            .open(&path)
+            .await?
+            .into_std()
+            .await;
+        Ok(Self::handle(Arc::new(Inner {
+            file,
+            path,
+            len: AtomicU64::new(0),
+        })))
+    }
+
+    /// Gets the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Creates a handle to the file, positioned at its start.
+    fn handle(inner: Arc<Inner>) -> Self {
+        Self {
+            inner,
+            buf: Some(AlignedBuf::new()),
+            offset: 0,
+            filled: 0,
+            flushed: 0,
+            position: 0,
+            op: None,
+        }
+    }
+
+    /// Starts an operation using the buffer on a blocking thread.
+    fn start(
+        &mut self,
+        op: Op,
+        f: impl FnOnce(&std::fs::File, &mut AlignedBuf) -> io::Result<usize> + Send + 'static,
+    ) {
+        let inner = Arc::clone(&self.inner);
+        let mut buf = self.buf.take().expect("no operation is in progress");
+        let task = tokio::task::spawn_blocking(move || {
+            let result = f(&inner.file, &mut buf);
+            (buf, result)
+        });
+        self.op = Some((op, task));
+    }
+
+    /// Waits for the operation in progress, if any, to complete.
+    fn poll_op(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let Some((op, task)) = self.op.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        let op = *op;
+        let (buf, result) = ready!(Pin::new(task).poll(cx))?;
+        self.op = None;
+        self.buf = Some(buf);
+        let count = result?;
+
+        match op {
+            Op::WriteFull => {
+                let end = self.offset + BUFFER_SIZE as u64;
+                self.inner.len.fetch_max(end, Ordering::AcqRel);
+                self.offset = end;
+                self.filled = 0;
+                self.flushed = 0;
+            }
+            Op::WritePartial => {
+                let end = self.offset + self.filled as u64;
+                self.inner.len.fetch_max(end, Ordering::AcqRel);
+                self.flushed = self.filled;
+            }
+            Op::Read { offset, len } => {
+                // Only data written when reading started is valid; the rest may be padding.
+                self.offset = offset;
+                self.filled = count.min(len.saturating_sub(offset) as usize);
+            }
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    /// Starts writing the buffer at its offset, padded to the alignment.
+    fn start_write(&mut self, op: Op) {
+        let offset = self.offset;
+        let len = align_up(self.filled);
+        self.start(op, move |file, buf| {
+            file.write_all_at(&buf.as_slice()[..len], offset)?;
+            Ok(len)
+        });
+    }
+}
+
+impl Inner {
+    /// Truncates the file to the written length, removing padding, and syncs it.
+    fn sync(&self, metadata: bool) -> io::Result<()> {
+        self.file.set_len(self.len.load(Ordering::Acquire))?;
+        if metadata {
+            self.file.sync_all()
+        } else {
+            self.file.sync_data()
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for DirectFile {
+    type ReadHandle = DirectFile;
+    type WriteHandle = DirectFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(Self::handle(Arc::clone(&self.inner)))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(Self::handle(Arc::clone(&self.inner)))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.sync(true)).await?
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.sync(false)).await?
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.inner.path)
+    }
+}
+
+impl FilePath for DirectFile {
+    fn file_path(&self) -> &PathBuf {
+        &self.inner.path
+    }
+}
+
+impl AsyncRead for DirectFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_op(cx))?;
+
+        let mut refreshed = false;
+        loop {
+            let end = this.offset + this.filled as u64;
+            if this.position >= this.offset && this.position < end {
+                let start = (this.position - this.offset) as usize;
+                let data = &this
+                    .buf
+                    .as_ref()
+                    .expect("the buffer is available")
+                    .as_slice()[start..this.filled];
+                let len = data.len().min(buf.remaining());
+                buf.put_slice(&data[..len]);
+                this.position += len as u64;
+                return Poll::Ready(Ok(()));
+            }
+
+            // Nothing is left to read at the end of the file.
+            if refreshed {
+                return Poll::Ready(Ok(()));
+            }
+
+            let offset = this.position - this.position % DIRECT_IO_ALIGNMENT as u64;
+            let len = this.inner.len.load(Ordering::Acquire);
+            this.start(Op::Read { offset, len }, move |file, buf| {
+                let mut read = 0;
+                while read < BUFFER_SIZE {
+                    match file.read_at(&mut buf.as_mut_slice()[read..], offset + read as u64) {
+                        Ok(0) => break,
+                        Ok(count) => read += count,
+                        Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                        Err(e) => return Err(e),
+                    }
+                    // Reads are aligned, so a partial read marks the end of the file.
+                    if read % DIRECT_IO_ALIGNMENT != 0 {
+                        break;
+                    }
+                }
+                Ok(read)
+            });
+            ready!(this.poll_op(cx))?;
+            refreshed = true;
+        }
+    }
+}
+
+impl AsyncWrite for DirectFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        ready!(this.poll_op(cx))?;
+
+        let len = buf.len().min(BUFFER_SIZE - this.filled);
+        let filled = this.filled;
+        this.buf
+            .as_mut()
+            .expect("the buffer is available")
+            .as_mut_slice()[filled..filled + len]
+            .copy_from_slice(&buf[..len]);
+        this.filled += len;
+        if this.filled == BUFFER_SIZE {
+            // The write completes in the background; the next operation waits for it.
+            this.start_write(Op::WriteFull);
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_op(cx))?;
+        if this.filled > this.flushed {
+            this.start_write(Op::WritePartial);
+            ready!(this.poll_op(cx))?;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
@@ -0,0 +1,110 @@
+//! Double-buffered reading functionality, notably the [`DoubleBufferedReader`] type.
+
+use crate::SharedFileReader;
+use bytes::{BufMut, Bytes, BytesMut};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncReadExt};
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+
+/// A reader adapter that fills the next buffer in the background while
+/// the previous one is processed by the consumer.
+///
+/// This overlaps disk I/O with downstream processing. At most two buffers are in flight:
+/// one handed to the consumer and one being filled. The next buffer is only filled once the
+/// consumer took the previous one. See [`SharedFileReader::double_buffered`].
+#[derive(Debug)]
+pub struct DoubleBufferedReader {
+    /// The receiver of filled buffers.
+    receiver: mpsc::Receiver<io::Result<Bytes>>,
+    /// The background task filling the buffers.
+    task: JoinHandle<()>,
+}
+
+impl<T, R> SharedFileReader<T, R>
+where
+    T: Send + Sync + 'static,
+    R: AsyncRead + Send + Sync + Unpin + 'static,
+{
+    /// Converts the reader into a [`DoubleBufferedReader`] that reads ahead in a background task.
+    ///
+    /// This must be called from within a Tokio runtime.
+    ///
+    /// ## Arguments
+    ///
+    /// * `buffer_size` - The size of each buffer. Only the last buffer of a completed file may be shorter.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `buffer_size` is zero.
+    pub fn double_buffered(self, buffer_size: usize) -> DoubleBufferedReader {
+        assert_ne!(buffer_size, 0, "The buffer size must not be zero");
+        let (sender, receiver) = mpsc::channel(1);
+        let task = tokio::spawn(fill_buffers(self, buffer_size, sender));
+        DoubleBufferedReader { receiver, task }
+    }
+}
+
+impl DoubleBufferedReader {
+    /// Gets the next filled buffer, waiting for the writer to commit enough data.
+    ///
+    /// Returns [`None`] once the file was read completely or after an error was returned.
+    ///
+    /// ## Cancel safety
+    ///
+    /// This method is cancel safe; no data is lost if it is cancelled.
+    pub async fn next_buffer(&mut self) -> io::Result<Option<Bytes>> {
+        self.receiver.recv().await.transpose()
+    }
+}
+
+impl Drop for DoubleBufferedReader {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// Fills buffers from the reader and sends them to the consumer until the file
+/// was read completely, an error occurred or the consumer was dropped.
+async fn fill_buffers<T, R>(
+    mut reader: SharedFileReader<T, R>,
+    buffer_size: usize,
+    sender: mpsc::Sender<io::Result<Bytes>>,
+) where
+    R: AsyncRead + Unpin,
+{
+    let mut buffer = BytesMut::new();
+    loop {
+        // Waits until the consumer took the previous buffer before filling the next one.
+        let Ok(permit) = sender.reserve().await else {
+            return;
+        };
+
+        // Reclaims the allocation of previously sent buffers once the consumer dropped them.
+        buffer.reserve(buffer_size);
+
+        let mut end_of_file = false;
+        while buffer.len() < buffer_size {
+            let remaining = buffer_size - buffer.len();
+            match reader.read_buf(&mut (&mut buffer).limit(remaining)).await {
+                Ok(0) => {
+                    end_of_file = true;
+                    break;
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    permit.send(Err(e));
+                    return;
+                }
+            }
+        }
+
+        if !buffer.is_empty() {
+            permit.send(Ok(buffer.split().freeze()));
+        }
+
+        if end_of_file {
+            return;
+        }
+    }
+}
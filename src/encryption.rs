@@ -0,0 +1,367 @@
+//! Encryption at rest, notably the [`EncryptedFile`] type.
+
+use crate::{FilePath, SharedFileType};
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng};
+use chacha20poly1305::{Key, XChaCha20Poly1305, XNonce};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The maximum number of bytes encrypted into a single chunk.
+pub const ENCRYPTION_CHUNK_SIZE: usize = 64 * 1024;
+
+/// The length of the nonce stored at the beginning of the file.
+const NONCE_LEN: usize = 24;
+
+/// The length of the big-endian length prefix of each chunk.
+const LEN_PREFIX_LEN: usize = 4;
+
+/// A file encrypting all data written to it and decrypting all data read from it, using
+/// XChaCha20-Poly1305 in chunks of up to [`ENCRYPTION_CHUNK_SIZE`] bytes.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(EncryptedFile::with_random_key(file))`. The number of written and committed
+/// bytes, as well as the data seen by readers, refer to the decrypted data.
+///
+/// Committing data by flushing the writer seals the current chunk, such that commits are
+/// aligned to chunk boundaries and readers can authenticate all committed data. Frequent
+/// commits therefore increase the overhead of 20 bytes per chunk.
+#[pin_project]
+pub struct EncryptedFile<T> {
+    /// The file to read from or write to.
+    #[pin]
+    file: T,
+    /// The cipher used for all chunks.
+    cipher: XChaCha20Poly1305,
+    /// The state of encrypting or decrypting the file, depending on how it was opened.
+    mode: Mode,
+}
+
+/// The mode of an [`EncryptedFile`], depending on how the file was opened.
+enum Mode {
+    /// The original file, which is neither read nor written.
+    Original,
+    /// The file was opened for writing.
+    Sealing(Sealer),
+    /// The file was opened for reading.
+    Opening(Opener),
+}
+
+/// The state of encrypting a file.
+struct Sealer {
+    /// The nonce of the file, from which the nonce of each chunk is derived.
+    nonce: [u8; NONCE_LEN],
+    /// The index of the next chunk.
+    chunk: u64,
+    /// The data of the current chunk, not yet encrypted.
+    plain: Vec<u8>,
+    /// The encrypted data not yet written to the file.
+    sealed: Vec<u8>,
+    /// The number of bytes of `sealed` already written to the file.
+    sealed_written: usize,
+}
+
+/// The state of decrypting a file.
+struct Opener {
+    /// The nonce of the file, once read.
+    nonce: Option<[u8; NONCE_LEN]>,
+    /// The index of the next chunk.
+    chunk: u64,
+    /// The encrypted data of the nonce or the current chunk read so far.
+    sealed: Vec<u8>,
+    /// The decrypted data of the current chunk.
+    plain: Vec<u8>,
+    /// The number of bytes of `plain` already read.
+    plain_read: usize,
+}
+
+impl<T> EncryptedFile<T> {
+    /// Wraps the file, encrypting it using the specified 256-bit key.
+    pub fn new(file: T, key: [u8; 32]) -> Self {
+        Self {
+            file,
+            cipher: XChaCha20Poly1305::new(Key::from_slice(&key)),
+            mode: Mode::Original,
+        }
+    }
+
+    /// Wraps the file, encrypting it using a randomly generated key that is only kept in memory.
+    ///
+    /// This suits temporary files, whose contents are not needed after the process exits.
+    pub fn with_random_key(file: T) -> Self {
+        Self {
+            file,
+            cipher: XChaCha20Poly1305::new(&XChaCha20Poly1305::generate_key(&mut OsRng)),
+            mode: Mode::Original,
+        }
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &T {
+        &self.file
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for EncryptedFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + AsyncRead + AsyncWrite + Send + Sync,
+{
+    type ReadHandle = EncryptedFile<T>;
+    type WriteHandle = EncryptedFile<T>;
+    type OpenError = T::OpenError;
+    type SyncError = T::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(Self {
+            file: self.file.open_ro().await?,
+            cipher: self.cipher.clone(),
+            mode: Mode::Opening(Opener {
+                nonce: None,
+                chunk: 0,
+                sealed: Vec::new(),
+                plain: Vec::new(),
+                plain_read: 0,
+            }),
+        })
+    }
+
+    /// Opens the file for writing, starting a new encrypted stream with a random nonce.
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        let mut nonce = [0; NONCE_LEN];
+        nonce.copy_from_slice(&XChaCha20Poly1305::generate_nonce(&mut OsRng));
+        Ok(Self {
+            file: self.file.open_rw().await?,
+            cipher: self.cipher.clone(),
+            mode: Mode::Sealing(Sealer {
+                nonce,
+                chunk: 0,
+                plain: Vec::with_capacity(ENCRYPTION_CHUNK_SIZE),
+                sealed: nonce.to_vec(),
+                sealed_written: 0,
+            }),
+        })
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.file.diagnostic_path()
+    }
+}
+
+impl<T> FilePath for EncryptedFile<T>
+where
+    T: FilePath,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.file.file_path()
+    }
+}
+
+/// Derives the nonce of the chunk with the specified index from the nonce of the file.
+fn chunk_nonce(nonce: &[u8; NONCE_LEN], chunk: u64) -> [u8; NONCE_LEN] {
+    let mut chunk_nonce = *nonce;
+    for (byte, index) in chunk_nonce[NONCE_LEN - 8..]
+        .iter_mut()
+        .zip(chunk.to_be_bytes())
+    {
+        *byte ^= index;
+    }
+    chunk_nonce
+}
+
+impl Sealer {
+    /// Encrypts the current chunk, if any data was written to it.
+    fn seal(&mut self, cipher: &XChaCha20Poly1305) -> io::Result<()> {
+        if self.plain.is_empty() {
+            return Ok(());
+        }
+
+        let nonce = chunk_nonce(&self.nonce, self.chunk);
+        let chunk = cipher
+            .encrypt(XNonce::from_slice(&nonce), &self.plain[..])
+            .map_err(|_| Error::new(ErrorKind::Other, "Failed to encrypt the chunk"))?;
+        let len = u32::try_from(chunk.len()).expect("the chunk size is bounded");
+        self.sealed.extend_from_slice(&len.to_be_bytes());
+        self.sealed.extend_from_slice(&chunk);
+        self.plain.clear();
+        self.chunk += 1;
+        Ok(())
+    }
+
+    /// Writes all encrypted data to the file.
+    fn poll_drain<T>(&mut self, mut file: Pin<&mut T>, cx: &mut Context<'_>) -> Poll<io::Result<()>>
+    where
+        T: AsyncWrite,
+    {
+        while self.sealed_written < self.sealed.len() {
+            let written = ready!(file
+                .as_mut()
+                .poll_write(cx, &self.sealed[self.sealed_written..]))?;
+            if written == 0 {
+                return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+            }
+            self.sealed_written += written;
+        }
+
+        self.sealed.clear();
+        self.sealed_written = 0;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl Opener {
+    /// Gets the number of encrypted bytes needed to decrypt the next chunk, or to read the nonce.
+    fn needed(&self) -> usize {
+        if self.nonce.is_none() {
+            return NONCE_LEN;
+        }
+        match self.sealed.get(..LEN_PREFIX_LEN) {
+            Some(prefix) => {
+                let len = u32::from_be_bytes(prefix.try_into().expect("the prefix has four bytes"));
+                LEN_PREFIX_LEN + len as usize
+            }
+            None => LEN_PREFIX_LEN,
+        }
+    }
+
+    /// Decrypts the chunk read, or stores the nonce read, once all of its bytes were read.
+    fn open(&mut self, cipher: &XChaCha20Poly1305) -> io::Result<()> {
+        let Some(file_nonce) = self.nonce else {
+            let mut nonce = [0; NONCE_LEN];
+            nonce.copy_from_slice(&self.sealed);
+            self.nonce = Some(nonce);
+            self.sealed.clear();
+            return Ok(());
+        };
+
+        let nonce = chunk_nonce(&file_nonce, self.chunk);
+        self.plain = cipher
+            .decrypt(XNonce::from_slice(&nonce), &self.sealed[LEN_PREFIX_LEN..])
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "Failed to decrypt the chunk"))?;
+        self.plain_read = 0;
+        self.sealed.clear();
+        self.chunk += 1;
+        Ok(())
+    }
+}
+
+impl<T> AsyncRead for EncryptedFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let Mode::Opening(opener) = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for reading",
+            )));
+        };
+
+        let mut file = this.file;
+        loop {
+            if opener.plain_read < opener.plain.len() {
+                let plain = &opener.plain[opener.plain_read..];
+                let len = plain.len().min(buf.remaining());
+                buf.put_slice(&plain[..len]);
+                opener.plain_read += len;
+                return Poll::Ready(Ok(()));
+            }
+
+            let read = opener.sealed.len();
+            let needed = opener.needed();
+            if read == needed {
+                opener.open(this.cipher)?;
+                continue;
+            }
+
+            opener.sealed.resize(needed, 0);
+            let mut sealed = ReadBuf::new(&mut opener.sealed[read..]);
+            let poll = file.as_mut().poll_read(cx, &mut sealed);
+            let filled = sealed.filled().len();
+            opener.sealed.truncate(read + filled);
+            ready!(poll)?;
+
+            // Nothing is returned until a chunk is complete, e.g. at the end of the file.
+            if filled == 0 {
+                return Poll::Ready(Ok(()));
+            }
+        }
+    }
+}
+
+impl<T> AsyncWrite for EncryptedFile<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let Mode::Sealing(sealer) = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for writing",
+            )));
+        };
+
+        ready!(sealer.poll_drain(this.file, cx))?;
+        let len = buf.len().min(ENCRYPTION_CHUNK_SIZE - sealer.plain.len());
+        sealer.plain.extend_from_slice(&buf[..len]);
+        if sealer.plain.len() == ENCRYPTION_CHUNK_SIZE {
+            sealer.seal(this.cipher)?;
+        }
+        Poll::Ready(Ok(len))
+    }
+
+    /// Encrypts the current chunk and writes it to the file, such that all data written
+    /// so far can be decrypted.
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let mut file = this.file;
+        if let Mode::Sealing(sealer) = this.mode {
+            sealer.seal(this.cipher)?;
+            ready!(sealer.poll_drain(file.as_mut(), cx))?;
+        }
+        file.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        self.project().file.poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chunk_nonce() {
+        let nonce = [7; NONCE_LEN];
+        assert_eq!(chunk_nonce(&nonce, 0), nonce);
+        assert_ne!(chunk_nonce(&nonce, 1), chunk_nonce(&nonce, 2));
+        assert_eq!(
+            chunk_nonce(&nonce, 1)[..NONCE_LEN - 1],
+            nonce[..NONCE_LEN - 1]
+        );
+    }
+}
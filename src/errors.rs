@@ -1,83 +1,252 @@
 //! Contains commonly used error types.
 
-use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
 use tokio::io;
 
-#[derive(Debug)]
+/// An error completing the writing operation of a file.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum CompleteWritingError {
     /// An I/O error occurred.
-    Io(io::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
     /// Writing to the file failed.
+    #[error("Writing to the file failed")]
     FileWritingFailed,
     /// Failed to synchronize the file with the underlying buffer.
-    SyncError,
+    #[error("Failed to synchronize the file with the underlying buffer")]
+    SyncError(#[source] Box<dyn std::error::Error + Send + Sync>),
+    /// No checksum was tracked for the written data.
+    #[error("No checksum was tracked for the written data")]
+    ChecksumNotTracked,
+    /// The writer was completed with uncommitted data.
+    #[error("The writer was completed with uncommitted data")]
+    UncommittedData,
 }
 
-#[derive(Debug)]
+/// An error writing to a file.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum WriteError {
     /// An I/O error occurred.
-    Io(io::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
     /// The file was already closed
+    #[error("The file was already closed")]
     FileClosed,
 }
 
-#[derive(Debug)]
+/// An error reading from a file.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum ReadError {
     /// An I/O error occurred.
-    Io(io::Error),
+    #[error(transparent)]
+    Io(#[from] io::Error),
     /// The file was already closed
+    #[error("The file was already closed")]
     FileClosed,
+    /// The checksum trailer is missing or malformed.
+    #[error("The checksum trailer is missing or malformed")]
+    InvalidTrailer,
+    /// The data read does not match the checksum trailer.
+    #[error("The data read does not match the checksum trailer")]
+    ChecksumMismatch,
+    /// The data read does not match the expected digest.
+    #[error("The data read does not match the expected digest")]
+    DigestMismatch,
+    /// The file was truncated below the read position.
+    #[error("The file was truncated below the read position")]
+    Truncated,
+    /// The writer was dropped or completed with uncommitted data.
+    #[error("The writer was dropped or completed with uncommitted data")]
+    UncommittedData,
+    /// Writing the file was aborted for the contained reason.
+    #[error("Writing the file was aborted: {0}")]
+    Aborted(#[source] Arc<dyn std::error::Error + Send + Sync>),
+    /// The writer made no progress for the contained duration.
+    #[error("The writer made no progress for {0:?}")]
+    WriterStalled(Duration),
+    /// Writing the file failed with the contained error.
+    #[error("Writing the file failed: {0}")]
+    WriteFailed(#[source] Arc<io::Error>),
+    /// The file was completed without publishing the contained checkpoint.
+    #[error("The file was completed without the checkpoint {0:?}")]
+    CheckpointNotFound(String),
+    /// The block with the contained index does not match its checksum,
+    /// see [`ChecksummedFile`](crate::ChecksummedFile).
+    #[error("The block {0} does not match its checksum")]
+    CorruptBlock(u64),
+    /// The data at the read position was overwritten before it was read,
+    /// see [`RingFile`](crate::RingFile).
+    ///
+    /// Like a lagged broadcast receiver, the reader missed the `lagged` bytes between its
+    /// position and the oldest retained data. It can resume reading the retained data using
+    /// [`SharedFileReader::seek_to_oldest`](crate::SharedFileReader::seek_to_oldest).
+    #[error("The data at position {position} was evicted, the reader lags {lagged} bytes behind the retained data")]
+    Evicted {
+        /// The read position.
+        position: u64,
+        /// The number of evicted bytes the reader did not read.
+        lagged: u64,
+    },
 }
 
-impl Display for CompleteWritingError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CompleteWritingError::Io(io) => write!(f, "{}", io),
-            CompleteWritingError::FileWritingFailed => write!(f, "Writing to the file failed"),
-            CompleteWritingError::SyncError => write!(
-                f,
-                "Failed to synchronize the file with the underlying buffer"
-            ),
-        }
-    }
+/// An error creating a reader using [`SharedFile::try_reader`](crate::SharedFile::try_reader).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum OpenReaderError<E> {
+    /// Opening the underlying file failed.
+    #[error("Opening the file failed")]
+    Open(#[source] E),
+    /// Writing to the file already failed.
+    #[error("Writing to the file failed")]
+    FileWritingFailed,
+}
+
+/// An error creating a writer using [`SharedFile::try_writer`](crate::SharedFile::try_writer)
+/// or [`SharedFile::append_writer`](crate::SharedFile::append_writer).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum OpenWriterError<E> {
+    /// Opening the underlying file failed.
+    #[error("Opening the file failed")]
+    Open(#[source] E),
+    /// An I/O error occurred.
+    #[error("Preparing the file for writing failed")]
+    Io(#[source] io::Error),
+    /// Writing to the file already failed.
+    #[error("Writing to the file failed")]
+    FileWritingFailed,
+    /// Another writer is still active.
+    #[error("Another writer is still active")]
+    WriterExists,
+    /// The range is empty, exceeds the file or overlaps a range claimed by another writer.
+    #[error("The range cannot be claimed for writing")]
+    InvalidRange,
 }
 
-impl Display for WriteError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            WriteError::Io(io) => write!(f, "{}", io),
-            WriteError::FileClosed => write!(f, "The file was already closed"),
-        }
-    }
+/// An error persisting a file using [`SharedFile::persist`](crate::SharedFile::persist)
+/// or [`SharedFileWriter::complete_and_persist`](crate::SharedFileWriter::complete_and_persist).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum PersistError<E> {
+    /// Completing the writing operation failed.
+    #[error("Completing the writing operation failed")]
+    Complete(#[source] CompleteWritingError),
+    /// Moving the file to its destination failed.
+    #[error("Moving the file to its destination failed")]
+    Persist(#[source] E),
+    /// Writing to the file did not complete successfully.
+    #[error("Writing to the file did not complete")]
+    NotCompleted,
+    /// The file was already persisted.
+    #[error("The file was already persisted")]
+    AlreadyPersisted,
+    /// The writer did not track a digest to derive the path from.
+    #[error("The writer did not track a digest")]
+    MissingDigest,
 }
 
-impl Display for ReadError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ReadError::Io(io) => write!(f, "{}", io),
-            ReadError::FileClosed => write!(f, "The file was already closed"),
-        }
-    }
+/// An error sealing a file using [`SharedFile::seal`](crate::SharedFile::seal)
+/// or [`SharedFileWriter::complete_and_seal`](crate::SharedFileWriter::complete_and_seal).
+#[cfg_attr(docsrs, doc(cfg(all(feature = "memfd", target_os = "linux"))))]
+#[cfg(all(feature = "memfd", target_os = "linux"))]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SealError {
+    /// Completing the writing operation failed.
+    #[error("Completing the writing operation failed")]
+    Complete(#[source] CompleteWritingError),
+    /// Writing to the file did not complete successfully.
+    #[error("Writing to the file did not complete")]
+    NotCompleted,
+    /// Adding the seals failed.
+    #[error("Adding the seals failed")]
+    Seal(#[source] io::Error),
 }
 
-impl From<io::Error> for CompleteWritingError {
-    fn from(value: io::Error) -> Self {
-        CompleteWritingError::Io(value)
-    }
+/// An error uploading a file using [`SharedFile::spawn_upload`](crate::SharedFile::spawn_upload).
+#[cfg_attr(docsrs, doc(cfg(feature = "object_store")))]
+#[cfg(feature = "object_store")]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum UploadError {
+    /// Reading the file failed, e.g. because writing it failed.
+    #[error("Reading the file failed")]
+    Read(#[source] io::Error),
+    /// Uploading to the object store failed.
+    #[error("Uploading to the object store failed")]
+    Store(#[from] object_store::Error),
 }
 
-impl From<io::Error> for WriteError {
-    fn from(value: io::Error) -> Self {
-        WriteError::Io(value)
-    }
+/// An error opening or syncing a [`FaultyFile`](crate::FaultyFile).
+#[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+#[cfg(feature = "fault-injection")]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FaultError<E> {
+    /// The operation failed because a fault was injected.
+    #[error("A fault was injected")]
+    Injected(#[source] io::Error),
+    /// The operation of the underlying file failed.
+    #[error("The operation of the underlying file failed")]
+    File(#[source] E),
 }
 
-impl From<io::Error> for ReadError {
-    fn from(value: io::Error) -> Self {
-        ReadError::Io(value)
-    }
+/// An error opening or syncing a [`MirroredFile`](crate::MirroredFile).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MirrorError<P, S> {
+    /// The operation of the primary file failed.
+    #[error("The operation of the primary file failed")]
+    Primary(#[source] P),
+    /// The operation of the secondary file failed.
+    #[error("The operation of the secondary file failed")]
+    Secondary(#[source] S),
 }
 
-impl std::error::Error for CompleteWritingError {}
-impl std::error::Error for WriteError {}
-impl std::error::Error for ReadError {}
+/// An error opening an [`OverlayFile`](crate::OverlayFile).
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum OverlayError<E> {
+    /// Opening the base file failed.
+    #[error("Opening the base file failed")]
+    Base(#[source] io::Error),
+    /// Opening the delta file failed.
+    #[error("Opening the delta file failed")]
+    Delta(#[source] E),
+}
+
+/// An error getting a file using
+/// [`SharedFileRegistry::get_or_create`](crate::SharedFileRegistry::get_or_create).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum RegistryError<E> {
+    /// Creating the file failed.
+    #[error("Creating the file failed")]
+    Create(#[source] E),
+    /// The registry was shut down and no longer hands out files.
+    #[error("The registry was shut down")]
+    ShutDown,
+}
+
+/// An error creating a writer using [`DiskQuota::writer`](crate::DiskQuota::writer).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum QuotaError<E> {
+    /// The reservation exceeds the remaining budget.
+    #[error("Reserving {requested} bytes exceeds the quota, {available} bytes are available")]
+    Exceeded {
+        /// The number of bytes requested.
+        requested: u64,
+        /// The number of bytes available.
+        available: u64,
+    },
+    /// Opening the writer failed.
+    #[error("Opening the writer failed")]
+    Open(#[source] E),
+}
@@ -0,0 +1,224 @@
+//! Event hooks, notably [`SharedFile::add_event_handler`] and [`SharedFile::events`].
+
+use crate::{EventHandler, Sentinel, SharedFile, WriteStatus};
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use tokio::io;
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use uuid::Uuid;
+
+/// The number of events buffered for each [`FileEvents`] stream before the oldest are skipped.
+pub(crate) const EVENT_CAPACITY: usize = 64;
+
+/// A lifecycle event of a file, see [`SharedFile::events`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub enum FileEvent {
+    /// The file was created. This is the first event of every stream.
+    Created,
+    /// A reader with the specified ID was attached to the file.
+    ReaderAttached(Uuid),
+    /// The reader with the specified ID was dropped.
+    ReaderDetached(Uuid),
+    /// The reader with the specified ID fell behind the committed data by more than its lag
+    /// limit, see [`SharedFileReader::with_lag_warning`](crate::SharedFileReader::with_lag_warning).
+    /// Contains the ID of the reader and its lag in bytes.
+    ReaderLagging(Uuid, u64),
+    /// Data was committed. Contains the number of committed bytes.
+    Committed(u64),
+    /// The file was completed. Contains the size of the file in bytes.
+    Completed(u64),
+    /// Writing the file failed. Contains the error readers see.
+    Failed(Arc<io::Error>),
+}
+
+/// A stream of the lifecycle events of a file, see [`SharedFile::events`].
+#[derive(Debug)]
+pub struct FileEvents {
+    /// Whether the [`FileEvent::Created`] event was yielded.
+    created: bool,
+    /// Receives the events of the file.
+    receiver: broadcast::Receiver<FileEvent>,
+}
+
+impl<T> SharedFile<T> {
+    /// Registers a handler notified when data is committed, the file is completed or
+    /// writing it fails, e.g. to trigger follow-up work without a dedicated watcher task.
+    ///
+    /// Handlers are called synchronously by the task changing the state of the file,
+    /// typically the writer, and therefore must not block.
+    pub fn add_event_handler<H>(&self, handler: H)
+    where
+        H: EventHandler + 'static,
+    {
+        self.sentinel
+            .event_handlers
+            .lock()
+            .expect("failed to lock event handlers for writing")
+            .0
+            .push(Arc::new(handler));
+    }
+
+    /// Registers a callback called with the number of committed bytes whenever data
+    /// is committed, see [`add_event_handler`](Self::add_event_handler).
+    pub fn on_commit<F>(&self, callback: F)
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.add_event_handler(OnCommit(callback));
+    }
+
+    /// Registers a callback called with the size of the file once it is completed,
+    /// see [`add_event_handler`](Self::add_event_handler).
+    pub fn on_complete<F>(&self, callback: F)
+    where
+        F: Fn(u64) + Send + Sync + 'static,
+    {
+        self.add_event_handler(OnComplete(callback));
+    }
+
+    /// Registers a callback called with the error readers see once writing the file fails,
+    /// see [`add_event_handler`](Self::add_event_handler).
+    pub fn on_failure<F>(&self, callback: F)
+    where
+        F: Fn(&io::Error) + Send + Sync + 'static,
+    {
+        self.add_event_handler(OnFailure(callback));
+    }
+
+    /// Subscribes to the lifecycle events of the file, e.g. for observability or orchestration
+    /// layers consuming a single event source per file.
+    ///
+    /// The stream starts with [`FileEvent::Created`], followed by the events occurring after
+    /// subscribing. Events are skipped if the subscriber falls behind by more than 64 events.
+    pub fn events(&self) -> FileEvents {
+        FileEvents {
+            created: false,
+            receiver: self.sentinel.events.subscribe(),
+        }
+    }
+}
+
+impl FileEvents {
+    /// Receives the next event, waiting for it if required.
+    ///
+    /// Returns [`None`] once the file was dropped and all events were received.
+    pub async fn recv(&mut self) -> Option<FileEvent> {
+        if !self.created {
+            self.created = true;
+            return Some(FileEvent::Created);
+        }
+
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) => return Some(event),
+                Err(RecvError::Lagged(_skipped)) => continue,
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Notifies the event handlers and event streams of the transition from `previous` to `current`.
+    pub(crate) fn dispatch_events(&self, previous: WriteStatus, current: WriteStatus) {
+        let handlers: Vec<_> = self
+            .event_handlers
+            .lock()
+            .expect("failed to lock event handlers for reading")
+            .0
+            .clone();
+
+        match current {
+            WriteStatus::Pending { committed, .. } => {
+                let unchanged = matches!(
+                    previous,
+                    WriteStatus::Pending { committed: previous, .. } if previous == committed
+                );
+                if !unchanged && committed > 0 {
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(committed, "committed data");
+                    handlers.iter().for_each(|h| h.on_commit(committed));
+                    self.emit_event(FileEvent::Committed(committed));
+
+                    for (reader, lag) in self.lagging_readers(committed) {
+                        #[cfg(feature = "tracing")]
+                        tracing::warn!(%reader, lag, "reader is lagging");
+                        handlers
+                            .iter()
+                            .for_each(|h| h.on_reader_lagging(reader, lag));
+                        self.emit_event(FileEvent::ReaderLagging(reader, lag));
+                    }
+                }
+            }
+            WriteStatus::Completed { len } => {
+                #[cfg(feature = "tracing")]
+                tracing::info!(len, "completed file");
+                handlers.iter().for_each(|h| h.on_complete(len));
+                self.emit_event(FileEvent::Completed(len));
+            }
+            WriteStatus::Failed => {
+                let error = self.read_error();
+                #[cfg(feature = "tracing")]
+                tracing::warn!(%error, "writing file failed");
+                handlers.iter().for_each(|h| h.on_failure(&error));
+                self.emit_event(FileEvent::Failed(Arc::new(error)));
+            }
+        }
+    }
+
+    /// Publishes an event to all event streams, if any.
+    pub(crate) fn emit_event(&self, event: FileEvent) {
+        // Sending only fails if there are no subscribers.
+        let _ = self.events.send(event);
+    }
+}
+
+/// The handlers notified of state changes of a file.
+#[derive(Default)]
+pub(crate) struct EventHandlers(Vec<Arc<dyn EventHandler>>);
+
+impl Debug for EventHandlers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventHandlers")
+            .field("count", &self.0.len())
+            .finish()
+    }
+}
+
+/// Adapts a callback to [`EventHandler::on_commit`].
+struct OnCommit<F>(F);
+
+/// Adapts a callback to [`EventHandler::on_complete`].
+struct OnComplete<F>(F);
+
+/// Adapts a callback to [`EventHandler::on_failure`].
+struct OnFailure<F>(F);
+
+impl<F> EventHandler for OnCommit<F>
+where
+    F: Fn(u64) + Send + Sync,
+{
+    fn on_commit(&self, committed: u64) {
+        (self.0)(committed)
+    }
+}
+
+impl<F> EventHandler for OnComplete<F>
+where
+    F: Fn(u64) + Send + Sync,
+{
+    fn on_complete(&self, len: u64) {
+        (self.0)(len)
+    }
+}
+
+impl<F> EventHandler for OnFailure<F>
+where
+    F: Fn(&io::Error) + Send + Sync,
+{
+    fn on_failure(&self, error: &io::Error) {
+        (self.0)(error)
+    }
+}
@@ -0,0 +1,103 @@
+//! Page cache hints for readers, notably the [`ReadHints`] type.
+
+use crate::SharedFileReader;
+use std::os::unix::io::{AsRawFd, RawFd};
+
+/// The number of bytes read before they are dropped from the page cache
+/// if [`ReadHints::drop_behind`] is enabled.
+const DROP_BEHIND_THRESHOLD: u64 = 1024 * 1024;
+
+/// Hints on how a reader accesses the file, see [`SharedFileReader::with_read_hints`].
+///
+/// The hints are passed to the kernel via `posix_fadvise(2)`.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ReadHints {
+    /// Advises the kernel that the file is read sequentially (`POSIX_FADV_SEQUENTIAL`).
+    pub sequential: bool,
+    /// Advises the kernel that the file will be read soon (`POSIX_FADV_WILLNEED`).
+    pub will_need: bool,
+    /// Drops data from the page cache after it was read (`POSIX_FADV_DONTNEED`).
+    pub drop_behind: bool,
+}
+
+impl ReadHints {
+    /// Hints for reading the file sequentially, keeping read data in the page cache.
+    pub const fn sequential() -> Self {
+        Self {
+            sequential: true,
+            will_need: true,
+            drop_behind: false,
+        }
+    }
+
+    /// Hints for reading the file sequentially, dropping read data from the page cache.
+    ///
+    /// This reduces page cache pressure when other readers are not expected to read the data.
+    pub const fn streaming() -> Self {
+        Self {
+            sequential: true,
+            will_need: true,
+            drop_behind: true,
+        }
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Issues page cache hints for the file of this reader.
+    ///
+    /// The hints are advisory; failures to issue them are ignored.
+    /// Forked readers do not inherit the hints.
+    pub fn with_read_hints(mut self, hints: ReadHints) -> Self
+    where
+        R: AsRawFd,
+    {
+        let fd = self.file().as_raw_fd();
+        if hints.sequential {
+            advise(fd, 0, 0, libc::POSIX_FADV_SEQUENTIAL);
+        }
+        if hints.will_need {
+            advise(fd, 0, 0, libc::POSIX_FADV_WILLNEED);
+        }
+        if hints.drop_behind {
+            self.set_drop_behind(DropBehind {
+                fd,
+                dropped: self.position(),
+            });
+        }
+        self
+    }
+}
+
+/// The state of a reader dropping read data from the page cache.
+pub(crate) struct DropBehind {
+    /// The file descriptor of the reader's file.
+    fd: RawFd,
+    /// The number of bytes already dropped from the page cache.
+    dropped: u64,
+}
+
+impl DropBehind {
+    /// Drops the data up to the specified position from the page cache
+    /// once enough data was read, or unconditionally if `force` is set.
+    pub(crate) fn advance(&mut self, position: u64, force: bool) {
+        let len = position.saturating_sub(self.dropped);
+        if len == 0 || (!force && len < DROP_BEHIND_THRESHOLD) {
+            return;
+        }
+
+        advise(self.fd, self.dropped, len, libc::POSIX_FADV_DONTNEED);
+        self.dropped = position;
+    }
+}
+
+/// Issues the advice for the specified range of the file, ignoring errors.
+fn advise(fd: RawFd, offset: u64, len: u64, advice: libc::c_int) {
+    let (Ok(offset), Ok(len)) = (libc::off_t::try_from(offset), libc::off_t::try_from(len)) else {
+        return;
+    };
+
+    // SAFETY: The call does not access memory; an invalid descriptor results in an error.
+    unsafe {
+        libc::posix_fadvise(fd, offset, len, advice);
+    }
+}
@@ -0,0 +1,325 @@
+//! Fault injection for testing, notably the [`FaultyFile`] type.
+
+use crate::errors::FaultError;
+use crate::{FilePath, SharedFileType};
+use pin_project::pin_project;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::task::{ready, Context, Poll};
+use std::time::Duration;
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::time::Sleep;
+
+/// An operation of a [`FaultyFile`] that faults can be injected into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FaultOperation {
+    /// Opening a reader or writer handle.
+    Open,
+    /// Reading from a reader handle.
+    Read,
+    /// Writing to or flushing a writer handle.
+    Write,
+    /// Syncing the file.
+    Sync,
+}
+
+/// Controls the faults injected into the operations of a [`FaultyFile`] and all handles
+/// opened from it.
+///
+/// Faults take effect immediately, including for handles already opened, and remain in
+/// effect until they are cleared.
+#[derive(Debug, Default, Clone)]
+pub struct FaultInjector {
+    /// The configured faults.
+    faults: Arc<Mutex<Faults>>,
+}
+
+/// The faults configured using a [`FaultInjector`].
+#[derive(Debug, Default)]
+struct Faults {
+    /// The faults of opening handles.
+    open: OperationFaults,
+    /// The faults of reading.
+    read: OperationFaults,
+    /// The faults of writing.
+    write: OperationFaults,
+    /// The faults of syncing.
+    sync: OperationFaults,
+    /// The maximum number of bytes accepted by a single write.
+    short_write: Option<usize>,
+}
+
+/// The faults of a single [`FaultOperation`].
+#[derive(Debug, Default, Clone, Copy)]
+struct OperationFaults {
+    /// The kind of error the operation fails with.
+    error: Option<ErrorKind>,
+    /// The delay before the operation is performed or fails.
+    delay: Option<Duration>,
+}
+
+impl FaultInjector {
+    /// Creates an injector without any faults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Makes the operation fail with an error of the specified kind.
+    pub fn fail(&self, operation: FaultOperation, kind: ErrorKind) {
+        self.lock().get_mut(operation).error = Some(kind);
+    }
+
+    /// Delays the operation, including failing it.
+    pub fn delay(&self, operation: FaultOperation, delay: Duration) {
+        self.lock().get_mut(operation).delay = Some(delay);
+    }
+
+    /// Makes writes accept at most `max_len` bytes at once. Accepting zero bytes makes
+    /// writers fail with [`ErrorKind::WriteZero`].
+    pub fn short_writes(&self, max_len: usize) {
+        self.lock().short_write = Some(max_len);
+    }
+
+    /// Clears the faults of the operation, including short writes when clearing
+    /// [`FaultOperation::Write`].
+    pub fn clear(&self, operation: FaultOperation) {
+        let mut faults = self.lock();
+        *faults.get_mut(operation) = OperationFaults::default();
+        if operation == FaultOperation::Write {
+            faults.short_write = None;
+        }
+    }
+
+    /// Clears all faults.
+    pub fn clear_all(&self) {
+        *self.lock() = Faults::default();
+    }
+
+    /// Locks the configured faults.
+    fn lock(&self) -> MutexGuard<'_, Faults> {
+        self.faults.lock().expect("failed to lock faults")
+    }
+
+    /// Gets the faults of the operation.
+    fn get(&self, operation: FaultOperation) -> OperationFaults {
+        *self.lock().get_mut(operation)
+    }
+
+    /// Gets the maximum number of bytes accepted by a single write.
+    fn short_write(&self) -> Option<usize> {
+        self.lock().short_write
+    }
+
+    /// Waits for the delay of the operation and fails if configured to.
+    async fn inject(&self, operation: FaultOperation) -> Result<(), Error> {
+        if let Some(delay) = self.get(operation).delay {
+            tokio::time::sleep(delay).await;
+        }
+        match self.get(operation).error {
+            Some(kind) => Err(injected(kind)),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Faults {
+    /// Gets the faults of the operation.
+    fn get_mut(&mut self, operation: FaultOperation) -> &mut OperationFaults {
+        match operation {
+            FaultOperation::Open => &mut self.open,
+            FaultOperation::Read => &mut self.read,
+            FaultOperation::Write => &mut self.write,
+            FaultOperation::Sync => &mut self.sync,
+        }
+    }
+}
+
+/// Creates an injected error of the specified kind.
+fn injected(kind: ErrorKind) -> Error {
+    Error::new(kind, "Injected fault")
+}
+
+/// A wrapper around a backend whose operations can be made to fail, be delayed or write
+/// short on demand, e.g. to deterministically test the error handling of applications.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(FaultyFile::new(file, injector.clone()))`, and configure the faults
+/// using the [`FaultInjector`]. Delays require a tokio runtime with its time driver enabled.
+#[pin_project]
+pub struct FaultyFile<T> {
+    /// The file to read from or write to.
+    #[pin]
+    file: T,
+    /// The faults to inject.
+    injector: FaultInjector,
+    /// The delay of the current read or write, if any.
+    delay: Option<Pin<Box<Sleep>>>,
+    /// Whether the current read or write was already delayed.
+    delayed: bool,
+}
+
+impl<T> FaultyFile<T> {
+    /// Wraps the file, injecting the faults configured using the injector.
+    pub fn new(file: T, injector: FaultInjector) -> Self {
+        Self {
+            file,
+            injector,
+            delay: None,
+            delayed: false,
+        }
+    }
+
+    /// Gets the injector controlling the faults of the file.
+    pub fn injector(&self) -> &FaultInjector {
+        &self.injector
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &T {
+        &self.file
+    }
+
+    /// Waits for the delay of the operation and fails if configured to.
+    fn poll_inject(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        operation: FaultOperation,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let faults = this.injector.get(operation);
+        if !*this.delayed {
+            if let Some(delay) = faults.delay {
+                let sleep = this
+                    .delay
+                    .get_or_insert_with(|| Box::pin(tokio::time::sleep(delay)));
+                ready!(sleep.as_mut().poll(cx));
+                *this.delay = None;
+            }
+            *this.delayed = true;
+        }
+        match faults.error {
+            Some(kind) => {
+                *this.delayed = false;
+                Poll::Ready(Err(injected(kind)))
+            }
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    /// Marks the current read or write as done, such that the next one is delayed again.
+    fn done<R>(self: Pin<&mut Self>, poll: Poll<R>) -> Poll<R> {
+        if poll.is_ready() {
+            *self.project().delayed = false;
+        }
+        poll
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for FaultyFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + Send + Sync,
+{
+    type ReadHandle = FaultyFile<T>;
+    type WriteHandle = FaultyFile<T>;
+    type OpenError = FaultError<T::OpenError>;
+    type SyncError = FaultError<T::SyncError>;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        self.injector
+            .inject(FaultOperation::Open)
+            .await
+            .map_err(FaultError::Injected)?;
+        let file = self.file.open_ro().await.map_err(FaultError::File)?;
+        Ok(Self::new(file, self.injector.clone()))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.injector
+            .inject(FaultOperation::Open)
+            .await
+            .map_err(FaultError::Injected)?;
+        let file = self.file.open_rw().await.map_err(FaultError::File)?;
+        Ok(Self::new(file, self.injector.clone()))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.injector
+            .inject(FaultOperation::Sync)
+            .await
+            .map_err(FaultError::Injected)?;
+        self.file.sync_all().await.map_err(FaultError::File)
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.injector
+            .inject(FaultOperation::Sync)
+            .await
+            .map_err(FaultError::Injected)?;
+        self.file.sync_data().await.map_err(FaultError::File)
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.file.diagnostic_path()
+    }
+}
+
+impl<T> FilePath for FaultyFile<T>
+where
+    T: FilePath,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.file.file_path()
+    }
+}
+
+impl<T> AsyncRead for FaultyFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_inject(cx, FaultOperation::Read))?;
+        let poll = self.as_mut().project().file.poll_read(cx, buf);
+        self.done(poll)
+    }
+}
+
+impl<T> AsyncWrite for FaultyFile<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_inject(cx, FaultOperation::Write))?;
+        let len = match self.injector.short_write() {
+            Some(0) if !buf.is_empty() => return self.done(Poll::Ready(Ok(0))),
+            Some(max_len) => buf.len().min(max_len),
+            None => buf.len(),
+        };
+        let poll = self.as_mut().project().file.poll_write(cx, &buf[..len]);
+        self.done(poll)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_inject(cx, FaultOperation::Write))?;
+        let poll = self.as_mut().project().file.poll_flush(cx);
+        self.done(poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_inject(cx, FaultOperation::Write))?;
+        let poll = self.as_mut().project().file.poll_shutdown(cx);
+        self.done(poll)
+    }
+}
@@ -0,0 +1,277 @@
+//! Coalescing requests for the same file, notably the [`SharedFileRegistry`] type.
+
+use crate::errors::RegistryError;
+use crate::{SharedFile, WriteState};
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// A registry of shared files by key, coalescing concurrent requests for the same key such
+/// that only one of them creates and writes the file, e.g. in a caching web service.
+///
+/// Files remain registered until they are [removed](Self::remove), their time to live
+/// expired, or they are replaced once writing them failed. The registry keeps registered
+/// files alive; once a file is no longer registered, it is deleted according to its
+/// [retention policy](SharedFile::set_retention_policy) when the last handle or reader
+/// is dropped.
+///
+/// Use [`shutdown_graceful`](Self::shutdown_graceful) to let files being written complete
+/// before shutting down, e.g. when a service is terminated.
+#[derive(Debug)]
+pub struct SharedFileRegistry<K, T> {
+    /// The registered files by key.
+    entries: Mutex<HashMap<K, Entry<T>>>,
+    /// The time to live of new entries, if limited.
+    ttl: Option<Duration>,
+    /// Whether the registry was shut down.
+    shut_down: AtomicBool,
+}
+
+/// A file of a [`SharedFileRegistry`], which may still be created.
+#[derive(Debug)]
+struct Entry<T> {
+    /// The file, once created.
+    file: Arc<OnceCell<SharedFile<T>>>,
+    /// The time the entry expires, if any.
+    expires: Option<Instant>,
+}
+
+impl<K, T> SharedFileRegistry<K, T> {
+    /// Creates an empty registry keeping files until they are removed.
+    pub fn new() -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            ttl: None,
+            shut_down: AtomicBool::new(false),
+        }
+    }
+
+    /// Sets the time to live of files registered afterwards, measured from the time they
+    /// are requested first. Expired files are no longer returned and are removed by
+    /// [`evict_expired`](Self::evict_expired).
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// Gets the time to live of newly registered files, if limited.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Removes all expired files, returning the number of removed files.
+    ///
+    /// Files still being created are kept until they were created. Existing handles and
+    /// readers of removed files remain valid.
+    pub fn evict_expired(&self) -> usize {
+        let now = Instant::now();
+        let mut entries = self.lock();
+        let len = entries.len();
+        entries.retain(|_key, entry| !entry.is_expired(now) || !entry.file.initialized());
+        len - entries.len()
+    }
+
+    /// Gets the number of registered keys, including files still being created.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Determines whether no keys are registered.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Determines whether the registry was shut down, see [`shutdown_graceful`](Self::shutdown_graceful).
+    pub fn is_shut_down(&self) -> bool {
+        self.shut_down.load(Ordering::Acquire)
+    }
+
+    /// Shuts down the registry, waiting up to `timeout` for registered files to be completed.
+    ///
+    /// New files are no longer created, and [`get_or_create`](Self::get_or_create) fails
+    /// with [`RegistryError::ShutDown`]. Files still being written after the timeout are
+    /// [aborted](SharedFile::abort), failing and waking up their readers. All files are then
+    /// removed from the registry, and deleted according to their
+    /// [retention policy](SharedFile::set_retention_policy) once their last handle or reader
+    /// is dropped. Files still being created by `init` are not awaited.
+    ///
+    /// Returns the number of aborted files.
+    pub async fn shutdown_graceful(&self, timeout: Duration) -> usize {
+        self.shut_down.store(true, Ordering::Release);
+        let files: Vec<_> = self
+            .lock()
+            .values()
+            .filter_map(|entry| entry.file.get().cloned())
+            .collect();
+
+        let deadline = Instant::now() + timeout;
+        let mut aborted = 0;
+        for file in &files {
+            // Failed files were completed as far as shutting down is concerned.
+            let completed = tokio::time::timeout_at(deadline, file.wait_until_complete()).await;
+            if completed.is_err() {
+                file.abort("The registry was shut down");
+                aborted += 1;
+            }
+        }
+
+        let entries = std::mem::take(&mut *self.lock());
+        // Files may be deleted when dropped, which must not happen while locked.
+        drop(entries);
+        aborted
+    }
+
+    /// Locks the registered files.
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<K, Entry<T>>> {
+        self.entries.lock().expect("failed to lock registry")
+    }
+}
+
+impl<K, T> SharedFileRegistry<K, T>
+where
+    K: Eq + Hash + Clone,
+{
+    /// Gets the file registered for the key, creating it using `init` if none is registered
+    /// or writing the registered file failed.
+    ///
+    /// `init` is expected to create the file and start writing it, e.g. by spawning a task
+    /// with its writer, and is called at most once at a time per key. Concurrent callers
+    /// wait for it and receive the same file, from which they create readers of the data
+    /// written so far. If `init` fails, the error is returned to its caller only; a waiting
+    /// caller then attempts to create the file using its own `init`.
+    ///
+    /// A newly registered file uses the [time to live](Self::with_ttl) of the registry.
+    ///
+    /// Fails with [`RegistryError::ShutDown`] once the registry was
+    /// [shut down](Self::shutdown_graceful).
+    pub async fn get_or_create<F, Fut, E>(
+        &self,
+        key: K,
+        init: F,
+    ) -> Result<SharedFile<T>, RegistryError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SharedFile<T>, E>>,
+    {
+        self.get_or_create_with_ttl(key, self.ttl, init).await
+    }
+
+    /// Gets the file registered for the key, creating it using `init` as in
+    /// [`get_or_create`](Self::get_or_create). A newly registered file expires after `ttl`,
+    /// if specified, instead of the time to live of the registry.
+    pub async fn get_or_create_with_ttl<F, Fut, E>(
+        &self,
+        key: K,
+        ttl: Option<Duration>,
+        init: F,
+    ) -> Result<SharedFile<T>, RegistryError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<SharedFile<T>, E>>,
+    {
+        if self.is_shut_down() {
+            return Err(RegistryError::ShutDown);
+        }
+        let cell = {
+            let now = Instant::now();
+            let mut entries = self.lock();
+            let entry = entries.entry(key).or_insert_with(|| Entry::new(ttl));
+            if entry.is_failed() || (entry.is_expired(now) && entry.file.initialized()) {
+                *entry = Entry::new(ttl);
+            }
+            Arc::clone(&entry.file)
+        };
+        cell.get_or_try_init(init)
+            .await
+            .cloned()
+            .map_err(RegistryError::Create)
+    }
+
+    /// Gets the file registered for the key, unless it is still being created or expired.
+    pub fn get<Q>(&self, key: &Q) -> Option<SharedFile<T>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock()
+            .get(key)
+            .filter(|entry| !entry.is_expired(Instant::now()))
+            .and_then(|entry| entry.file.get().cloned())
+    }
+
+    /// Removes the file registered for the key, returning it unless it was still being created.
+    ///
+    /// Existing handles and readers remain valid; the next request for the key creates
+    /// a new file.
+    pub fn remove<Q>(&self, key: &Q) -> Option<SharedFile<T>>
+    where
+        K: Borrow<Q>,
+        Q: Eq + Hash + ?Sized,
+    {
+        self.lock()
+            .remove(key)
+            .and_then(|entry| entry.file.get().cloned())
+    }
+}
+
+impl<K, T> SharedFileRegistry<K, T>
+where
+    K: Send + 'static,
+    T: Send + Sync + 'static,
+{
+    /// Spawns a task removing expired files every `interval`, see
+    /// [`evict_expired`](Self::evict_expired).
+    ///
+    /// The task ends once the registry was dropped. This must be called from within a
+    /// Tokio runtime.
+    pub fn spawn_eviction(self: &Arc<Self>, interval: Duration) -> JoinHandle<()> {
+        // The eviction task must not keep the registry alive.
+        let registry = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut ticks = tokio::time::interval(interval);
+            ticks.tick().await;
+            loop {
+                ticks.tick().await;
+                let Some(registry) = registry.upgrade() else {
+                    return;
+                };
+                registry.evict_expired();
+            }
+        })
+    }
+}
+
+impl<K, T> Default for SharedFileRegistry<K, T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Entry<T> {
+    /// Creates an entry of a file that is yet to be created, expiring after `ttl`.
+    fn new(ttl: Option<Duration>) -> Self {
+        Self {
+            file: Arc::new(OnceCell::new()),
+            expires: ttl.map(|ttl| Instant::now() + ttl),
+        }
+    }
+
+    /// Determines whether the entry expired at the specified time.
+    fn is_expired(&self, now: Instant) -> bool {
+        self.expires.map_or(false, |expires| now >= expires)
+    }
+
+    /// Determines whether writing the file failed.
+    fn is_failed(&self) -> bool {
+        self.file.get().map_or(false, |file| {
+            file.sentinel.state.load() == WriteState::Failed
+        })
+    }
+}
@@ -0,0 +1,164 @@
+//! Following files written by other processes, notably [`SharedFile::spawn_growth_watcher`].
+
+use crate::{FailureReason, SharedFile, WriteState};
+use notify::event::{AccessKind, AccessMode};
+use notify::{EventKind, RecursiveMode, Watcher};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io;
+use tokio::io::ErrorKind;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Options for following a file written by another process, see
+/// [`SharedFile::spawn_growth_watcher`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WatchOptions {
+    /// The interval at which the size of the file is checked if no notification arrives,
+    /// e.g. because file system notifications are not available.
+    pub poll_interval: Duration,
+    /// Whether the file is completed once the writing process closes it.
+    pub complete_on_close: bool,
+    /// Completes the file once it did not grow for this duration, if any.
+    pub complete_after_idle: Option<Duration>,
+}
+
+impl WatchOptions {
+    /// Creates options checking the size of the file at least every `poll_interval`,
+    /// completing the file once the writing process closes it.
+    pub const fn new(poll_interval: Duration) -> Self {
+        Self {
+            poll_interval,
+            complete_on_close: true,
+            complete_after_idle: None,
+        }
+    }
+
+    /// Sets whether the file is completed once the writing process closes it.
+    pub const fn with_complete_on_close(mut self, complete_on_close: bool) -> Self {
+        self.complete_on_close = complete_on_close;
+        self
+    }
+
+    /// Completes the file once it did not grow for the specified duration.
+    pub const fn with_complete_after_idle(mut self, idle: Duration) -> Self {
+        self.complete_after_idle = Some(idle);
+        self
+    }
+}
+
+impl Default for WatchOptions {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1))
+    }
+}
+
+impl<T> SharedFile<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Spawns a task following the file at the specified path while another process writes it,
+    /// committing its size as it grows such that readers of this file wait for more data
+    /// instead of reaching the end of the file prematurely.
+    ///
+    /// Growth is observed using file system notifications, i.e. inotify on Linux and kqueue on
+    /// BSD and macOS, falling back to polling the size every
+    /// [`poll_interval`](WatchOptions::poll_interval) if they are unavailable or missed. Closing
+    /// a file is only observed with inotify; use
+    /// [`complete_after_idle`](WatchOptions::complete_after_idle) to complete files otherwise.
+    /// If the file shrinks, it is marked as failed.
+    ///
+    /// The task ends once the file is completed, failed or dropped. No writer must be created
+    /// for this file. This must be called from within a Tokio runtime.
+    pub fn spawn_growth_watcher<P: AsRef<Path>>(
+        &self,
+        path: P,
+        options: WatchOptions,
+    ) -> JoinHandle<()> {
+        let path = path.as_ref().to_path_buf();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        let watcher = notify::recommended_watcher(move |event: notify::Result<notify::Event>| {
+            if let Ok(event) = event {
+                let closed = event.kind == EventKind::Access(AccessKind::Close(AccessMode::Write));
+                let _ = sender.send(closed);
+            }
+        })
+        .and_then(|mut watcher| {
+            watcher.watch(&path, RecursiveMode::NonRecursive)?;
+            Ok(watcher)
+        });
+
+        // The watcher must not keep the file alive.
+        let sentinel = Arc::downgrade(&self.sentinel);
+        tokio::spawn(async move {
+            // Without notifications, the size is polled; the watcher stops once dropped.
+            let watcher = watcher.ok();
+            let mut receiver = watcher.as_ref().map(|_| receiver);
+            let mut last_growth = Instant::now();
+            loop {
+                let closed = match receiver.as_mut() {
+                    Some(events) => {
+                        match tokio::time::timeout(options.poll_interval, events.recv()).await {
+                            Ok(Some(mut closed)) => {
+                                // Bursts of notifications are handled at once.
+                                while let Ok(next) = events.try_recv() {
+                                    closed |= next;
+                                }
+                                closed
+                            }
+                            Ok(None) => {
+                                receiver = None;
+                                false
+                            }
+                            Err(_) => false,
+                        }
+                    }
+                    None => {
+                        tokio::time::sleep(options.poll_interval).await;
+                        false
+                    }
+                };
+                let len = tokio::fs::metadata(&path).await.map(|m| m.len());
+
+                let Some(sentinel) = sentinel.upgrade() else {
+                    return;
+                };
+                let WriteState::Pending(committed, _written) = sentinel.state.load() else {
+                    return;
+                };
+                let len = match len {
+                    Ok(len) if len < committed => {
+                        let error = io::Error::new(
+                            ErrorKind::InvalidData,
+                            "The file was truncated while it was followed",
+                        );
+                        sentinel.fail_with(FailureReason::Io(Arc::new(error)));
+                        return;
+                    }
+                    Ok(len) => len,
+                    Err(e) => {
+                        sentinel.fail_with_error(&e);
+                        return;
+                    }
+                };
+
+                if len > committed {
+                    last_growth = Instant::now();
+                    sentinel.state.store(WriteState::Pending(len, len));
+                    sentinel.wake_readers();
+                }
+
+                let idle = options
+                    .complete_after_idle
+                    .map_or(false, |idle| last_growth.elapsed() >= idle);
+                if (closed && options.complete_on_close) || idle {
+                    sentinel.state.store(WriteState::Completed(len));
+                    sentinel.wake_readers();
+                    return;
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,72 @@
+//! Immutable snapshots of the committed prefix of a file, notably [`SharedFile::freeze`].
+
+use crate::{Sentinel, SharedFile, SharedFileReader, SharedFileType};
+use std::sync::Arc;
+
+/// An immutable snapshot of the committed prefix of a [`SharedFile`], see [`SharedFile::freeze`].
+///
+/// Readers of the snapshot observe a completed file of exactly [`len`](Self::len) bytes,
+/// even while the original file continues to grow. The snapshot keeps the file alive.
+#[derive(Debug)]
+pub struct FrozenFile<T> {
+    /// The sentinel value to keep the file alive.
+    sentinel: Arc<Sentinel<T>>,
+    /// The number of bytes in the snapshot.
+    len: u64,
+}
+
+impl<T> SharedFile<T> {
+    /// Creates an immutable snapshot of the currently committed bytes of the file,
+    /// e.g. to periodically publish consistent prefixes of an append-only file.
+    ///
+    /// The snapshot covers [`len`](Self::len) bytes. Its readers fail with
+    /// [`ReadError::Truncated`](crate::prelude::ReadError::Truncated) if the file is
+    /// truncated below their read position.
+    pub fn freeze(&self) -> FrozenFile<T> {
+        FrozenFile {
+            sentinel: self.sentinel.clone(),
+            len: self.len(),
+        }
+    }
+}
+
+impl<T> FrozenFile<T>
+where
+    T: SharedFileType,
+{
+    /// Creates a reader for the snapshot.
+    pub async fn reader(&self) -> Result<SharedFileReader<T, T::ReadHandle>, T::OpenError> {
+        let file = self.sentinel.original.open_ro().await?;
+        let mut reader = SharedFileReader::new(file, self.sentinel.clone());
+        reader.set_frozen(self.len);
+        Ok(reader)
+    }
+}
+
+impl<T> FrozenFile<T> {
+    /// Gets the number of bytes in the snapshot.
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    /// Determines whether the snapshot contains no bytes.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Gets a handle to the original file.
+    pub fn file(&self) -> SharedFile<T> {
+        SharedFile {
+            sentinel: self.sentinel.clone(),
+        }
+    }
+}
+
+impl<T> Clone for FrozenFile<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sentinel: Arc::clone(&self.sentinel),
+            len: self.len,
+        }
+    }
+}
@@ -0,0 +1,348 @@
+//! A backend for files at a path controlled by the caller, notably the [`FsFile`] type.
+
+use crate::{FilePath, SetLen, SharedFile, SharedFileType};
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::fs::{File, OpenOptions};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// A [`tokio::fs::File`] at a known path, usable as a backend of a [`SharedFile`]
+/// without the `async-tempfile` crate feature, see [`SharedFile::open`].
+///
+/// Readers and writers open their own handles to the path. Unlike a temporary file,
+/// the file is not deleted when it is no longer used, unless configured otherwise using
+/// [`SharedFile::set_retention_policy`].
+///
+/// On Windows, the handles are opened with a share mode allowing concurrent reading, writing
+/// and deletion, see [`WindowsOptions`].
+#[derive(Debug)]
+pub struct FsFile {
+    /// The open file.
+    file: File,
+    /// The path of the file.
+    path: PathBuf,
+    /// The share mode used when opening reader and writer handles.
+    #[cfg(windows)]
+    share_mode: u32,
+}
+
+/// Allows other handles to read the file, see `FILE_SHARE_READ`.
+#[cfg(windows)]
+const FILE_SHARE_READ: u32 = 0x0000_0001;
+
+/// Allows other handles to write the file, see `FILE_SHARE_WRITE`.
+#[cfg(windows)]
+const FILE_SHARE_WRITE: u32 = 0x0000_0002;
+
+/// Allows other handles to delete or rename the file, see `FILE_SHARE_DELETE`.
+#[cfg(windows)]
+const FILE_SHARE_DELETE: u32 = 0x0000_0004;
+
+/// Deletes the file once all of its handles are closed, see `FILE_FLAG_DELETE_ON_CLOSE`.
+#[cfg(windows)]
+const FILE_FLAG_DELETE_ON_CLOSE: u32 = 0x0400_0000;
+
+/// Windows-specific options for creating an [`FsFile`], see [`FsFile::create_with_options`].
+///
+/// Windows denies opening a file if its share mode does not permit the requested access. Since
+/// readers and writers open their own handles, the file must be shared for reading and writing
+/// for them to be opened reliably; the default options therefore share the file for reading,
+/// writing and deletion.
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[cfg(windows)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WindowsOptions {
+    /// Whether other handles may read the file (`FILE_SHARE_READ`).
+    pub share_read: bool,
+    /// Whether other handles may write the file (`FILE_SHARE_WRITE`).
+    pub share_write: bool,
+    /// Whether other handles may delete or rename the file (`FILE_SHARE_DELETE`).
+    pub share_delete: bool,
+    /// Whether the file is deleted once all of its handles are closed
+    /// (`FILE_FLAG_DELETE_ON_CLOSE`). This implies sharing the file for deletion.
+    pub delete_on_close: bool,
+}
+
+#[cfg(windows)]
+impl WindowsOptions {
+    /// Creates options sharing the file for reading, writing and deletion, without deleting
+    /// it on close.
+    pub const fn new() -> Self {
+        Self {
+            share_read: true,
+            share_write: true,
+            share_delete: true,
+            delete_on_close: false,
+        }
+    }
+
+    /// Gets the share mode flags of the options.
+    ///
+    /// Handles of a file deleted on close can only be opened if they share it for deletion,
+    /// so sharing for deletion is implied by [`WindowsOptions::delete_on_close`].
+    pub const fn share_mode(&self) -> u32 {
+        let mut mode = 0;
+        if self.share_read {
+            mode |= FILE_SHARE_READ;
+        }
+        if self.share_write {
+            mode |= FILE_SHARE_WRITE;
+        }
+        if self.share_delete || self.delete_on_close {
+            mode |= FILE_SHARE_DELETE;
+        }
+        mode
+    }
+}
+
+#[cfg(windows)]
+impl Default for WindowsOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FsFile {
+    /// Creates the file at the specified path, truncating it if it already exists.
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await?;
+        Ok(Self::from_parts(file, path))
+    }
+
+    /// Creates the file at the specified path with the specified permissions, e.g. `0o600`,
+    /// truncating it if it already exists.
+    ///
+    /// The permissions are subject to the umask of the process and only apply to newly
+    /// created files; the permissions of an existing file are left unchanged.
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[cfg(unix)]
+    pub async fn create_with_mode<P: AsRef<Path>>(path: P, mode: u32) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(mode)
+            .open(&path)
+            .await?;
+        Ok(Self::from_parts(file, path))
+    }
+
+    /// Creates the file at the specified path using the Windows-specific options,
+    /// truncating it if it already exists.
+    ///
+    /// Reader and writer handles are opened using the same share mode. If the file is
+    /// deleted on close, it is removed once the last handle is closed.
+    #[cfg_attr(docsrs, doc(cfg(windows)))]
+    #[cfg(windows)]
+    pub async fn create_with_options<P: AsRef<Path>>(
+        path: P,
+        options: WindowsOptions,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let share_mode = options.share_mode();
+        let mut open_options = OpenOptions::new();
+        open_options
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .share_mode(share_mode);
+        if options.delete_on_close {
+            open_options.custom_flags(FILE_FLAG_DELETE_ON_CLOSE);
+        }
+        let file = open_options.open(&path).await?;
+        Ok(Self {
+            file,
+            path,
+            share_mode,
+        })
+    }
+
+    /// Opens an existing file at the specified path for reading and writing.
+    ///
+    /// The file is written from the start; its existing contents are not visible to readers.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)
+            .await?;
+        Ok(Self::from_parts(file, path))
+    }
+
+    /// Opens an existing file at the specified path for reading only.
+    #[cfg(feature = "tempfile")]
+    pub(crate) async fn open_read_only<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = File::open(&path).await?;
+        Ok(Self::from_parts(file, path))
+    }
+
+    /// Opens another handle to the file, using the share mode the file was created with.
+    async fn reopen(&self, write: bool) -> io::Result<Self> {
+        let mut options = OpenOptions::new();
+        options.read(true).write(write);
+        #[cfg(windows)]
+        options.share_mode(self.share_mode);
+        let file = options.open(&self.path).await?;
+        Ok(Self {
+            file,
+            path: self.path.clone(),
+            #[cfg(windows)]
+            share_mode: self.share_mode,
+        })
+    }
+
+    /// Wraps the open file at the path, sharing it using the default options.
+    fn from_parts(file: File, path: PathBuf) -> Self {
+        Self {
+            file,
+            path,
+            #[cfg(windows)]
+            share_mode: WindowsOptions::new().share_mode(),
+        }
+    }
+
+    /// Gets the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Gets the open file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+}
+
+impl SharedFile<FsFile> {
+    /// Creates a shared file at the specified path, truncating it if it already exists.
+    /// Convenience wrapper around [`FsFile::create`] and [`SharedFile::from`].
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::from(FsFile::create(path).await?))
+    }
+
+    /// Creates a shared file at the specified path with the specified permissions, e.g. `0o600`,
+    /// truncating it if it already exists.
+    /// Convenience wrapper around [`FsFile::create_with_mode`] and [`SharedFile::from`].
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[cfg(unix)]
+    pub async fn create_with_mode<P: AsRef<Path>>(path: P, mode: u32) -> io::Result<Self> {
+        Ok(Self::from(FsFile::create_with_mode(path, mode).await?))
+    }
+
+    /// Creates a shared file at the specified path using the Windows-specific options,
+    /// truncating it if it already exists.
+    /// Convenience wrapper around [`FsFile::create_with_options`] and [`SharedFile::from`].
+    #[cfg_attr(docsrs, doc(cfg(windows)))]
+    #[cfg(windows)]
+    pub async fn create_with_options<P: AsRef<Path>>(
+        path: P,
+        options: WindowsOptions,
+    ) -> io::Result<Self> {
+        Ok(Self::from(
+            FsFile::create_with_options(path, options).await?,
+        ))
+    }
+
+    /// Opens an existing file at the specified path as a shared file.
+    /// Convenience wrapper around [`FsFile::open`] and [`SharedFile::from`].
+    ///
+    /// The file is written from the start; its existing contents are not visible to readers.
+    pub async fn open<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Ok(Self::from(FsFile::open(path).await?))
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for FsFile {
+    type ReadHandle = FsFile;
+    type WriteHandle = FsFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        self.reopen(false).await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.reopen(true).await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.path)
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for FsFile {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        self.file.set_len(size).await
+    }
+}
+
+impl FilePath for FsFile {
+    fn file_path(&self) -> &PathBuf {
+        &self.path
+    }
+}
+
+impl AsyncRead for FsFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for FsFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.get_mut().file).poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_shutdown(cx)
+    }
+}
+
+impl AsyncSeek for FsFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        Pin::new(&mut self.get_mut().file).start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Pin::new(&mut self.get_mut().file).poll_complete(cx)
+    }
+}
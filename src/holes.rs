@@ -0,0 +1,86 @@
+//! Hole tracking for sparse files, notably [`SharedFileWriter::write_hole`](crate::SharedFileWriter::write_hole).
+
+use crate::{Sentinel, SharedFile, SharedFileReader, WriteState};
+use std::collections::BTreeMap;
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::pin::Pin;
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt};
+
+/// The declared holes of a file, mapping their start to their end.
+pub(crate) type Holes = BTreeMap<u64, u64>;
+
+impl<T> SharedFile<T> {
+    /// Gets the holes declared by the writer, in file order.
+    ///
+    /// See [`SharedFileWriter::write_hole`](crate::SharedFileWriter::write_hole).
+    pub fn holes(&self) -> Vec<Range<u64>> {
+        self.sentinel
+            .holes
+            .lock()
+            .expect("failed to lock holes for reading")
+            .iter()
+            .map(|(&start, &end)| start..end)
+            .collect()
+    }
+}
+
+impl<T, R> SharedFileReader<T, R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Skips the committed part of a declared hole at the read position, if any.
+    ///
+    /// The skipped bytes are accounted for as if zeros were read, such that checksum and
+    /// digest verification still succeed. Returns the number of bytes skipped.
+    pub async fn skip_hole(&mut self) -> io::Result<u64> {
+        let position = self.position();
+        let available = match self.state() {
+            WriteState::Pending(committed, _written) => committed,
+            WriteState::Completed(count) => count,
+            WriteState::Failed => return Err(self.sentinel().read_error()),
+        };
+        let end = match self.sentinel().hole_end(position) {
+            Some(end) => end.min(available),
+            None => return Ok(0),
+        };
+        if end <= position {
+            return Ok(0);
+        }
+
+        self.file_mut().seek(SeekFrom::Start(end)).await?;
+        Pin::new(&mut *self).record_skipped(end - position, position);
+        Ok(end - position)
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Records the bytes from `start` (inclusive) to `end` (exclusive) as a hole.
+    pub(crate) fn add_hole(&self, start: u64, end: u64) {
+        if start < end {
+            self.holes
+                .lock()
+                .expect("failed to lock holes for writing")
+                .insert(start, end);
+        }
+    }
+
+    /// Gets the end of the hole containing `position`, if any.
+    fn hole_end(&self, position: u64) -> Option<u64> {
+        let holes = self.holes.lock().expect("failed to lock holes for reading");
+        match holes.range(..=position).next_back() {
+            Some((_start, &end)) if end > position => Some(end),
+            _ => None,
+        }
+    }
+
+    /// Discards holes after `len`, e.g. when the file was truncated.
+    pub(crate) fn truncate_holes(&self, len: u64) {
+        let mut holes = self.holes.lock().expect("failed to lock holes for writing");
+        holes.split_off(&len);
+        if let Some((_start, end)) = holes.iter_mut().next_back() {
+            *end = (*end).min(len);
+        }
+    }
+}
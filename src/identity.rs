@@ -0,0 +1,30 @@
+//! Identification of files, notably [`SharedFile::id`].
+
+use crate::{SharedFile, SharedFileReader, SharedFileWriter};
+use uuid::Uuid;
+
+impl<T> SharedFile<T> {
+    /// Gets the ID of the file, e.g. to correlate it with its readers and writers in logs,
+    /// metrics or registries.
+    ///
+    /// The ID is generated when the file is created, unless assigned using
+    /// [`SharedFileBuilder::id`](crate::SharedFileBuilder::id). It is shared by all handles
+    /// to the same file.
+    pub fn id(&self) -> Uuid {
+        self.sentinel.id
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Gets the ID of the file written to, see [`SharedFile::id`].
+    pub fn file_id(&self) -> Uuid {
+        self.sentinel().id
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Gets the ID of the file read from, see [`SharedFile::id`].
+    pub fn file_id(&self) -> Uuid {
+        self.sentinel().id
+    }
+}
@@ -9,35 +9,328 @@
 //! as EOF; the purpose of this crate is to prevent exactly that.
 //!
 //! Any file type can be used as a backing as long as it implements the crate's [`SharedFileType`]
-//! trait, which in turn requires [`AsyncWrite`](tokio::io::AsyncWrite) and [`AsyncRead`](tokio::io::AsyncRead).
+//! trait, which opens the handles readers and writers use. Reader handles need to implement
+//! [`AsyncRead`](tokio::io::AsyncRead), and writer handles [`AsyncWrite`](tokio::io::AsyncWrite);
+//! they may be of different types. The built-in [`FsFile`] backend wraps a
+//! [`tokio::fs::File`] at a path controlled by the caller, see [`SharedFile::open`].
+//! An already open [`tokio::fs::File`] can be shared directly on Linux and Android.
+//!
+//! ## Runtimes
+//!
+//! Readers and writers are woken up using the [`Waker`](std::task::Waker) of the polling task,
+//! so sharing a file does not depend on the tokio runtime. With the `futures-io` crate feature,
+//! readers and writers implement the [`futures_io`] traits as well, e.g. for use with async-std
+//! or smol. Backends such as [`FsFile`] and features such as [`RetryPolicy`] backoff use tokio
+//! I/O and timers internally and thus still require a tokio runtime context.
+//!
+//! On WebAssembly targets such as `wasm32-unknown-unknown`, where neither `tokio::fs` nor
+//! blocking tasks are available, the file system backends and [`BlockingWriter`] are not
+//! compiled. Files can be shared in memory using the [`MemoryFile`] backend of the `memory`
+//! crate feature, e.g. to decode a download in the browser while it is received.
 //!
 //! ## Crate Features
 //!
 //! - `async-tempfile`: Enables the [`SharedTemporaryFile`] type via the
 //!   [async-tempfile](https://github.com/sunsided/async-tempfile-rs) crate. Since this is how
 //!   this crate was initially meant to be used, this feature is enabled by default.
+//! - `tokio-util`: Enables conversion of readers into [`ReaderStream`](tokio_util::io::ReaderStream)
+//!   and [`FramedRead`](tokio_util::codec::FramedRead) via the
+//!   [tokio-util](https://github.com/tokio-rs/tokio) crate.
+//! - `blake3`: Implements the [`Digest`] trait for [`Hasher`](blake3::Hasher) via the
+//!   [blake3](https://github.com/BLAKE3-team/BLAKE3) crate.
+//! - `compression`: Enables the [`CompressedFile`] type compressing files in the gzip or
+//!   Zstandard format via the [async-compression](https://github.com/Nullus157/async-compression) crate.
+//! - `cross-process`: Enables [`SharedFile::publish_state`] and [`SharedFile::spawn_state_follower`]
+//!   on Unix, sharing the committed and completed state of a file with readers in other processes
+//!   through a state file protected by advisory locks, using the [libc](https://github.com/rust-lang/libc) crate.
+//! - `encryption`: Enables the [`EncryptedFile`] type encrypting files at rest using XChaCha20-Poly1305
+//!   via the [chacha20poly1305](https://github.com/RustCrypto/AEADs) crate.
+//! - `fadvise`: Enables issuing page cache hints for readers via `posix_fadvise(2)` with
+//!   `SharedFileReader::with_read_hints` on Linux and Android, using the
+//!   [libc](https://github.com/rust-lang/libc) crate.
+//! - `fault-injection`: Enables the [`FaultyFile`] type whose operations can be made to fail,
+//!   be delayed or write short on demand, e.g. to test the error handling of applications.
+//! - `futures-io`: Implements [`AsyncRead`](futures_io::AsyncRead) for [`SharedFileReader`] and
+//!   [`AsyncWrite`](futures_io::AsyncWrite) for [`SharedFileWriter`] via the
+//!   [futures-io](https://github.com/rust-lang/futures-rs) crate, see [Runtimes](#runtimes).
+//! - `futures-sink`: Implements [`Sink<Bytes>`](futures_sink::Sink) for [`SharedFileWriter`]
+//!   via the [futures-sink](https://github.com/rust-lang/futures-rs) crate.
+//! - `memfd`: Enables the [`MemfdFile`] type on Linux, a file in anonymous memory created with
+//!   `memfd_create(2)` that can be sealed read-only once completed and passed to other processes,
+//!   using the [libc](https://github.com/rust-lang/libc) crate.
+//! - `memory`: Enables the [`MemoryFile`] type, a growable in-memory buffer for small payloads
+//!   and unit tests that should not touch the disk.
+//! - `mmap`: Enables the [`MmapFile`] type, a file mapped into memory that readers and writers
+//!   access without system calls, via the [memmap2](https://github.com/RazrFalcon/memmap2-rs) crate.
+//! - `notify`: Enables [`SharedFile::spawn_growth_watcher`] following files written by other
+//!   processes using file system notifications, with a polling fallback, via the
+//!   [notify](https://github.com/notify-rs/notify) crate.
+//! - `o-direct`: Enables the [`DirectFile`] type on Linux, bypassing the page cache with
+//!   `O_DIRECT` while handling its alignment requirements internally, using the
+//!   [libc](https://github.com/rust-lang/libc) crate.
+//! - `o-tmpfile`: Enables the [`UnnamedTempFile`] type on Linux, creating temporary files with
+//!   `O_TMPFILE` that never appear in the file system unless persisted, using the
+//!   [libc](https://github.com/rust-lang/libc) crate.
+//! - `object_store`: Enables the [`ObjectStoreFile`] type uploading files to cloud object storage
+//!   such as Amazon S3 while they are written, and [`SharedFile::spawn_upload`] uploading
+//!   committed data of any shared file, via the
+//!   [object_store](https://github.com/apache/arrow-rs/tree/master/object_store) crate.
+//! - `sha2`: Implements the [`Digest`] trait for [`Sha256`](sha2::Sha256) via the
+//!   [sha2](https://github.com/RustCrypto/hashes) crate.
+//! - `tempfile`: Implements [`SharedFileType`] for [`NamedTempFile`](tempfile::NamedTempFile) via the
+//!   [tempfile](https://github.com/Stebalien/tempfile) crate, enabling the [`SharedNamedTempFile`] type
+//!   as an alternative to the `async-tempfile` crate feature.
+//! - `tracing`: Enables attaching a [`Span`](tracing::Span) to a [`SharedFile`] via
+//!   [`SharedFile::set_span`]; readers and writers then record their work in child spans of it,
+//!   including events for syncs, commits, completion, failures, reader waits and wakeups.
+//! - `unix-socket`: Enables [`SharedFile::spawn_socket_notifier`] on Unix, publishing commits,
+//!   completion and failures over a Unix domain socket, and the [`SocketEvents`] client
+//!   consuming them.
 
 #![cfg_attr(docsrs, feature(doc_cfg))]
 #![allow(unsafe_code)]
 
 mod reader;
 
+mod abort;
+mod append;
+mod auto_sync;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod blocking;
+mod buffered;
+mod builder;
+mod checkpoint;
+mod checksum;
+mod chunked;
+#[cfg_attr(docsrs, doc(cfg(feature = "tokio-util")))]
+#[cfg(feature = "tokio-util")]
+mod codec;
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-io")))]
+#[cfg(feature = "futures-io")]
+mod compat;
+mod completion;
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[cfg(feature = "compression")]
+mod compression;
+mod copy;
+mod crc32;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "cross-process", unix))))]
+#[cfg(all(feature = "cross-process", unix))]
+mod cross_process;
+mod digest;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "o-direct", target_os = "linux"))))]
+#[cfg(all(feature = "o-direct", target_os = "linux"))]
+mod direct;
+mod double_buffer;
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+#[cfg(feature = "encryption")]
+mod encryption;
 mod errors;
+mod events;
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android"))))
+)]
+#[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+mod fadvise;
+#[cfg_attr(docsrs, doc(cfg(feature = "fault-injection")))]
+#[cfg(feature = "fault-injection")]
+mod fault;
+mod file_registry;
+#[cfg_attr(docsrs, doc(cfg(feature = "notify")))]
+#[cfg(feature = "notify")]
+mod follow;
+mod freeze;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod fs_file;
+mod holes;
+mod identity;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "memfd", target_os = "linux"))))]
+#[cfg(all(feature = "memfd", target_os = "linux"))]
+mod memfd;
+#[cfg_attr(docsrs, doc(cfg(feature = "memory")))]
+#[cfg(feature = "memory")]
+mod memory;
+mod metadata;
+mod mirror;
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+#[cfg(feature = "mmap")]
+mod mmap;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod multi_file;
+#[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
+#[cfg(feature = "tempfile")]
+mod named_temp_file;
+mod notifier;
+mod null;
+#[cfg_attr(docsrs, doc(cfg(feature = "object_store")))]
+#[cfg(feature = "object_store")]
+mod object_storage;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod overlay;
+mod persist;
+mod priority;
+mod progress;
+mod quota;
+mod ranges;
+mod rate_limit;
+mod registry;
+mod resume;
+mod retention;
+mod retry;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod ring;
+mod segments;
+mod shutdown;
+#[cfg_attr(docsrs, doc(cfg(feature = "futures-sink")))]
+#[cfg(feature = "futures-sink")]
+mod sink;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "unix-socket", unix))))]
+#[cfg(all(feature = "unix-socket", unix))]
+mod socket;
+#[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+#[cfg(feature = "tracing")]
+mod spans;
+mod stats;
+mod status;
+mod tail_cache;
+mod tee;
 #[cfg_attr(docsrs, doc(cfg(feature = "async-tempfile")))]
 #[cfg(feature = "async-tempfile")]
 mod temp_file;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod tmpfs;
+#[cfg_attr(docsrs, doc(cfg(not(target_family = "wasm"))))]
+#[cfg(not(target_family = "wasm"))]
+mod tokio_file;
+mod trailer;
 mod traits;
+mod truncate;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "o-tmpfile", target_os = "linux"))))]
+#[cfg(all(feature = "o-tmpfile", target_os = "linux"))]
+mod unnamed_temp_file;
+mod watchdog;
+mod watermark;
+mod weak;
 mod writer;
 
+use crate::builder::WriterOptions;
+use crate::errors::{OpenReaderError, OpenWriterError, ReadError};
+use crate::events::{EventHandlers, EVENT_CAPACITY};
+use crate::holes::Holes;
+use crate::notifier::Notifier;
+use crate::reader::NODE_ID;
+use crate::registry::RegisteredReader;
+use crate::retention::Retention;
+use crate::segments::Segments;
+use crate::stats::WriterCounters;
+use crate::tail_cache::TailCache;
 use crossbeam::atomic::AtomicCell;
 use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::mem::ManuallyDrop;
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, Mutex};
 use std::task::Waker;
+use std::time::Duration;
+use tokio::io;
+use tokio::sync::{broadcast, watch, Notify, OnceCell};
+use tokio::time::Instant;
 use uuid::Uuid;
 
+pub use auto_sync::AutoSyncPolicy;
+#[cfg(not(target_family = "wasm"))]
+pub use blocking::BlockingWriter;
+pub use buffered::BufferedWriter;
+pub use builder::SharedFileBuilder;
+pub use checksum::{ChecksummedFile, CHECKSUM_BLOCK_SIZE};
+pub use chunked::ChunkedReader;
+pub use completion::Completion;
+#[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+#[cfg(feature = "compression")]
+pub use compression::{CompressedFile, Compression};
+pub use crc32::Crc32;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "o-direct", target_os = "linux"))))]
+#[cfg(all(feature = "o-direct", target_os = "linux"))]
+pub use direct::{DirectFile, SharedDirectFile, DIRECT_IO_ALIGNMENT};
+pub use double_buffer::DoubleBufferedReader;
+#[cfg_attr(docsrs, doc(cfg(feature = "encryption")))]
+#[cfg(feature = "encryption")]
+pub use encryption::{EncryptedFile, ENCRYPTION_CHUNK_SIZE};
+pub use events::{FileEvent, FileEvents};
+#[cfg_attr(
+    docsrs,
+    doc(cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android"))))
+)]
+#[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+pub use fadvise::ReadHints;
+#[cfg(feature = "fault-injection")]
+pub use fault::{FaultInjector, FaultOperation, FaultyFile};
+pub use file_registry::SharedFileRegistry;
+#[cfg_attr(docsrs, doc(cfg(feature = "notify")))]
+#[cfg(feature = "notify")]
+pub use follow::WatchOptions;
+pub use freeze::FrozenFile;
+#[cfg(not(target_family = "wasm"))]
+pub use fs_file::FsFile;
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[cfg(windows)]
+pub use fs_file::WindowsOptions;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "memfd", target_os = "linux"))))]
+#[cfg(all(feature = "memfd", target_os = "linux"))]
+pub use memfd::*;
+#[cfg_attr(docsrs, doc(cfg(feature = "memory")))]
+#[cfg(feature = "memory")]
+pub use memory::*;
+pub use mirror::MirroredFile;
+#[cfg_attr(docsrs, doc(cfg(feature = "mmap")))]
+#[cfg(feature = "mmap")]
+pub use mmap::*;
+#[cfg(not(target_family = "wasm"))]
+pub use multi_file::{MultiFile, SharedMultiFile, DEFAULT_SEGMENT_SIZE};
+#[cfg_attr(docsrs, doc(cfg(feature = "tempfile")))]
+#[cfg(feature = "tempfile")]
+pub use named_temp_file::*;
+pub use notifier::WakerMap;
+pub use null::{NullFile, NullRead, SharedNullFile};
+#[cfg_attr(docsrs, doc(cfg(feature = "object_store")))]
+#[cfg(feature = "object_store")]
+pub use object_storage::{ObjectStoreFile, DEFAULT_UPLOAD_PART_SIZE};
+#[cfg(not(target_family = "wasm"))]
+pub use overlay::OverlayFile;
+pub use priority::ReaderPriority;
+pub use progress::Progress;
+pub use quota::{DiskQuota, QuotaPolicy};
+pub use rate_limit::RateLimit;
 pub use reader::{FileSize, SharedFileReader};
+pub use registry::ReaderInfo;
+pub use retention::RetentionPolicy;
+pub use retry::RetryPolicy;
+#[cfg(not(target_family = "wasm"))]
+pub use ring::{RingFile, SharedRingFile};
+pub use segments::{SegmentWriter, SegmentedWriter};
+pub use shutdown::ShutdownBehavior;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "unix-socket", unix))))]
+#[cfg(all(feature = "unix-socket", unix))]
+pub use socket::SocketEvents;
+pub use stats::WriterStats;
+pub use status::WriteStatus;
+pub use tee::{TeeErrorPolicy, TeeWriter};
+#[cfg(not(target_family = "wasm"))]
+pub use tmpfs::{fastest_dir, is_memory_backed};
+pub use trailer::TRAILER_LEN;
 pub use traits::*;
+#[cfg_attr(docsrs, doc(cfg(all(feature = "o-tmpfile", target_os = "linux"))))]
+#[cfg(all(feature = "o-tmpfile", target_os = "linux"))]
+pub use unnamed_temp_file::*;
+pub use weak::SharedFileWeak;
 pub use writer::SharedFileWriter;
 
 /// Prelude for commonly used types and traits.
@@ -67,7 +360,8 @@ pub use temp_file::*;
 /// ## Writer Finalization
 /// When a writer is dropped, it will move the state of the [`SharedFile`] to
 /// [`WriteState::Completed`]. It is important to note that drop is not asynchronous
-/// and therefore no flush to disk can be performed on the wrapped file.
+/// and therefore no flush to disk can be performed on the wrapped file. If data was
+/// written but not committed, the file is marked as failed instead.
 ///
 /// <div class="warning">User code must make sure to manually sync to disk before dropping the writer.</div>
 #[derive(Debug)]
@@ -78,28 +372,108 @@ pub struct SharedFile<T> {
 
 #[derive(Debug)]
 struct Sentinel<T> {
+    /// The ID of the file.
+    id: Uuid,
     /// The original file. This keeps the file open until all references are dropped.
-    original: T,
+    /// It is dropped manually to apply the retention policy, unless it was taken.
+    original: ManuallyDrop<T>,
+    /// Whether the original file was taken using [`SharedFile::try_into_inner`].
+    original_taken: bool,
     /// The state of the write operation.
     state: AtomicCell<WriteState>,
-    /// Wakers to wake up all interested readers.
-    wakers: Mutex<HashMap<Uuid, Waker>>,
+    /// Wakes up all interested readers.
+    notifier: Notifier,
+    /// Information about all active readers.
+    readers: Mutex<HashMap<Uuid, RegisteredReader>>,
+    /// Notifies waiters when the slowest reader progressed or readers were dropped.
+    readers_progressed: Notify,
+    /// The number of writers and tasks waiting for readers to progress.
+    progress_watchers: AtomicUsize,
+    /// The number of readers reported when lagging, see [`SharedFileReader::with_lag_warning`].
+    lag_limits: AtomicUsize,
+    /// Wakes up a writer waiting for readers to catch up.
+    writer_waker: Mutex<Option<Waker>>,
+    /// The time the writer last wrote data.
+    last_progress: AtomicCell<Instant>,
+    /// The most recently written data, if cached.
+    tail_cache: Mutex<TailCache>,
+    /// Notifies waiters when data was committed or the state of the write operation changed.
+    committed: Notify,
+    /// The checkpoints published by the writer, mapping their names to their offsets.
+    checkpoints: Mutex<HashMap<String, u64>>,
+    /// The metadata entries attached to the file.
+    metadata: Mutex<HashMap<String, String>>,
+    /// The holes declared by the writer.
+    holes: Mutex<Holes>,
+    /// The ranges claimed and committed by segment writers, if written in segments.
+    segments: Mutex<Option<Segments>>,
+    /// The options applied to all writers.
+    writer_options: WriterOptions,
+    /// Publishes the state of writing the file to subscribers.
+    status: watch::Sender<WriteStatus>,
+    /// The handlers notified of state changes.
+    event_handlers: Mutex<EventHandlers>,
+    /// Publishes lifecycle events to event streams.
+    events: broadcast::Sender<FileEvent>,
+    /// Counters describing the work of the writers.
+    stats: WriterCounters,
+    /// Publishes the file size to progress subscribers.
+    progress: watch::Sender<FileSize>,
+    /// What happens to the file once it is no longer used, unless left to the backing type.
+    retention: Mutex<Option<Retention>>,
+    /// The path the file was persisted to, if it was.
+    persisted_path: OnceCell<PathBuf>,
+    /// The expected total size of the file, if known in advance.
+    expected_len: AtomicCell<Option<u64>>,
+    /// The number of committed bytes a failed file is resumed at.
+    resume_offset: AtomicU64,
+    /// The number of times the file was truncated.
+    truncations: AtomicUsize,
+    /// The number of currently active writers.
+    active_writers: AtomicUsize,
+    /// The reason the write operation failed, if known.
+    failure: Mutex<Option<FailureReason>>,
+    /// The finalized digest of the written data, if tracked.
+    digest: Mutex<Option<Vec<u8>>>,
+    /// The span readers and writers are attached to.
+    #[cfg(feature = "tracing")]
+    span: Mutex<tracing::Span>,
 }
 
 /// The state of a file write operation.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum WriteState {
     /// The write operation is pending. Contains the committed byte count and the written byte count.
-    Pending(usize, usize),
+    Pending(u64, u64),
     /// The write operation completed. Contains the number of bytes written (and committed).
-    Completed(usize),
+    Completed(u64),
     /// The write operation failed.
     Failed,
 }
 
+/// The reason a write operation failed, reported to readers.
+#[derive(Debug, Clone)]
+enum FailureReason {
+    /// The writer was dropped with uncommitted data.
+    UncommittedData,
+    /// The write operation was aborted for the specified reason.
+    Aborted(Arc<dyn std::error::Error + Send + Sync>),
+    /// The writer made no progress for the specified duration.
+    Stalled(Duration),
+    /// Writing the file failed with the specified error.
+    Io(Arc<io::Error>),
+}
+
+impl WriteState {
+    /// Determines whether the state is pending with the specified number of committed bytes.
+    fn is_pending_with(&self, committed: u64) -> bool {
+        matches!(self, WriteState::Pending(c, _) if *c == committed)
+    }
+}
+
 impl<T> SharedFile<T>
 where
-    T: SharedFileType<Type = T>,
+    T: SharedFileType,
 {
     /// Synchronously creates a new temporary file.
     pub fn new() -> Result<SharedFile<T>, T::Error>
@@ -135,34 +509,135 @@ where
     ///
     /// When a writer is dropped, it will move the state of the [`SharedFile`] to
     /// [`WriteState::Completed`]. It is important to note that drop is not asynchronous
-    /// and therefore no flush to disk can be performed on the wrapped file.
+    /// and therefore no flush to disk can be performed on the wrapped file. If data was
+    /// written but not committed, the file is marked as failed instead.
     ///
     /// ## One writer at a time
     ///
     /// This operation can result in odd behavior if the
     /// file is accessed multiple times for write access. User code
     /// must make sure that only one meaningful write is performed at
-    /// the same time.
-    pub async fn writer(&self) -> Result<SharedFileWriter<T::Type>, T::OpenError> {
+    /// the same time. Use [`try_writer`](Self::try_writer) to enforce this at runtime.
+    pub async fn writer(&self) -> Result<SharedFileWriter<T, T::WriteHandle>, T::OpenError> {
         let file = self.sentinel.original.open_rw().await?;
         Ok(SharedFileWriter::new(file, self.sentinel.clone()))
     }
 
+    /// Creates a writer for the file, failing if another writer is still active.
+    ///
+    /// See [`writer`](Self::writer) for details.
+    pub async fn try_writer(
+        &self,
+    ) -> Result<SharedFileWriter<T, T::WriteHandle>, OpenWriterError<T::OpenError>> {
+        // Reserves the only writer while opening the file.
+        if self
+            .sentinel
+            .active_writers
+            .compare_exchange(0, 1, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(OpenWriterError::WriterExists);
+        }
+
+        let result = match self.sentinel.original.open_rw().await {
+            Ok(file) => Ok(SharedFileWriter::new(file, self.sentinel.clone())),
+            Err(e) => Err(OpenWriterError::Open(e)),
+        };
+        // The writer itself is counted as active from here on.
+        self.sentinel.active_writers.fetch_sub(1, Ordering::AcqRel);
+        result
+    }
+
     /// Creates a reader for the file.
-    pub async fn reader(&self) -> Result<SharedFileReader<T::Type>, T::OpenError> {
+    ///
+    /// If writing the file already failed, the error is only reported when reading.
+    /// Use [`try_reader`](Self::try_reader) to fail early instead.
+    pub async fn reader(&self) -> Result<SharedFileReader<T, T::ReadHandle>, T::OpenError> {
         let file = self.sentinel.original.open_ro().await?;
         Ok(SharedFileReader::new(file, self.sentinel.clone()))
     }
+
+    /// Creates a connected writer and reader for the file, consuming the [`SharedFile`].
+    ///
+    /// This is a convenience for the common case of a single producer and a single consumer;
+    /// the file is kept alive until both the writer and the reader are dropped.
+    pub async fn split(
+        self,
+    ) -> Result<
+        (
+            SharedFileWriter<T, T::WriteHandle>,
+            SharedFileReader<T, T::ReadHandle>,
+        ),
+        T::OpenError,
+    > {
+        let writer = self.writer().await?;
+        let reader = self.reader().await?;
+        Ok((writer, reader))
+    }
+
+    /// Creates a new file and returns a connected writer and reader for it.
+    ///
+    /// This is a shorthand for [`new_async`](Self::new_async) followed by [`split`](Self::split)
+    /// for the common produce/consume pattern; the file is kept alive until both the writer
+    /// and the reader are dropped.
+    pub async fn pipe() -> Result<
+        (
+            SharedFileWriter<T, T::WriteHandle>,
+            SharedFileReader<T, T::ReadHandle>,
+        ),
+        T::OpenError,
+    >
+    where
+        T: AsyncNewFile<Target = T>,
+        T::OpenError: From<<T as AsyncNewFile>::Error>,
+    {
+        Self::new_async().await?.split().await
+    }
+
+    /// Creates a reader for the file, failing immediately if writing the file already failed.
+    pub async fn try_reader(
+        &self,
+    ) -> Result<SharedFileReader<T, T::ReadHandle>, OpenReaderError<T::OpenError>> {
+        if let WriteState::Failed = self.sentinel.state.load() {
+            return Err(OpenReaderError::FileWritingFailed);
+        }
+
+        self.reader().await.map_err(OpenReaderError::Open)
+    }
+
+    /// Returns the backing file if the file was completely written and no other handles,
+    /// readers or writers exist, e.g. to pass it to APIs requiring exclusive ownership.
+    ///
+    /// Otherwise, the handle is returned as the error. The
+    /// [retention policy](Self::set_retention_policy) does not apply to the returned file.
+    pub fn try_into_inner(self) -> Result<T, Self> {
+        if !matches!(self.sentinel.state.load(), WriteState::Completed(_)) {
+            return Err(self);
+        }
+
+        match Arc::try_unwrap(self.sentinel) {
+            Ok(sentinel) => Ok(sentinel.into_original()),
+            Err(sentinel) => Err(Self { sentinel }),
+        }
+    }
+}
+
+impl<T> Clone for SharedFile<T> {
+    /// Creates another handle to the same file.
+    ///
+    /// This is cheap and does not require `T` to be [`Clone`]; the file is kept alive
+    /// until all handles, readers and writers are dropped.
+    fn clone(&self) -> Self {
+        Self {
+            sentinel: Arc::clone(&self.sentinel),
+        }
+    }
 }
 
 impl<T> From<T> for SharedFile<T> {
     fn from(value: T) -> Self {
         Self {
-            sentinel: Arc::new(Sentinel {
-                original: value,
-                state: AtomicCell::new(WriteState::Pending(0, 0)),
-                wakers: Mutex::new(HashMap::default()),
-            }),
+            sentinel: Arc::new(Sentinel::new(value)),
         }
     }
 }
@@ -172,13 +647,7 @@ where
     T: Default,
 {
     fn default() -> Self {
-        Self {
-            sentinel: Arc::new(Sentinel {
-                original: T::default(),
-                state: AtomicCell::new(WriteState::Pending(0, 0)),
-                wakers: Mutex::new(HashMap::default()),
-            }),
-        }
+        Self::from(T::default())
     }
 }
 
@@ -187,32 +656,132 @@ where
     T: FilePath,
 {
     fn file_path(&self) -> &PathBuf {
-        self.sentinel.original.file_path()
+        self.sentinel.file_path()
     }
 }
 
 impl<T> Sentinel<T> {
-    fn wake_readers(&self) {
-        let mut lock = self
-            .wakers
+    fn new(original: T) -> Self {
+        Self {
+            id: Uuid::now_v1(NODE_ID),
+            original: ManuallyDrop::new(original),
+            original_taken: false,
+            state: AtomicCell::new(WriteState::Pending(0, 0)),
+            notifier: Notifier::default(),
+            readers: Mutex::new(HashMap::default()),
+            readers_progressed: Notify::new(),
+            progress_watchers: AtomicUsize::new(0),
+            lag_limits: AtomicUsize::new(0),
+            writer_waker: Mutex::new(None),
+            last_progress: AtomicCell::new(Instant::now()),
+            tail_cache: Mutex::new(TailCache::default()),
+            committed: Notify::new(),
+            checkpoints: Mutex::new(HashMap::default()),
+            metadata: Mutex::new(HashMap::default()),
+            holes: Mutex::new(Holes::new()),
+            segments: Mutex::new(None),
+            writer_options: WriterOptions::default(),
+            status: watch::channel(WriteStatus::from(WriteState::Pending(0, 0))).0,
+            event_handlers: Mutex::new(EventHandlers::default()),
+            events: broadcast::channel(EVENT_CAPACITY).0,
+            stats: WriterCounters::default(),
+            progress: watch::channel(FileSize::AtLeast(0)).0,
+            retention: Mutex::new(None),
+            persisted_path: OnceCell::new(),
+            expected_len: AtomicCell::new(None),
+            resume_offset: AtomicU64::new(0),
+            truncations: AtomicUsize::new(0),
+            active_writers: AtomicUsize::new(0),
+            failure: Mutex::new(None),
+            digest: Mutex::new(None),
+            #[cfg(feature = "tracing")]
+            span: Mutex::new(tracing::Span::none()),
+        }
+    }
+
+    /// Marks the file as failed for the specified reason and wakes up all readers.
+    fn fail_with(&self, reason: FailureReason) {
+        *self
+            .failure
             .lock()
-            .expect("failed to lock waker vector for writing");
-        lock.drain().for_each(|(_id, w)| w.wake());
+            .expect("failed to lock failure reason for writing") = Some(reason);
+        self.state.store(WriteState::Failed);
+        self.wake_readers();
     }
 
-    fn register_reader_waker(&self, id: Uuid, waker: &Waker) {
-        let mut lock = self
-            .wakers
+    /// Marks the file as failed due to the specified error and wakes up all readers.
+    ///
+    /// Readers receive a copy of the error's kind and message as the root cause.
+    fn fail_with_error(&self, error: &io::Error) {
+        let error = io::Error::new(error.kind(), error.to_string());
+        self.fail_with(FailureReason::Io(Arc::new(error)));
+    }
+
+    /// Clears the failure reason, e.g. after the file was reset.
+    fn clear_failure(&self) {
+        *self
+            .failure
+            .lock()
+            .expect("failed to lock failure reason for writing") = None;
+    }
+
+    /// Creates the error reported to readers of a failed file.
+    fn read_error(&self) -> io::Error {
+        let reason = self
+            .failure
             .lock()
-            .expect("failed to lock waker vector for reading");
+            .expect("failed to lock failure reason for reading")
+            .clone();
+        let error = match reason {
+            Some(FailureReason::UncommittedData) => ReadError::UncommittedData,
+            Some(FailureReason::Aborted(reason)) => ReadError::Aborted(reason),
+            Some(FailureReason::Stalled(timeout)) => ReadError::WriterStalled(timeout),
+            Some(FailureReason::Io(error)) => ReadError::WriteFailed(error),
+            None => ReadError::FileClosed,
+        };
+        io::Error::new(ErrorKind::BrokenPipe, error)
+    }
+
+    /// Gets the current size of the file.
+    fn file_size(&self) -> FileSize {
+        FileSize::from_state(self.state.load(), self.expected_len.load())
+    }
+
+    fn wake_readers(&self) {
+        let state = self.state.load();
+        if let WriteState::Pending(committed, _) | WriteState::Completed(committed) = state {
+            // Remembers the committed bytes so that a failed file can be resumed.
+            self.resume_offset.store(committed, Ordering::Release);
+        }
+
+        let size = FileSize::from_state(state, self.expected_len.load());
+        self.progress.send_if_modified(|current| {
+            let modified = *current != size;
+            *current = size;
+            modified
+        });
+        let status = WriteStatus::from(state);
+        let mut previous = None;
+        self.status.send_if_modified(|current| {
+            let modified = *current != status;
+            if modified {
+                previous = Some(*current);
+                *current = status;
+            }
+            modified
+        });
+        if let Some(previous) = previous {
+            self.dispatch_events(previous, status);
+        }
+        self.committed.notify_waiters();
+        self.notifier.0.notify_all();
+    }
 
-        lock.entry(id)
-            .and_modify(|e| e.clone_from(waker))
-            .or_insert(waker.clone());
+    fn register_reader_waker(&self, id: Uuid, priority: ReaderPriority, waker: &Waker) {
+        self.notifier.0.register(id, priority, waker);
     }
 
     fn remove_reader_waker(&self, id: &Uuid) {
-        let mut lock = self.wakers.lock().expect("failed to get lock for readers");
-        lock.remove(id);
+        self.notifier.0.remove(id);
     }
 }
@@ -0,0 +1,173 @@
+//! Anonymous shared memory files created with `memfd_create`, notably the [`MemfdFile`] type.
+
+use crate::errors::SealError;
+use crate::{SetLen, SharedFile, SharedFileReader, SharedFileType, SharedFileWriter, WriteState};
+use std::ffi::CString;
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd, RawFd};
+use tokio::fs::File;
+use tokio::io;
+
+/// A type alias for a [`SharedFile`] wrapping a [`MemfdFile`].
+pub type SharedMemfdFile = SharedFile<MemfdFile>;
+
+/// A type alias for a [`SharedFileReader`] wrapping a [`MemfdFile`].
+pub type SharedMemfdFileReader = SharedFileReader<MemfdFile, File>;
+
+/// A type alias for a [`SharedFileWriter`] wrapping a [`MemfdFile`].
+pub type SharedMemfdFileWriter = SharedFileWriter<MemfdFile, File>;
+
+/// The seals making a file read-only, see `fcntl(2)`.
+const READ_ONLY_SEALS: libc::c_int =
+    libc::F_SEAL_SEAL | libc::F_SEAL_SHRINK | libc::F_SEAL_GROW | libc::F_SEAL_WRITE;
+
+/// A file living in anonymous memory, created with `memfd_create(2)`.
+///
+/// The file never appears in the file system and is released once the last handle to it is
+/// closed. Its file descriptor, available through [`AsFd`], can be passed to other processes,
+/// e.g. over a Unix domain socket, which can then map or read the file. Once completed, the
+/// file can be sealed read-only using [`SharedFile::seal`], guaranteeing receivers that its
+/// contents no longer change.
+///
+/// The file descriptor is created with `MFD_CLOEXEC` and is therefore not inherited by child
+/// processes unless the flag is cleared.
+#[derive(Debug)]
+pub struct MemfdFile {
+    /// The open file.
+    file: File,
+}
+
+impl MemfdFile {
+    /// Creates an empty file in anonymous memory that allows sealing.
+    ///
+    /// The name is used for debugging only and appears as the target of the symbolic link
+    /// in `/proc/self/fd`, prefixed with `memfd:`. It must not contain a NUL byte.
+    pub fn new(name: &str) -> io::Result<Self> {
+        let name = CString::new(name)?;
+        // SAFETY: The name is a valid, NUL-terminated string.
+        let fd = unsafe {
+            libc::memfd_create(name.as_ptr(), libc::MFD_CLOEXEC | libc::MFD_ALLOW_SEALING)
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // SAFETY: The file descriptor was just created and is owned by nothing else.
+        let file = unsafe { std::fs::File::from_raw_fd(fd) };
+        Ok(Self {
+            file: File::from_std(file),
+        })
+    }
+
+    /// Gets the open file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+
+    /// Seals the file read-only, such that neither this process nor processes the file
+    /// descriptor was passed to can modify, grow or shrink it, or remove the seals.
+    ///
+    /// Prefer [`SharedFile::seal`], which only seals completed files.
+    pub fn seal_read_only(&self) -> io::Result<()> {
+        // SAFETY: The file descriptor is valid for the lifetime of the file.
+        let result =
+            unsafe { libc::fcntl(self.file.as_raw_fd(), libc::F_ADD_SEALS, READ_ONLY_SEALS) };
+        if result < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    /// Determines whether the file was sealed read-only.
+    pub fn is_sealed(&self) -> io::Result<bool> {
+        // SAFETY: The file descriptor is valid for the lifetime of the file.
+        let seals = unsafe { libc::fcntl(self.file.as_raw_fd(), libc::F_GET_SEALS) };
+        if seals < 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(seals & READ_ONLY_SEALS == READ_ONLY_SEALS)
+    }
+}
+
+impl SharedMemfdFile {
+    /// Creates a shared file in anonymous memory. Convenience wrapper around
+    /// [`MemfdFile::new`] and [`SharedFile::from`].
+    pub fn new_memfd(name: &str) -> io::Result<Self> {
+        Ok(Self::from(MemfdFile::new(name)?))
+    }
+
+    /// Seals the completed file read-only, see [`MemfdFile::seal_read_only`].
+    ///
+    /// Fails with [`SealError::NotCompleted`] if writing the file did not complete successfully.
+    /// See [`SharedFileWriter::complete_and_seal`].
+    pub fn seal(&self) -> Result<(), SealError> {
+        let WriteState::Completed(_) = self.sentinel.state.load() else {
+            return Err(SealError::NotCompleted);
+        };
+        self.sentinel
+            .original
+            .seal_read_only()
+            .map_err(SealError::Seal)
+    }
+
+    /// Gets the file in anonymous memory.
+    pub fn memfd(&self) -> &MemfdFile {
+        &self.sentinel.original
+    }
+}
+
+impl SharedMemfdFileWriter {
+    /// Completes the writing operation and seals the file read-only.
+    ///
+    /// See [`SharedFile::seal`].
+    pub async fn complete_and_seal(self) -> Result<(), SealError> {
+        let file = SharedFile {
+            sentinel: self.sentinel_arc().clone(),
+        };
+        self.complete().await.map_err(SealError::Complete)?;
+        file.seal()
+    }
+}
+
+impl AsFd for MemfdFile {
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.file.as_fd()
+    }
+}
+
+impl AsRawFd for MemfdFile {
+    fn as_raw_fd(&self) -> RawFd {
+        self.file.as_raw_fd()
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for MemfdFile {
+    type ReadHandle = File;
+    type WriteHandle = File;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        self.file.open_ro().await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.file.open_rw().await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for MemfdFile {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        self.file.set_len(size).await
+    }
+}
@@ -0,0 +1,211 @@
+//! An in-memory backend, notably the [`MemoryFile`] type.
+
+use crate::{
+    AsyncNewFile, NewFile, SetLen, SharedFile, SharedFileReader, SharedFileType, SharedFileWriter,
+};
+use std::convert::Infallible;
+use std::io::{ErrorKind, SeekFrom};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// A type alias for a [`SharedFile`] wrapping a [`MemoryFile`].
+pub type SharedMemoryFile = SharedFile<MemoryFile>;
+
+/// A type alias for a [`SharedFileReader`] wrapping a [`MemoryFile`].
+pub type SharedMemoryFileReader = SharedFileReader<MemoryFile>;
+
+/// A type alias for a [`SharedFileWriter`] wrapping a [`MemoryFile`].
+pub type SharedMemoryFileWriter = SharedFileWriter<MemoryFile>;
+
+/// A growable in-memory buffer usable as a backend of a [`SharedFile`], e.g. for small
+/// payloads or unit tests that should not touch the disk.
+///
+/// All handles opened from the same instance share the buffer, but have their own position.
+/// Syncing the file does nothing.
+#[derive(Debug, Default)]
+pub struct MemoryFile {
+    /// The contents of the file, shared by all handles.
+    buffer: Arc<Mutex<Vec<u8>>>,
+    /// The position of this handle in the buffer.
+    position: u64,
+}
+
+impl MemoryFile {
+    /// Creates an empty in-memory file.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty in-memory file, preallocating `capacity` bytes.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            buffer: Arc::new(Mutex::new(Vec::with_capacity(capacity))),
+            position: 0,
+        }
+    }
+
+    /// Gets the number of bytes in the buffer, including data not yet committed by a writer.
+    pub fn len(&self) -> u64 {
+        self.lock().len() as u64
+    }
+
+    /// Determines whether the buffer is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Copies the contents of the buffer.
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.lock().clone()
+    }
+
+    /// Creates another handle to the same buffer, positioned at its start.
+    fn handle(&self) -> Self {
+        Self {
+            buffer: Arc::clone(&self.buffer),
+            position: 0,
+        }
+    }
+
+    /// Locks the buffer.
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<u8>> {
+        self.buffer.lock().expect("failed to lock memory buffer")
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for MemoryFile {
+    type ReadHandle = MemoryFile;
+    type WriteHandle = MemoryFile;
+    type OpenError = Infallible;
+    type SyncError = Infallible;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(self.handle())
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(self.handle())
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        Ok(())
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for MemoryFile {
+    type SetLenError = Infallible;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        let size = usize::try_from(size).unwrap_or(usize::MAX);
+        self.lock().resize(size, 0);
+        Ok(())
+    }
+}
+
+impl NewFile for MemoryFile {
+    type Target = MemoryFile;
+    type Error = Infallible;
+
+    fn new() -> Result<Self::Target, Self::Error> {
+        Ok(Self::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncNewFile for MemoryFile {
+    type Target = MemoryFile;
+    type Error = Infallible;
+
+    async fn new_async() -> Result<Self::Target, Self::Error> {
+        Ok(Self::default())
+    }
+}
+
+impl AsyncRead for MemoryFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let read = {
+            let buffer = this.lock();
+            let start = usize::try_from(this.position)
+                .unwrap_or(usize::MAX)
+                .min(buffer.len());
+            let len = (buffer.len() - start).min(buf.remaining());
+            buf.put_slice(&buffer[start..start + len]);
+            len
+        };
+        this.position += read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MemoryFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let Ok(start) = usize::try_from(this.position) else {
+            return Poll::Ready(Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "The write position exceeds the addressable memory",
+            )));
+        };
+        {
+            let mut buffer = this.lock();
+            let end = start + buf.len();
+            if buffer.len() < end {
+                buffer.resize(end, 0);
+            }
+            buffer[start..end].copy_from_slice(buf);
+        }
+        this.position += buf.len() as u64;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for MemoryFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        let position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => this.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                this.position = position;
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
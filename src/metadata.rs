@@ -0,0 +1,79 @@
+//! User metadata attached to files, notably [`SharedFile::set_metadata`].
+
+use crate::{Sentinel, SharedFile, SharedFileReader, SharedFileWriter};
+use std::collections::HashMap;
+
+impl<T> SharedFile<T> {
+    /// Attaches a metadata entry to the file, e.g. its content type, original file name or
+    /// owner, so that readers can retrieve it alongside the data.
+    ///
+    /// Returns the previous value of the entry, if any.
+    pub fn set_metadata(&self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.sentinel.set_metadata(key.into(), value.into())
+    }
+
+    /// Removes a metadata entry from the file, returning its value.
+    pub fn remove_metadata(&self, key: &str) -> Option<String> {
+        self.sentinel
+            .metadata
+            .lock()
+            .expect("failed to lock metadata for writing")
+            .remove(key)
+    }
+
+    /// Gets the value of a metadata entry, see [`set_metadata`](Self::set_metadata).
+    pub fn metadata(&self, key: &str) -> Option<String> {
+        self.sentinel.metadata(key)
+    }
+
+    /// Gets a snapshot of all metadata entries.
+    pub fn all_metadata(&self) -> HashMap<String, String> {
+        self.sentinel.all_metadata()
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Attaches a metadata entry to the file, see [`SharedFile::set_metadata`].
+    pub fn set_metadata(&self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+        self.sentinel().set_metadata(key.into(), value.into())
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Gets the value of a metadata entry of the file, see [`SharedFile::set_metadata`].
+    pub fn metadata(&self, key: &str) -> Option<String> {
+        self.sentinel().metadata(key)
+    }
+
+    /// Gets a snapshot of all metadata entries of the file.
+    pub fn all_metadata(&self) -> HashMap<String, String> {
+        self.sentinel().all_metadata()
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Sets a metadata entry, returning its previous value.
+    fn set_metadata(&self, key: String, value: String) -> Option<String> {
+        self.metadata
+            .lock()
+            .expect("failed to lock metadata for writing")
+            .insert(key, value)
+    }
+
+    /// Gets the value of a metadata entry.
+    fn metadata(&self, key: &str) -> Option<String> {
+        self.metadata
+            .lock()
+            .expect("failed to lock metadata for reading")
+            .get(key)
+            .cloned()
+    }
+
+    /// Gets a snapshot of all metadata entries.
+    fn all_metadata(&self) -> HashMap<String, String> {
+        self.metadata
+            .lock()
+            .expect("failed to lock metadata for reading")
+            .clone()
+    }
+}
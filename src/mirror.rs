@@ -0,0 +1,290 @@
+//! Replicating files to a secondary backend, notably the [`MirroredFile`] type.
+
+use crate::errors::MirrorError;
+use crate::{FilePath, SharedFileType, TeeErrorPolicy};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A wrapper around a backend duplicating every successful write to a secondary backend,
+/// e.g. a file on another device for redundancy.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(MirroredFile::new(primary, secondary, TeeErrorPolicy::Detach))`.
+/// Readers read from the primary file only. Data written to the primary file is forwarded
+/// to the secondary file before the next write, and flushed to it before it is committed,
+/// such that committed data is mirrored.
+///
+/// Errors of the secondary file are handled according to the [`TeeErrorPolicy`]: either
+/// they fail the file like errors of the primary file, or the secondary file is detached
+/// and no longer written or synced.
+#[pin_project]
+pub struct MirroredFile<T, M> {
+    /// The primary file to read from or write to.
+    #[pin]
+    primary: T,
+    /// The secondary file to forward written data to, unless this handle does not write
+    /// or the secondary file was detached.
+    secondary: Option<M>,
+    /// The original secondary file, from which writer handles are opened.
+    original: Arc<M>,
+    /// The policy for handling errors of the secondary file.
+    policy: TeeErrorPolicy,
+    /// Whether the secondary file was detached, shared by all handles.
+    detached: Arc<AtomicBool>,
+    /// The data written to the primary file but not yet forwarded.
+    pending: Vec<u8>,
+    /// The number of pending bytes already forwarded.
+    forwarded: usize,
+}
+
+impl<T, M> MirroredFile<T, M> {
+    /// Wraps the primary file, mirroring all data written to it to the secondary file.
+    ///
+    /// ## Arguments
+    ///
+    /// * `primary` - The file to read from and write to.
+    /// * `secondary` - The file to forward written data to.
+    /// * `policy` - The policy for handling errors of the secondary file.
+    pub fn new(primary: T, secondary: M, policy: TeeErrorPolicy) -> Self {
+        Self {
+            primary,
+            secondary: None,
+            original: Arc::new(secondary),
+            policy,
+            detached: Arc::new(AtomicBool::new(false)),
+            pending: Vec::new(),
+            forwarded: 0,
+        }
+    }
+
+    /// Gets a reference to the primary file.
+    pub fn get_ref(&self) -> &T {
+        &self.primary
+    }
+
+    /// Determines whether the secondary file was detached after an error.
+    pub fn is_detached(&self) -> bool {
+        self.detached.load(Ordering::Acquire)
+    }
+
+    /// Creates a handle of the primary file sharing the state of this file.
+    fn handle(&self, primary: T, secondary: Option<M>) -> Self {
+        Self {
+            primary,
+            secondary,
+            original: Arc::clone(&self.original),
+            policy: self.policy,
+            detached: Arc::clone(&self.detached),
+            pending: Vec::new(),
+            forwarded: 0,
+        }
+    }
+
+    /// Handles an error of the secondary file according to the policy.
+    fn handle_secondary_error(self: Pin<&mut Self>, error: Error) -> io::Result<()> {
+        let this = self.project();
+        match this.policy {
+            TeeErrorPolicy::Fail => Err(error),
+            TeeErrorPolicy::Detach => {
+                this.detached.store(true, Ordering::Release);
+                *this.secondary = None;
+                this.pending.clear();
+                *this.forwarded = 0;
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<T, M> MirroredFile<T, M>
+where
+    M: AsyncWrite + Unpin,
+{
+    /// Forwards all pending data to the secondary file.
+    fn poll_forward(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let this = self.as_mut().project();
+            let Some(secondary) = this.secondary.as_mut() else {
+                return Poll::Ready(Ok(()));
+            };
+            if *this.forwarded == this.pending.len() {
+                this.pending.clear();
+                *this.forwarded = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            let result =
+                ready!(Pin::new(secondary).poll_write(cx, &this.pending[*this.forwarded..]));
+            match result {
+                Ok(0) => {
+                    let error = Error::from(ErrorKind::WriteZero);
+                    return Poll::Ready(self.handle_secondary_error(error));
+                }
+                Ok(written) => *this.forwarded += written,
+                Err(e) => return Poll::Ready(self.handle_secondary_error(e)),
+            }
+        }
+    }
+
+    /// Polls an operation of the secondary file, handling errors according to the policy.
+    fn poll_secondary(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        op: fn(Pin<&mut M>, &mut Context<'_>) -> Poll<io::Result<()>>,
+    ) -> Poll<io::Result<()>> {
+        let Some(secondary) = self.as_mut().project().secondary.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        match ready!(op(Pin::new(secondary), cx)) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(self.handle_secondary_error(e)),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<T, M> SharedFileType for MirroredFile<T, M>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + Send + Sync,
+    M: SharedFileType<WriteHandle = M> + Send + Sync,
+{
+    type ReadHandle = MirroredFile<T, M>;
+    type WriteHandle = MirroredFile<T, M>;
+    type OpenError = MirrorError<T::OpenError, M::OpenError>;
+    type SyncError = MirrorError<T::SyncError, M::SyncError>;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        let primary = self.primary.open_ro().await.map_err(MirrorError::Primary)?;
+        Ok(self.handle(primary, None))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        let primary = self.primary.open_rw().await.map_err(MirrorError::Primary)?;
+        if self.is_detached() {
+            return Ok(self.handle(primary, None));
+        }
+        let secondary = match self.original.open_rw().await {
+            Ok(secondary) => Some(secondary),
+            Err(e) if self.policy == TeeErrorPolicy::Fail => return Err(MirrorError::Secondary(e)),
+            Err(_) => {
+                self.detached.store(true, Ordering::Release);
+                None
+            }
+        };
+        Ok(self.handle(primary, secondary))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.primary
+            .sync_all()
+            .await
+            .map_err(MirrorError::Primary)?;
+        if self.is_detached() {
+            return Ok(());
+        }
+        self.sync_secondary(self.original.sync_all().await)
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.primary
+            .sync_data()
+            .await
+            .map_err(MirrorError::Primary)?;
+        if self.is_detached() {
+            return Ok(());
+        }
+        self.sync_secondary(self.original.sync_data().await)
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.primary.diagnostic_path()
+    }
+}
+
+impl<T, M> MirroredFile<T, M>
+where
+    T: SharedFileType,
+    M: SharedFileType,
+{
+    /// Handles the result of syncing the secondary file according to the policy.
+    fn sync_secondary(
+        &self,
+        result: Result<(), M::SyncError>,
+    ) -> Result<(), MirrorError<T::SyncError, M::SyncError>> {
+        match result {
+            Err(e) if self.policy == TeeErrorPolicy::Fail => Err(MirrorError::Secondary(e)),
+            Err(_) => {
+                self.detached.store(true, Ordering::Release);
+                Ok(())
+            }
+            Ok(()) => Ok(()),
+        }
+    }
+}
+
+impl<T, M> FilePath for MirroredFile<T, M>
+where
+    T: FilePath,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.primary.file_path()
+    }
+}
+
+impl<T, M> AsyncRead for MirroredFile<T, M>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.project().primary.poll_read(cx, buf)
+    }
+}
+
+impl<T, M> AsyncWrite for MirroredFile<T, M>
+where
+    T: AsyncWrite,
+    M: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+
+        let this = self.as_mut().project();
+        let written = ready!(this.primary.poll_write(cx, buf))?;
+        if this.secondary.is_some() {
+            this.pending.extend_from_slice(&buf[..written]);
+        }
+
+        // Forward eagerly; data not forwarded yet is forwarded by the next operation.
+        if let Poll::Ready(Err(e)) = self.poll_forward(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+        ready!(self.as_mut().poll_secondary(cx, M::poll_flush))?;
+        self.project().primary.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+        ready!(self.as_mut().poll_secondary(cx, M::poll_shutdown))?;
+        self.project().primary.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,349 @@
+//! A memory-mapped backend, notably the [`MmapFile`] type.
+
+use crate::{FilePath, SetLen, SharedFile, SharedFileReader, SharedFileType, SharedFileWriter};
+use memmap2::{MmapMut, MmapOptions};
+use std::io::{ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::task::{Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// The minimum number of bytes the mapping grows by.
+const MIN_GROWTH: u64 = 64 * 1024;
+
+/// A type alias for a [`SharedFile`] wrapping an [`MmapFile`].
+pub type SharedMmapFile = SharedFile<MmapFile>;
+
+/// A type alias for a [`SharedFileReader`] wrapping an [`MmapFile`].
+pub type SharedMmapFileReader = SharedFileReader<MmapFile>;
+
+/// A type alias for a [`SharedFileWriter`] wrapping an [`MmapFile`].
+pub type SharedMmapFileWriter = SharedFileWriter<MmapFile>;
+
+/// A file mapped into memory using the [memmap2](https://docs.rs/memmap2) crate, usable as a
+/// backend of a [`SharedFile`].
+///
+/// The writer copies data into the mapping and readers copy it out, without any system calls
+/// once the mapping is large enough. Readers only see committed data. Committing data by
+/// flushing the writer schedules writing it back to disk (`msync(2)` with `MS_ASYNC`),
+/// while syncing the file waits for it.
+///
+/// The file is grown in steps while it is written, so it may be larger than the written data
+/// on disk. [`SharedFileType::sync_all`], e.g. when completing the writer, truncates it to the
+/// written length.
+///
+/// <div class="warning">The file must not be modified or truncated by other processes while it
+/// is mapped.</div>
+#[derive(Debug)]
+pub struct MmapFile {
+    /// The state shared by all handles.
+    inner: Arc<Inner>,
+    /// The position of this handle in the file.
+    position: u64,
+}
+
+/// The state shared by all handles of an [`MmapFile`].
+#[derive(Debug)]
+struct Inner {
+    /// The mapped file.
+    file: std::fs::File,
+    /// The path of the file.
+    path: PathBuf,
+    /// The mapping, which may extend beyond the written data.
+    map: RwLock<MmapMut>,
+    /// The number of bytes written to the mapping.
+    len: AtomicU64,
+}
+
+impl MmapFile {
+    /// Creates the file at the specified path, truncating it if it already exists.
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::with_capacity(path, 0).await
+    }
+
+    /// Creates the file at the specified path, truncating it if it already exists,
+    /// and maps `capacity` bytes of it up front.
+    pub async fn with_capacity<P: AsRef<Path>>(path: P, capacity: u64) -> io::Result<Self> {
+        let mut options = std::fs::OpenOptions::new();
+        options.read(true).write(true).create(true).truncate(true);
+        Self::create_with_options(path, capacity, options).await
+    }
+
+    /// Creates the file at the specified path with the specified permissions, e.g. `0o600`,
+    /// truncating it if it already exists, and maps `capacity` bytes of it up front.
+    ///
+    /// The permissions are subject to the umask of the process and only apply to newly
+    /// created files; the permissions of an existing file are left unchanged.
+    #[cfg_attr(docsrs, doc(cfg(unix)))]
+    #[cfg(unix)]
+    pub async fn with_capacity_and_mode<P: AsRef<Path>>(
+        path: P,
+        capacity: u64,
+        mode: u32,
+    ) -> io::Result<Self> {
+        use std::os::unix::fs::OpenOptionsExt;
+
+        let mut options = std::fs::OpenOptions::new();
+        options
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(mode);
+        Self::create_with_options(path, capacity, options).await
+    }
+
+    /// Opens the file at the specified path using the options and maps `capacity` bytes of it.
+    async fn create_with_options<P: AsRef<Path>>(
+        path: P,
+        capacity: u64,
+        options: std::fs::OpenOptions,
+    ) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        tokio::task::spawn_blocking(move || {
+            let file = options.open(&path)?;
+            file.set_len(capacity)?;
+            let map = map(&file, capacity)?;
+            Ok(Self {
+                inner: Arc::new(Inner {
+                    file,
+                    path,
+                    map: RwLock::new(map),
+                    len: AtomicU64::new(0),
+                }),
+                position: 0,
+            })
+        })
+        .await?
+    }
+
+    /// Gets the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Gets the number of bytes written, including data not yet committed by a writer.
+    pub fn len(&self) -> u64 {
+        self.inner.len.load(Ordering::Acquire)
+    }
+
+    /// Determines whether no bytes were written.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Gets the number of bytes currently mapped.
+    pub fn capacity(&self) -> u64 {
+        self.inner.read().len() as u64
+    }
+
+    /// Creates another handle to the same file, positioned at its start.
+    fn handle(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+            position: 0,
+        }
+    }
+}
+
+impl Inner {
+    /// Locks the mapping for reading.
+    fn read(&self) -> RwLockReadGuard<'_, MmapMut> {
+        self.map.read().expect("failed to lock mapping for reading")
+    }
+
+    /// Locks the mapping for writing.
+    fn write(&self) -> RwLockWriteGuard<'_, MmapMut> {
+        self.map
+            .write()
+            .expect("failed to lock mapping for writing")
+    }
+
+    /// Grows the file and the mapping to hold at least `end` bytes.
+    fn reserve(&self, map: &mut MmapMut, end: u64) -> io::Result<()> {
+        let capacity = map.len() as u64;
+        if end <= capacity {
+            return Ok(());
+        }
+        let capacity = end.max(capacity * 2).max(capacity + MIN_GROWTH);
+        self.remap(map, capacity)
+    }
+
+    /// Resizes the file and maps `capacity` bytes of it.
+    fn remap(&self, map: &mut MmapMut, capacity: u64) -> io::Result<()> {
+        self.file.set_len(capacity)?;
+        *map = self::map(&self.file, capacity)?;
+        Ok(())
+    }
+
+    /// Writes back the written data to disk. When syncing `metadata`, also truncates the
+    /// file to the written length.
+    fn sync(&self, metadata: bool) -> io::Result<()> {
+        if !metadata {
+            let map = self.read();
+            return flush(&map, self.len.load(Ordering::Acquire));
+        }
+
+        let mut map = self.write();
+        let len = self.len.load(Ordering::Acquire);
+        flush(&map, len)?;
+        if map.len() as u64 != len {
+            self.remap(&mut map, len)?;
+        }
+        self.file.sync_all()
+    }
+}
+
+/// Writes back the first `len` bytes of the mapping to disk, waiting for completion.
+fn flush(map: &MmapMut, len: u64) -> io::Result<()> {
+    if len == 0 {
+        return Ok(());
+    }
+    map.flush_range(0, len as usize)
+}
+
+/// Maps `len` bytes of the file.
+fn map(file: &std::fs::File, len: u64) -> io::Result<MmapMut> {
+    let len = usize::try_from(len).map_err(|_| {
+        io::Error::new(
+            ErrorKind::InvalidInput,
+            "The file exceeds the addressable memory",
+        )
+    })?;
+    // SAFETY: The file is owned by the mapped file and, as documented, must not be
+    // modified or truncated by other processes.
+    unsafe { MmapOptions::new().len(len).map_mut(file) }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for MmapFile {
+    type ReadHandle = MmapFile;
+    type WriteHandle = MmapFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(self.handle())
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(self.handle())
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.sync(true)).await?
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        let inner = Arc::clone(&self.inner);
+        tokio::task::spawn_blocking(move || inner.sync(false)).await?
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.inner.path)
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for MmapFile {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        let mut map = self.inner.write();
+        let len = self.inner.len.load(Ordering::Acquire);
+        if size < len {
+            // Clears the removed data so that extending the file again yields zeros.
+            map[size as usize..len as usize].fill(0);
+        }
+        self.inner.reserve(&mut map, size)?;
+        self.inner.len.store(size, Ordering::Release);
+        Ok(())
+    }
+}
+
+impl FilePath for MmapFile {
+    fn file_path(&self) -> &PathBuf {
+        &self.inner.path
+    }
+}
+
+impl AsyncRead for MmapFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let read = {
+            let map = this.inner.read();
+            let len = this.inner.len.load(Ordering::Acquire);
+            let start = this.position.min(len);
+            let count = (len - start).min(buf.remaining() as u64) as usize;
+            let start = start as usize;
+            buf.put_slice(&map[start..start + count]);
+            count
+        };
+        this.position += read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MmapFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let end = this.position + buf.len() as u64;
+        {
+            let mut map = this.inner.write();
+            this.inner.reserve(&mut map, end)?;
+            map[this.position as usize..end as usize].copy_from_slice(buf);
+            this.inner.len.fetch_max(end, Ordering::AcqRel);
+        }
+        this.position = end;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let len = self.len() as usize;
+        if len == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        Poll::Ready(self.inner.read().flush_async_range(0, len))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.poll_flush(cx)
+    }
+}
+
+impl AsyncSeek for MmapFile {
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        let position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => this.len().checked_add_signed(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+        };
+        match position {
+            Some(position) => {
+                this.position = position;
+                Ok(())
+            }
+            None => Err(io::Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
@@ -0,0 +1,297 @@
+//! A backend splitting files into multiple segment files, notably the [`MultiFile`] type.
+
+use crate::{SharedFile, SharedFileType};
+use std::ffi::OsString;
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::fs::{File, OpenOptions};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// The default size of each segment file, 512 MiB.
+pub const DEFAULT_SEGMENT_SIZE: u64 = 512 * 1024 * 1024;
+
+/// A type alias for a [`SharedFile`] wrapping a [`MultiFile`].
+pub type SharedMultiFile = SharedFile<MultiFile>;
+
+/// The future opening a segment file.
+type OpenFuture = Pin<Box<dyn Future<Output = io::Result<File>> + Send + Sync>>;
+
+/// A file split into segment files of a fixed size, usable as a backend of a [`SharedFile`],
+/// e.g. on file systems limiting the size of files.
+///
+/// The data is stored in files at the base path suffixed with the index of the segment,
+/// e.g. `stream.bin.00000`, `stream.bin.00001` and so on. The writer creates segment files
+/// as it reaches them, and readers open them as they read on. To readers, the segments
+/// appear as a single contiguous file.
+///
+/// Segment files are not deleted when the file is no longer used; use
+/// [`MultiFile::remove`] to delete them.
+#[derive(Debug)]
+pub struct MultiFile {
+    /// The state shared by all handles.
+    inner: Arc<Inner>,
+    /// The position of this handle in the logical file.
+    position: u64,
+    /// Whether this handle writes the file.
+    write: bool,
+    /// The open segment file and its index, if any.
+    segment: Option<(u64, File)>,
+    /// The segment file being opened and its index, if any.
+    opening: Option<(u64, Opening)>,
+}
+
+/// A segment file being opened.
+struct Opening(OpenFuture);
+
+impl std::fmt::Debug for Opening {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Opening").finish_non_exhaustive()
+    }
+}
+
+/// The state shared by all handles of a [`MultiFile`].
+#[derive(Debug)]
+struct Inner {
+    /// The base path of the segment files.
+    path: PathBuf,
+    /// The size of each segment file.
+    segment_size: u64,
+    /// The number of segment files created.
+    segments: AtomicU64,
+}
+
+impl MultiFile {
+    /// Creates a file split into segments of [`DEFAULT_SEGMENT_SIZE`] bytes at the base path.
+    pub async fn create<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::with_segment_size(path, DEFAULT_SEGMENT_SIZE).await
+    }
+
+    /// Creates a file split into segments of `segment_size` bytes at the base path.
+    ///
+    /// Segment files are created by the writer; existing segment files at the base path
+    /// are truncated once the writer reaches them.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `segment_size` is zero.
+    pub async fn with_segment_size<P: AsRef<Path>>(path: P, segment_size: u64) -> io::Result<Self> {
+        assert_ne!(segment_size, 0, "The segment size must not be zero");
+        let path = path.as_ref().to_path_buf();
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            if !tokio::fs::metadata(parent).await?.is_dir() {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    "The parent of the base path is not a directory",
+                ));
+            }
+        }
+
+        Ok(Self::handle(
+            Arc::new(Inner {
+                path,
+                segment_size,
+                segments: AtomicU64::new(0),
+            }),
+            false,
+        ))
+    }
+
+    /// Gets the base path of the segment files.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Gets the size of each segment file.
+    pub fn segment_size(&self) -> u64 {
+        self.inner.segment_size
+    }
+
+    /// Gets the number of segment files created so far.
+    pub fn segment_count(&self) -> u64 {
+        self.inner.segments.load(Ordering::Acquire)
+    }
+
+    /// Gets the path of the segment file with the specified index.
+    pub fn segment_path(&self, index: u64) -> PathBuf {
+        self.inner.segment_path(index)
+    }
+
+    /// Deletes all segment files created so far.
+    pub async fn remove(&self) -> io::Result<()> {
+        for index in 0..self.segment_count() {
+            match tokio::fs::remove_file(self.segment_path(index)).await {
+                Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates a handle to the file, positioned at its start.
+    fn handle(inner: Arc<Inner>, write: bool) -> Self {
+        Self {
+            inner,
+            position: 0,
+            write,
+            segment: None,
+            opening: None,
+        }
+    }
+
+    /// Ensures the segment file containing the current position is open, returning it along
+    /// with the number of bytes remaining in the segment.
+    fn poll_segment(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<(&mut File, usize)>> {
+        let index = self.position / self.inner.segment_size;
+        let offset = self.position % self.inner.segment_size;
+
+        if !matches!(self.segment, Some((current, _)) if current == index) {
+            // Previous writes must be done before the next segment can be committed.
+            if let Some((_, file)) = self.segment.as_mut().filter(|_| self.write) {
+                ready!(Pin::new(file).poll_flush(cx))?;
+            }
+            self.segment = None;
+
+            if !matches!(self.opening, Some((opening, _)) if opening == index) {
+                self.opening = Some((index, Opening(self.inner.open(index, self.write))));
+            }
+            let (_, Opening(future)) = self.opening.as_mut().expect("a segment is being opened");
+            let result = ready!(future.as_mut().poll(cx));
+            self.opening = None;
+            // Handles only move forward, so segments are always entered at their start.
+            self.segment = Some((index, result?));
+        }
+
+        let (_, file) = self.segment.as_mut().expect("the segment is open");
+        let remaining = usize::try_from(self.inner.segment_size - offset).unwrap_or(usize::MAX);
+        Poll::Ready(Ok((file, remaining)))
+    }
+}
+
+impl Inner {
+    /// Gets the path of the segment file with the specified index.
+    fn segment_path(&self, index: u64) -> PathBuf {
+        let mut path = OsString::from(self.path.as_os_str());
+        path.push(format!(".{index:05}"));
+        PathBuf::from(path)
+    }
+
+    /// Opens the segment file with the specified index, creating it when writing.
+    fn open(self: &Arc<Self>, index: u64, write: bool) -> OpenFuture {
+        let inner = Arc::clone(self);
+        Box::pin(async move {
+            let path = inner.segment_path(index);
+            if !write {
+                return File::open(path).await;
+            }
+
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(path)
+                .await?;
+            inner.segments.fetch_max(index + 1, Ordering::AcqRel);
+            Ok(file)
+        })
+    }
+
+    /// Syncs all segment files created so far.
+    async fn sync(&self, metadata: bool) -> io::Result<()> {
+        for index in 0..self.segments.load(Ordering::Acquire) {
+            let file = OpenOptions::new()
+                .write(true)
+                .open(self.segment_path(index))
+                .await?;
+            if metadata {
+                file.sync_all().await?;
+            } else {
+                file.sync_data().await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for MultiFile {
+    type ReadHandle = MultiFile;
+    type WriteHandle = MultiFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(Self::handle(Arc::clone(&self.inner), false))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(Self::handle(Arc::clone(&self.inner), true))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.inner.sync(true).await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.inner.sync(false).await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.inner.path)
+    }
+}
+
+impl AsyncRead for MultiFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let (file, remaining) = ready!(this.poll_segment(cx))?;
+        let mut smaller_buf = buf.take(remaining);
+        ready!(Pin::new(file).poll_read(cx, &mut smaller_buf))?;
+        let read = smaller_buf.filled().len();
+
+        // SAFETY: The bytes were initialized by reading into the smaller buffer.
+        unsafe { buf.assume_init(read) };
+        buf.advance(read);
+        this.position += read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for MultiFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let (file, remaining) = ready!(this.poll_segment(cx))?;
+        let len = buf.len().min(remaining);
+        let written = ready!(Pin::new(file).poll_write(cx, &buf[..len]))?;
+        this.position += written as u64;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().segment.as_mut() {
+            Some((_, file)) => Pin::new(file).poll_flush(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut().segment.as_mut() {
+            Some((_, file)) => Pin::new(file).poll_shutdown(cx),
+            None => Poll::Ready(Ok(())),
+        }
+    }
+}
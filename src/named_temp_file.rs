@@ -0,0 +1,82 @@
+//! Implementations for [`NamedTempFile`] of the [tempfile](https://docs.rs/tempfile) crate.
+
+use crate::{
+    AsyncNewFile, FsFile, NewFile, SetLen, SharedFile, SharedFileReader, SharedFileType,
+    SharedFileWriter,
+};
+use std::path::Path;
+use tempfile::NamedTempFile;
+use tokio::fs::File;
+use tokio::io;
+
+/// A type alias for a [`SharedFile`] wrapping a [`NamedTempFile`].
+pub type SharedNamedTempFile = SharedFile<NamedTempFile>;
+
+/// A type alias for a [`SharedFileReader`] wrapping a [`NamedTempFile`].
+pub type SharedNamedTempFileReader = SharedFileReader<NamedTempFile, FsFile>;
+
+/// A type alias for a [`SharedFileWriter`] wrapping a [`NamedTempFile`].
+pub type SharedNamedTempFileWriter = SharedFileWriter<NamedTempFile, FsFile>;
+
+/// Readers and writers open their own [`FsFile`] handles to the path of the temporary file. The file is deleted when the [`NamedTempFile`] is dropped, i.e. once
+/// the [`SharedFile`] and all of its readers and writers are dropped.
+#[async_trait::async_trait]
+impl SharedFileType for NamedTempFile {
+    type ReadHandle = FsFile;
+    type WriteHandle = FsFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        FsFile::open_read_only(self.path()).await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        FsFile::open(self.path()).await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        async_file(self)?.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        async_file(self)?.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(self.path())
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for NamedTempFile {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        async_file(self)?.set_len(size).await
+    }
+}
+
+impl NewFile for NamedTempFile {
+    type Target = NamedTempFile;
+    type Error = io::Error;
+
+    fn new() -> Result<Self::Target, Self::Error> {
+        NamedTempFile::new()
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncNewFile for NamedTempFile {
+    type Target = NamedTempFile;
+    type Error = io::Error;
+
+    async fn new_async() -> Result<Self::Target, Self::Error> {
+        tokio::task::spawn_blocking(NamedTempFile::new).await?
+    }
+}
+
+/// Duplicates the handle of the temporary file for use with blocking-aware async operations.
+fn async_file(file: &NamedTempFile) -> io::Result<File> {
+    Ok(File::from_std(file.as_file().try_clone()?))
+}
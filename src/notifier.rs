@@ -0,0 +1,73 @@
+//! Strategies for waking up readers, notably the [`WakerMap`] type.
+
+use crate::{ReaderNotifier, ReaderPriority};
+use std::cmp::Reverse;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::Mutex;
+use std::task::Waker;
+use uuid::Uuid;
+
+/// The default [`ReaderNotifier`], storing the wakers of all waiting readers in a map.
+///
+/// Readers of higher [`ReaderPriority`] are woken up first.
+#[derive(Debug, Default)]
+pub struct WakerMap {
+    /// The wakers of all waiting readers, along with their priorities.
+    wakers: Mutex<HashMap<Uuid, (ReaderPriority, Waker)>>,
+}
+
+impl ReaderNotifier for WakerMap {
+    fn register(&self, id: Uuid, priority: ReaderPriority, waker: &Waker) {
+        let mut lock = self
+            .wakers
+            .lock()
+            .expect("failed to lock waker vector for reading");
+
+        lock.entry(id)
+            .and_modify(|(p, w)| {
+                *p = priority;
+                w.clone_from(waker)
+            })
+            .or_insert((priority, waker.clone()));
+    }
+
+    fn remove(&self, id: &Uuid) {
+        let mut lock = self.wakers.lock().expect("failed to get lock for readers");
+        lock.remove(id);
+    }
+
+    fn notify_all(&self) {
+        let mut wakers: Vec<_> = {
+            let mut lock = self
+                .wakers
+                .lock()
+                .expect("failed to lock waker vector for writing");
+            lock.drain().map(|(_id, entry)| entry).collect()
+        };
+
+        #[cfg(feature = "tracing")]
+        if !wakers.is_empty() {
+            tracing::trace!(readers = wakers.len(), "waking readers");
+        }
+
+        // Wake readers of higher priority first.
+        wakers.sort_by_key(|(priority, _)| Reverse(*priority));
+        wakers.into_iter().for_each(|(_priority, w)| w.wake());
+    }
+}
+
+/// The strategy waking up the readers of a file.
+pub(crate) struct Notifier(pub(crate) Box<dyn ReaderNotifier>);
+
+impl Default for Notifier {
+    fn default() -> Self {
+        Self(Box::<WakerMap>::default())
+    }
+}
+
+impl Debug for Notifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Notifier").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,133 @@
+//! A backend discarding all data, notably the [`NullFile`] type.
+
+use crate::{AsyncNewFile, NewFile, SetLen, SharedFile, SharedFileType};
+use std::convert::Infallible;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+/// A type alias for a [`SharedFile`] wrapping a [`NullFile`].
+pub type SharedNullFile = SharedFile<NullFile>;
+
+/// What readers of a [`NullFile`] receive.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NullRead {
+    /// Readers receive as many zeros as were committed.
+    #[default]
+    Zeros,
+    /// Readers receive no data, i.e. they wait for commits until the file is completed
+    /// and then reach its end.
+    Eof,
+}
+
+/// A backend discarding all data written to it, e.g. to benchmark committing data and
+/// waking up readers independently of the speed of the disk.
+///
+/// Writers and readers take part in committing data and waiting for it as with any other
+/// backend; what readers receive is configured using [`NullRead`].
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NullFile {
+    /// What readers receive.
+    read: NullRead,
+}
+
+impl NullFile {
+    /// Creates a file whose readers receive what is specified by `read`.
+    pub const fn new(read: NullRead) -> Self {
+        Self { read }
+    }
+
+    /// Gets what readers receive.
+    pub const fn read(&self) -> NullRead {
+        self.read
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for NullFile {
+    type ReadHandle = NullFile;
+    type WriteHandle = NullFile;
+    type OpenError = Infallible;
+    type SyncError = Infallible;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(*self)
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        Ok(*self)
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        Ok(())
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for NullFile {
+    type SetLenError = Infallible;
+
+    async fn set_len(&self, _size: u64) -> Result<(), Self::SetLenError> {
+        Ok(())
+    }
+}
+
+impl NewFile for NullFile {
+    type Target = NullFile;
+    type Error = Infallible;
+
+    fn new() -> Result<Self::Target, Self::Error> {
+        Ok(Self::default())
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncNewFile for NullFile {
+    type Target = NullFile;
+    type Error = Infallible;
+
+    async fn new_async() -> Result<Self::Target, Self::Error> {
+        Ok(Self::default())
+    }
+}
+
+impl AsyncRead for NullFile {
+    /// Fills the buffer with zeros, or reads nothing, depending on [`NullRead`].
+    ///
+    /// Readers only ask for as many bytes as were committed.
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        if self.read == NullRead::Zeros {
+            let len = buf.remaining();
+            buf.initialize_unfilled().fill(0);
+            buf.advance(len);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for NullFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,412 @@
+//! Uploads to cloud object storage, notably the [`ObjectStoreFile`] type
+//! and [`SharedFile::spawn_upload`].
+
+use crate::errors::UploadError;
+use crate::{FilePath, SharedFile, SharedFileType};
+use object_store::path::Path as ObjectPath;
+use object_store::{MultipartUpload, ObjectStore, PutResult, WriteMultipart};
+use pin_project::pin_project;
+use std::fmt::{Debug, Formatter};
+use std::future::Future;
+use std::io::{Error, ErrorKind};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, ReadBuf};
+use tokio::task::JoinHandle;
+
+/// The default size of the uploaded parts in bytes.
+pub const DEFAULT_UPLOAD_PART_SIZE: usize = 5 * 1024 * 1024;
+
+/// The default number of parts uploaded concurrently.
+const DEFAULT_MAX_CONCURRENCY: usize = 8;
+
+/// The maximum size of the buffer used for reading the file to upload.
+const UPLOAD_BUFFER_SIZE: usize = 64 * 1024;
+
+impl<T> SharedFile<T>
+where
+    T: SharedFileType + Send + Sync + 'static,
+    T::ReadHandle: AsyncRead + Unpin + Send + Sync + 'static,
+{
+    /// Uploads the file to the specified location in the store in a background task, using a
+    /// multipart upload with parts of `part_size` bytes, e.g. to Amazon S3.
+    ///
+    /// Unlike [`ObjectStoreFile`], this works with any backing file: the task reads the file
+    /// like any other reader, so that parts are uploaded as the writer commits data. Once the
+    /// writer completes the file, the remaining data is uploaded and the upload is completed.
+    /// If writing the file fails, the upload is aborted.
+    ///
+    /// The returned task resolves to the number of bytes uploaded. This must be called from
+    /// within a Tokio runtime.
+    ///
+    /// Note that stores may impose limits on the part size, e.g. Amazon S3 requires all
+    /// but the last part to be at least 5 MiB.
+    ///
+    /// ## Arguments
+    ///
+    /// * `store` - The store to upload to.
+    /// * `location` - The location of the object in the store.
+    /// * `part_size` - The size of the uploaded parts in bytes.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `part_size` is zero.
+    pub async fn spawn_upload(
+        &self,
+        store: Arc<dyn ObjectStore>,
+        location: ObjectPath,
+        part_size: usize,
+    ) -> Result<JoinHandle<Result<u64, UploadError>>, T::OpenError> {
+        assert_ne!(part_size, 0, "The part size must not be zero");
+        let mut reader = self.reader().await?;
+        Ok(tokio::spawn(async move {
+            let upload = store.put_multipart(&location).await?;
+            let mut upload = WriteMultipart::new_with_chunk_size(upload, part_size);
+
+            let mut buffer = vec![0; UPLOAD_BUFFER_SIZE.min(part_size)];
+            let mut uploaded = 0;
+            loop {
+                let read = match reader.read(&mut buffer).await {
+                    Ok(0) => break,
+                    Ok(read) => read,
+                    Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        // The upload failing to abort is not the root cause.
+                        let _ = upload.abort().await;
+                        return Err(UploadError::Read(e));
+                    }
+                };
+
+                upload.wait_for_capacity(DEFAULT_MAX_CONCURRENCY).await?;
+                upload.write(&buffer[..read]);
+                uploaded += read as u64;
+            }
+
+            upload.finish().await?;
+            Ok(uploaded)
+        }))
+    }
+}
+
+/// A future starting a multipart upload.
+type StartFuture =
+    Pin<Box<dyn Future<Output = object_store::Result<Box<dyn MultipartUpload>>> + Send>>;
+
+/// A future completing a multipart upload.
+type FinishFuture = Pin<Box<dyn Future<Output = object_store::Result<PutResult>> + Send>>;
+
+/// A file uploading all data written to it to an [`ObjectStore`], such as Amazon S3,
+/// Google Cloud Storage or Azure Blob Storage, using the
+/// [object_store](https://docs.rs/object_store) crate.
+///
+/// Use it as the backing of a [`SharedFile`](crate::SharedFile), e.g.
+/// `SharedFile::from(ObjectStoreFile::new(file, store, location))`. Data is written to the
+/// wrapped file, from which readers stream it locally, and is uploaded in parts of
+/// [`part_size`](Self::with_part_size) bytes in the background as it is written.
+///
+/// Shut the writer down (see [`ShutdownBehavior`](crate::ShutdownBehavior)) to upload the
+/// remaining data and complete the multipart upload; shutting down fails if uploading failed.
+/// Every writer starts a new upload, replacing the object once it completes.
+#[pin_project]
+pub struct ObjectStoreFile<T> {
+    /// The file to read from or write to.
+    #[pin]
+    file: T,
+    /// The store to upload to.
+    store: Arc<dyn ObjectStore>,
+    /// The location of the object in the store.
+    location: ObjectPath,
+    /// The size of the uploaded parts in bytes.
+    part_size: usize,
+    /// The maximum number of parts uploaded concurrently.
+    max_concurrency: usize,
+    /// How the file was opened.
+    mode: Mode,
+}
+
+/// The mode of an [`ObjectStoreFile`], depending on how the file was opened.
+enum Mode {
+    /// The original file, which is neither read nor written.
+    Original,
+    /// The file was opened for reading.
+    Reading,
+    /// The file was opened for writing. The mutex only makes the file [`Sync`] and is never contended.
+    Writing(Mutex<Upload>),
+}
+
+/// The state of the upload of a file opened for writing.
+enum Upload {
+    /// The multipart upload is being started.
+    Starting(StartFuture),
+    /// Parts are being uploaded.
+    Writing(WriteMultipart),
+    /// The multipart upload is being completed.
+    Finishing(FinishFuture),
+    /// The multipart upload completed.
+    Finished,
+    /// Uploading failed.
+    Failed,
+}
+
+impl<T> ObjectStoreFile<T> {
+    /// Wraps the file, uploading it to the specified location in the store.
+    pub fn new(file: T, store: Arc<dyn ObjectStore>, location: ObjectPath) -> Self {
+        Self {
+            file,
+            store,
+            location,
+            part_size: DEFAULT_UPLOAD_PART_SIZE,
+            max_concurrency: DEFAULT_MAX_CONCURRENCY,
+            mode: Mode::Original,
+        }
+    }
+
+    /// Sets the size of the uploaded parts in bytes, [`DEFAULT_UPLOAD_PART_SIZE`] by default.
+    ///
+    /// Note that stores may impose limits on the part size, e.g. Amazon S3 requires all
+    /// but the last part to be at least 5 MiB.
+    pub fn with_part_size(mut self, part_size: usize) -> Self {
+        self.part_size = part_size.max(1);
+        self
+    }
+
+    /// Sets the maximum number of parts uploaded concurrently, 8 by default.
+    ///
+    /// Writing waits for uploads to finish once this many parts are in flight.
+    pub fn with_max_concurrency(mut self, max_concurrency: usize) -> Self {
+        self.max_concurrency = max_concurrency.max(1);
+        self
+    }
+
+    /// Gets a reference to the underlying file.
+    pub fn get_ref(&self) -> &T {
+        &self.file
+    }
+
+    /// Gets the store the file is uploaded to.
+    pub fn store(&self) -> &Arc<dyn ObjectStore> {
+        &self.store
+    }
+
+    /// Gets the location of the object in the store.
+    pub fn location(&self) -> &ObjectPath {
+        &self.location
+    }
+
+    /// Creates a handle to the specified file, sharing the configuration of this one.
+    fn handle(&self, file: T, mode: Mode) -> Self {
+        Self {
+            file,
+            store: Arc::clone(&self.store),
+            location: self.location.clone(),
+            part_size: self.part_size,
+            max_concurrency: self.max_concurrency,
+            mode,
+        }
+    }
+}
+
+impl<T> Debug for ObjectStoreFile<T>
+where
+    T: Debug,
+{
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ObjectStoreFile")
+            .field("file", &self.file)
+            .field("store", &self.store)
+            .field("location", &self.location)
+            .field("part_size", &self.part_size)
+            .field("max_concurrency", &self.max_concurrency)
+            .finish_non_exhaustive()
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for ObjectStoreFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + AsyncRead + AsyncWrite + Send + Sync,
+{
+    type ReadHandle = ObjectStoreFile<T>;
+    type WriteHandle = ObjectStoreFile<T>;
+    type OpenError = T::OpenError;
+    type SyncError = T::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(self.handle(self.file.open_ro().await?, Mode::Reading))
+    }
+
+    /// Opens the file for writing. The multipart upload is started when data is first written.
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        let store = Arc::clone(&self.store);
+        let location = self.location.clone();
+        let start: StartFuture = Box::pin(async move { store.put_multipart(&location).await });
+        let mode = Mode::Writing(Mutex::new(Upload::Starting(start)));
+        Ok(self.handle(self.file.open_rw().await?, mode))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        self.file.diagnostic_path()
+    }
+}
+
+impl<T> FilePath for ObjectStoreFile<T>
+where
+    T: FilePath,
+{
+    fn file_path(&self) -> &PathBuf {
+        self.file.file_path()
+    }
+}
+
+impl Upload {
+    /// Waits until the upload accepts more data, starting it if needed.
+    fn poll_writable(
+        &mut self,
+        cx: &mut Context<'_>,
+        part_size: usize,
+        max_concurrency: usize,
+    ) -> Poll<io::Result<&mut WriteMultipart>> {
+        if let Upload::Starting(start) = self {
+            let result = ready!(start.as_mut().poll(cx));
+            *self = match result {
+                Ok(upload) => {
+                    Upload::Writing(WriteMultipart::new_with_chunk_size(upload, part_size))
+                }
+                Err(e) => {
+                    *self = Upload::Failed;
+                    return Poll::Ready(Err(upload_error(e)));
+                }
+            };
+        }
+
+        match self {
+            Upload::Writing(upload) => {
+                if let Err(e) = ready!(upload.poll_for_capacity(cx, max_concurrency)) {
+                    *self = Upload::Failed;
+                    return Poll::Ready(Err(upload_error(e)));
+                }
+                let Upload::Writing(upload) = self else {
+                    unreachable!("the upload is in progress");
+                };
+                Poll::Ready(Ok(upload))
+            }
+            Upload::Failed => Poll::Ready(Err(Error::new(ErrorKind::Other, "Uploading failed"))),
+            _ => Poll::Ready(Err(Error::new(
+                ErrorKind::Other,
+                "The upload was already completed",
+            ))),
+        }
+    }
+
+    /// Uploads the remaining data and completes the upload.
+    fn poll_finish(&mut self, cx: &mut Context<'_>, part_size: usize) -> Poll<io::Result<()>> {
+        loop {
+            match std::mem::replace(self, Upload::Failed) {
+                Upload::Starting(mut start) => match start.as_mut().poll(cx) {
+                    Poll::Ready(Ok(upload)) => {
+                        let upload = WriteMultipart::new_with_chunk_size(upload, part_size);
+                        *self = Upload::Writing(upload);
+                    }
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(upload_error(e))),
+                    Poll::Pending => {
+                        *self = Upload::Starting(start);
+                        return Poll::Pending;
+                    }
+                },
+                Upload::Writing(upload) => *self = Upload::Finishing(Box::pin(upload.finish())),
+                Upload::Finishing(mut finish) => match finish.as_mut().poll(cx) {
+                    Poll::Ready(Ok(_)) => *self = Upload::Finished,
+                    Poll::Ready(Err(e)) => return Poll::Ready(Err(upload_error(e))),
+                    Poll::Pending => {
+                        *self = Upload::Finishing(finish);
+                        return Poll::Pending;
+                    }
+                },
+                Upload::Finished => {
+                    *self = Upload::Finished;
+                    return Poll::Ready(Ok(()));
+                }
+                Upload::Failed => {
+                    return Poll::Ready(Err(Error::new(ErrorKind::Other, "Uploading failed")))
+                }
+            }
+        }
+    }
+}
+
+/// Converts an error of the store into an I/O error.
+fn upload_error(error: object_store::Error) -> Error {
+    Error::new(ErrorKind::Other, error)
+}
+
+impl<T> AsyncRead for ObjectStoreFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let Mode::Reading = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for reading",
+            )));
+        };
+        this.file.poll_read(cx, buf)
+    }
+}
+
+impl<T> AsyncWrite for ObjectStoreFile<T>
+where
+    T: AsyncWrite,
+{
+    /// Writes the data to the file and queues it for uploading, waiting if too many
+    /// parts are in flight.
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let Mode::Writing(upload) = this.mode else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for writing",
+            )));
+        };
+
+        let upload = upload.get_mut().expect("failed to lock upload");
+        let upload = ready!(upload.poll_writable(cx, *this.part_size, *this.max_concurrency))?;
+        let written = ready!(this.file.poll_write(cx, buf))?;
+        upload.write(&buf[..written]);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().file.poll_flush(cx)
+    }
+
+    /// Uploads the remaining data and completes the upload, then shuts down the file.
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if let Mode::Writing(upload) = this.mode {
+            let upload = upload.get_mut().expect("failed to lock upload");
+            ready!(upload.poll_finish(cx, *this.part_size))?;
+        }
+        this.file.poll_shutdown(cx)
+    }
+}
@@ -0,0 +1,263 @@
+//! Extending read-only files, notably the [`OverlayFile`] type.
+
+use crate::errors::OverlayError;
+use crate::{SharedFile, SharedFileType, WriteState};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::fs::File;
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+
+/// A read-only base file with an append-only delta file layered on top, presented as
+/// a single file, e.g. to extend an immutable cached artifact without copying it.
+///
+/// Readers read the base file followed by the delta file. Writers only write to the delta
+/// file; the base file is never modified. Create the shared file using
+/// [`SharedFile::from_overlay`], which presents the base file as completed, and extend it
+/// using [`SharedFile::append_writer`].
+#[pin_project]
+#[derive(Debug)]
+pub struct OverlayFile<T> {
+    /// The delta file to read from or write to.
+    #[pin]
+    delta: T,
+    /// The path of the base file.
+    base_path: PathBuf,
+    /// The length of the base file.
+    base_len: u64,
+    /// The open base file, for read handles.
+    base: Option<File>,
+    /// The position of this handle in the logical file.
+    position: u64,
+    /// The position being seeked to, if any.
+    seek: Option<u64>,
+}
+
+impl<T> OverlayFile<T> {
+    /// Layers the delta file on top of the base file at the specified path.
+    ///
+    /// The delta file is expected to be empty; its data is appended to the base file
+    /// as it is written.
+    pub async fn new<P: AsRef<Path>>(base: P, delta: T) -> io::Result<Self> {
+        let base_path = base.as_ref().to_path_buf();
+        let base_len = tokio::fs::metadata(&base_path).await?.len();
+        Ok(Self::handle(delta, base_path, base_len, None))
+    }
+
+    /// Gets the path of the base file.
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Gets the length of the base file.
+    pub fn base_len(&self) -> u64 {
+        self.base_len
+    }
+
+    /// Gets a reference to the delta file.
+    pub fn get_ref(&self) -> &T {
+        &self.delta
+    }
+
+    /// Creates a handle positioned at the start of the logical file.
+    fn handle(delta: T, base_path: PathBuf, base_len: u64, base: Option<File>) -> Self {
+        Self {
+            delta,
+            base_path,
+            base_len,
+            base,
+            position: 0,
+            seek: None,
+        }
+    }
+}
+
+impl<T> SharedFile<OverlayFile<T>> {
+    /// Creates a shared file presenting the base file of the overlay as completed, such that
+    /// readers can read it right away. Use [`append_writer`](Self::append_writer) to extend it
+    /// by writing to the delta file.
+    pub fn from_overlay(file: OverlayFile<T>) -> Self {
+        let base_len = file.base_len;
+        let file = Self::from(file);
+        file.sentinel.state.store(WriteState::Completed(base_len));
+        file
+    }
+}
+
+#[async_trait::async_trait]
+impl<T> SharedFileType for OverlayFile<T>
+where
+    T: SharedFileType<ReadHandle = T, WriteHandle = T> + Send + Sync,
+{
+    type ReadHandle = OverlayFile<T>;
+    type WriteHandle = OverlayFile<T>;
+    type OpenError = OverlayError<T::OpenError>;
+    type SyncError = T::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        let base = File::open(&self.base_path)
+            .await
+            .map_err(OverlayError::Base)?;
+        let delta = self.delta.open_ro().await.map_err(OverlayError::Delta)?;
+        Ok(Self::handle(
+            delta,
+            self.base_path.clone(),
+            self.base_len,
+            Some(base),
+        ))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        let delta = self.delta.open_rw().await.map_err(OverlayError::Delta)?;
+        Ok(Self::handle(
+            delta,
+            self.base_path.clone(),
+            self.base_len,
+            None,
+        ))
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.delta.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.delta.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.base_path)
+    }
+}
+
+impl<T> AsyncRead for OverlayFile<T>
+where
+    T: AsyncRead,
+{
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.project();
+        if *this.position >= *this.base_len {
+            let filled = buf.filled().len();
+            ready!(this.delta.poll_read(cx, buf))?;
+            *this.position += (buf.filled().len() - filled) as u64;
+            return Poll::Ready(Ok(()));
+        }
+
+        let Some(base) = this.base.as_mut() else {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::Unsupported,
+                "The file was not opened for reading",
+            )));
+        };
+        let remaining = usize::try_from(*this.base_len - *this.position).unwrap_or(usize::MAX);
+        let mut smaller_buf = buf.take(remaining);
+        ready!(Pin::new(base).poll_read(cx, &mut smaller_buf))?;
+        let read = smaller_buf.filled().len();
+        if read == 0 {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::UnexpectedEof,
+                "The base file is shorter than when it was opened",
+            )));
+        }
+
+        // SAFETY: The bytes were initialized by reading into the smaller buffer.
+        unsafe { buf.assume_init(read) };
+        buf.advance(read);
+        *this.position += read as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T> AsyncWrite for OverlayFile<T>
+where
+    T: AsyncWrite,
+{
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        if *this.position < *this.base_len {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "The base file is read-only",
+            )));
+        }
+        let written = ready!(this.delta.poll_write(cx, buf))?;
+        *this.position += written as u64;
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().delta.poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().delta.poll_shutdown(cx)
+    }
+}
+
+impl<T> AsyncSeek for OverlayFile<T>
+where
+    T: AsyncSeek,
+{
+    /// Seeks the logical file. Seeking relative to its end is not supported, and only read
+    /// handles can seek into the base file.
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.project();
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+            SeekFrom::End(_) => {
+                return Err(Error::new(
+                    ErrorKind::Unsupported,
+                    "Seeking relative to the end is not supported",
+                ))
+            }
+        };
+        let Some(target) = target else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        let delta_offset = target.saturating_sub(*this.base_len);
+        if target < *this.base_len {
+            let Some(base) = this.base.as_mut() else {
+                return Err(Error::new(
+                    ErrorKind::PermissionDenied,
+                    "The base file is read-only",
+                ));
+            };
+            Pin::new(base).start_seek(SeekFrom::Start(target))?;
+        }
+        this.delta.start_seek(SeekFrom::Start(delta_offset))?;
+        *this.seek = Some(target);
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.project();
+        let Some(target) = *this.seek else {
+            return Poll::Ready(Ok(*this.position));
+        };
+        if target < *this.base_len {
+            if let Some(base) = this.base.as_mut() {
+                ready!(Pin::new(base).poll_complete(cx))?;
+            }
+        }
+        ready!(this.delta.poll_complete(cx))?;
+        *this.seek = None;
+        *this.position = target;
+        Poll::Ready(Ok(target))
+    }
+}
@@ -0,0 +1,146 @@
+//! Persisting completed files to a final destination, notably
+//! [`SharedFileWriter::complete_and_persist`] and [`SharedFile::persist`].
+
+use crate::errors::PersistError;
+use crate::{
+    FilePath, Persist, Sentinel, SharedFile, SharedFileType, SharedFileWriter, WriteState,
+};
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWrite;
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Completes the writing operation and atomically moves the file to the specified path.
+    ///
+    /// The file is flushed and synced to disk as in [`complete`](Self::complete) before it is
+    /// persisted, so that a successfully assembled file can be promoted to its final destination
+    /// without copying it. Afterwards, [`SharedFile::file_path`](FilePath::file_path) reports
+    /// the new path. Readers remain valid.
+    ///
+    /// If persisting the file fails, the file is still completed and can be persisted again
+    /// using [`SharedFile::persist`].
+    pub async fn complete_and_persist<P>(self, path: P) -> Result<(), PersistError<T::PersistError>>
+    where
+        T: Persist,
+        W: SharedFileType + AsyncWrite,
+        W::SyncError: std::error::Error + Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        let file = SharedFile {
+            sentinel: self.sentinel_arc().clone(),
+        };
+        self.complete().await.map_err(PersistError::Complete)?;
+        file.persist(path).await
+    }
+
+    /// Completes the writing operation and atomically moves the file into the directory,
+    /// named after the hex-encoded digest of its contents, returning the new path.
+    ///
+    /// This requires the digest to be tracked using [`with_digest`](Self::with_digest),
+    /// e.g. with [`Sha256`](https://docs.rs/sha2/latest/sha2/type.Sha256.html), and fails with
+    /// [`PersistError::MissingDigest`] otherwise. See [`SharedFile::persist_content_addressed`].
+    pub async fn complete_and_persist_content_addressed<P>(
+        self,
+        dir: P,
+    ) -> Result<PathBuf, PersistError<T::PersistError>>
+    where
+        T: Persist,
+        W: SharedFileType + AsyncWrite,
+        W::SyncError: std::error::Error + Send + Sync + 'static,
+        P: AsRef<Path>,
+    {
+        let file = SharedFile {
+            sentinel: self.sentinel_arc().clone(),
+        };
+        self.complete().await.map_err(PersistError::Complete)?;
+        file.persist_content_addressed(dir).await
+    }
+}
+
+impl<T> SharedFile<T> {
+    /// Atomically moves the completed file to the specified path.
+    ///
+    /// Afterwards, [`file_path`](FilePath::file_path) reports the new path. Fails with
+    /// [`PersistError::NotCompleted`] if writing the file did not complete successfully,
+    /// and with [`PersistError::AlreadyPersisted`] if the file was already persisted.
+    ///
+    /// See [`SharedFileWriter::complete_and_persist`].
+    pub async fn persist<P>(&self, path: P) -> Result<(), PersistError<T::PersistError>>
+    where
+        T: Persist,
+        P: AsRef<Path>,
+    {
+        let WriteState::Completed(_) = self.sentinel.state.load() else {
+            return Err(PersistError::NotCompleted);
+        };
+
+        let path = path.as_ref();
+        let mut persisted = false;
+        self.sentinel
+            .persisted_path
+            .get_or_try_init(|| async {
+                self.sentinel
+                    .original
+                    .persist(path)
+                    .await
+                    .map_err(PersistError::Persist)?;
+                persisted = true;
+                Ok(path.to_path_buf())
+            })
+            .await?;
+
+        if persisted {
+            Ok(())
+        } else {
+            Err(PersistError::AlreadyPersisted)
+        }
+    }
+
+    /// Atomically moves the completed file into the directory, named after the hex-encoded
+    /// [digest](Self::digest) of its contents, returning the new path.
+    ///
+    /// Files with identical contents are stored at the same path, such that identical
+    /// artifacts are deduplicated; an existing file at the path is replaced. Afterwards,
+    /// [`persisted_path`](Self::persisted_path) reports the new path.
+    ///
+    /// Fails with [`PersistError::MissingDigest`] if the writer did not track a digest using
+    /// [`SharedFileWriter::with_digest`], and otherwise as [`persist`](Self::persist).
+    pub async fn persist_content_addressed<P>(
+        &self,
+        dir: P,
+    ) -> Result<PathBuf, PersistError<T::PersistError>>
+    where
+        T: Persist,
+        P: AsRef<Path>,
+    {
+        let WriteState::Completed(_) = self.sentinel.state.load() else {
+            return Err(PersistError::NotCompleted);
+        };
+        let digest = self.digest().ok_or(PersistError::MissingDigest)?;
+
+        let mut name = String::with_capacity(digest.len() * 2);
+        for byte in digest {
+            write!(name, "{byte:02x}").expect("writing to a string does not fail");
+        }
+        let path = dir.as_ref().join(name);
+        self.persist(&path).await?;
+        Ok(path)
+    }
+
+    /// Gets the path the file was persisted to, if it was.
+    pub fn persisted_path(&self) -> Option<&PathBuf> {
+        self.sentinel.persisted_path.get()
+    }
+}
+
+impl<T> Sentinel<T>
+where
+    T: FilePath,
+{
+    /// Gets the path the file was persisted to, or the path of the original file.
+    pub(crate) fn file_path(&self) -> &PathBuf {
+        self.persisted_path
+            .get()
+            .unwrap_or_else(|| self.original.file_path())
+    }
+}
@@ -0,0 +1,30 @@
+//! Reader priority functionality, notably the [`ReaderPriority`] type.
+
+/// The priority of a reader when it is woken up after the writer committed data.
+///
+/// Readers of higher priority are woken up before readers of lower priority, so
+/// that e.g. interactive consumers are scheduled ahead of bulk background consumers.
+/// Note that this only determines the order in which the readers' tasks are woken up;
+/// how they are scheduled afterwards is up to the runtime.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ReaderPriority {
+    /// The reader is woken up after all other readers.
+    Low,
+    /// The reader is woken up after readers of high priority. This is the default.
+    #[default]
+    Normal,
+    /// The reader is woken up before all other readers.
+    High,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ordering() {
+        assert!(ReaderPriority::High > ReaderPriority::Normal);
+        assert!(ReaderPriority::Normal > ReaderPriority::Low);
+        assert_eq!(ReaderPriority::default(), ReaderPriority::Normal);
+    }
+}
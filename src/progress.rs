@@ -0,0 +1,60 @@
+//! Progress reporting functionality, notably the [`Progress`] type.
+
+use crate::{FileSize, SharedFileReader};
+use tokio::sync::watch;
+
+/// A subscription to the progress of the writer, see [`SharedFileReader::progress`].
+///
+/// Updates are published whenever the writer commits data, completes or fails.
+/// Intermediate updates may be skipped if the subscriber is slower than the writer.
+#[derive(Debug)]
+pub struct Progress {
+    /// The receiver of state updates.
+    receiver: watch::Receiver<FileSize>,
+    /// Whether the current state was already yielded once.
+    started: bool,
+    /// Whether a final state was already yielded.
+    done: bool,
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Subscribes to the progress of the writer.
+    pub fn progress(&self) -> Progress {
+        Progress::new(self.sentinel().progress.subscribe())
+    }
+}
+
+impl Progress {
+    pub(crate) fn new(receiver: watch::Receiver<FileSize>) -> Self {
+        Self {
+            receiver,
+            started: false,
+            done: false,
+        }
+    }
+
+    /// Gets the current file size.
+    pub fn current(&self) -> FileSize {
+        *self.receiver.borrow()
+    }
+
+    /// Waits for the next update of the file size.
+    ///
+    /// The first call returns the current file size immediately. Returns [`None`] after
+    /// the final size ([`FileSize::Exactly`] or [`FileSize::Error`]) was returned, or if
+    /// the file was dropped.
+    pub async fn next(&mut self) -> Option<FileSize> {
+        if self.done {
+            return None;
+        }
+
+        if self.started {
+            self.receiver.changed().await.ok()?;
+        }
+
+        self.started = true;
+        let size = *self.receiver.borrow_and_update();
+        self.done = matches!(size, FileSize::Exactly(_) | FileSize::Error);
+        Some(size)
+    }
+}
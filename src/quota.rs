@@ -0,0 +1,285 @@
+//! Limiting the disk space used by a set of files, notably the [`DiskQuota`] type.
+
+use crate::errors::QuotaError;
+use crate::{SharedFile, SharedFileType, SharedFileWriter, WriteState};
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard};
+use uuid::Uuid;
+
+/// What a [`DiskQuota`] does when a new writer would exceed its budget.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaPolicy {
+    /// Rejects the writer.
+    #[default]
+    Reject,
+    /// Evicts the least recently used files that are no longer written until the writer
+    /// fits into the budget, and rejects it if it still does not fit.
+    EvictLeastRecentlyUsed,
+}
+
+/// A byte budget shared by a set of shared files, e.g. the files of a cache on a disk of
+/// limited size.
+///
+/// Writers are created through the quota using [`writer`](Self::writer), reserving the space
+/// they are expected to need. The quota tracks the size of every file written this way,
+/// counting at least the reserved bytes while a file is still written, and keeps the file
+/// alive until it is evicted or [removed](Self::remove). Once no longer tracked, a file is
+/// deleted according to its [retention policy](SharedFile::set_retention_policy) when the
+/// last handle or reader is dropped.
+///
+/// The budget is enforced when writers are created; a writer exceeding its reservation is
+/// not interrupted, but its excess counts against the budget of later writers. Cloning the
+/// quota creates another handle to the same budget.
+#[derive(Clone)]
+pub struct DiskQuota {
+    /// The state shared by all handles.
+    inner: Arc<Inner>,
+}
+
+/// The state shared by all handles of a [`DiskQuota`].
+struct Inner {
+    /// The number of bytes the files may use in total.
+    budget: u64,
+    /// What happens when a writer would exceed the budget.
+    policy: QuotaPolicy,
+    /// The tracked files and their usage.
+    state: Mutex<State>,
+}
+
+/// The tracked files of a [`DiskQuota`].
+#[derive(Default)]
+struct State {
+    /// The tracked files by their ID.
+    files: HashMap<Uuid, TrackedFile>,
+    /// The value of the usage clock at the most recent use of any file.
+    clock: u64,
+}
+
+/// A file tracked by a [`DiskQuota`].
+struct TrackedFile {
+    /// A handle to the file, keeping it alive.
+    file: Box<dyn QuotaFile>,
+    /// The number of bytes reserved for writing the file.
+    reserved: u64,
+    /// The value of the usage clock when the file was last used.
+    last_used: u64,
+}
+
+/// A file whose size is tracked by a [`DiskQuota`], regardless of its type.
+trait QuotaFile: Send + Sync {
+    /// Gets the number of bytes written to the file.
+    fn size(&self) -> u64;
+
+    /// Determines whether the file is still being written.
+    fn is_pending(&self) -> bool;
+}
+
+impl<T> QuotaFile for SharedFile<T>
+where
+    T: Send + Sync,
+{
+    fn size(&self) -> u64 {
+        match self.sentinel.state.load() {
+            WriteState::Pending(_committed, written) => written,
+            WriteState::Completed(len) => len,
+            WriteState::Failed => self.resume_offset(),
+        }
+    }
+
+    fn is_pending(&self) -> bool {
+        matches!(self.sentinel.state.load(), WriteState::Pending(_, _))
+    }
+}
+
+impl TrackedFile {
+    /// Gets the number of bytes the file uses, or is expected to use while it is written.
+    fn usage(&self) -> u64 {
+        let size = self.file.size();
+        if self.file.is_pending() {
+            size.max(self.reserved)
+        } else {
+            size
+        }
+    }
+}
+
+impl State {
+    /// Gets the number of bytes used by all tracked files.
+    fn used(&self) -> u64 {
+        self.files.values().map(TrackedFile::usage).sum()
+    }
+
+    /// Advances the usage clock, returning its new value.
+    fn tick(&mut self) -> u64 {
+        self.clock += 1;
+        self.clock
+    }
+}
+
+impl DiskQuota {
+    /// Creates a quota limiting the tracked files to `budget` bytes in total.
+    pub fn new(budget: u64, policy: QuotaPolicy) -> Self {
+        Self {
+            inner: Arc::new(Inner {
+                budget,
+                policy,
+                state: Mutex::new(State::default()),
+            }),
+        }
+    }
+
+    /// Gets the number of bytes the tracked files may use in total.
+    pub fn budget(&self) -> u64 {
+        self.inner.budget
+    }
+
+    /// Gets what happens when a writer would exceed the budget.
+    pub fn policy(&self) -> QuotaPolicy {
+        self.inner.policy
+    }
+
+    /// Gets the number of bytes used by the tracked files, counting at least the reserved
+    /// bytes of files still being written.
+    pub fn used(&self) -> u64 {
+        self.lock().used()
+    }
+
+    /// Gets the number of tracked files.
+    pub fn len(&self) -> usize {
+        self.lock().files.len()
+    }
+
+    /// Determines whether no files are tracked.
+    pub fn is_empty(&self) -> bool {
+        self.lock().files.is_empty()
+    }
+
+    /// Creates a writer for the file, reserving `reserve` bytes for it, and tracks the file.
+    ///
+    /// If the reservation would exceed the budget, least recently used files that are no
+    /// longer written are evicted first if the policy allows it. Fails with
+    /// [`QuotaError::Exceeded`] if the reservation still does not fit. Writing a file that
+    /// is already tracked replaces its reservation.
+    pub async fn writer<T>(
+        &self,
+        file: &SharedFile<T>,
+        reserve: u64,
+    ) -> Result<SharedFileWriter<T, T::WriteHandle>, QuotaError<T::OpenError>>
+    where
+        T: SharedFileType + Send + Sync + 'static,
+    {
+        let id = file.id();
+        let evicted = self.reserve(file, id, reserve)?;
+        // Evicted files may be deleted when dropped, which must not happen while locked.
+        drop(evicted);
+
+        match file.writer().await {
+            Ok(writer) => Ok(writer),
+            Err(e) => {
+                let removed = self.lock().files.remove(&id);
+                drop(removed);
+                Err(QuotaError::Open(e))
+            }
+        }
+    }
+
+    /// Marks the file as used, e.g. when it is read, such that least recently used files
+    /// are evicted first. Returns whether the file is tracked.
+    pub fn touch<T>(&self, file: &SharedFile<T>) -> bool {
+        let mut state = self.lock();
+        let clock = state.tick();
+        match state.files.get_mut(&file.id()) {
+            Some(tracked) => {
+                tracked.last_used = clock;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Stops tracking the file, releasing the space it uses from the budget once it is no
+    /// longer used. Returns whether the file was tracked.
+    pub fn remove<T>(&self, file: &SharedFile<T>) -> bool {
+        let removed = self.lock().files.remove(&file.id());
+        removed.is_some()
+    }
+
+    /// Reserves space for writing the file, returning the evicted files.
+    fn reserve<T>(
+        &self,
+        file: &SharedFile<T>,
+        id: Uuid,
+        reserve: u64,
+    ) -> Result<Vec<(Uuid, TrackedFile)>, QuotaError<T::OpenError>>
+    where
+        T: SharedFileType + Send + Sync + 'static,
+    {
+        let mut state = self.lock();
+        let previous = state.files.remove(&id);
+        let mut used = state.used();
+        let mut evicted = Vec::new();
+
+        if used.saturating_add(reserve) > self.inner.budget
+            && self.inner.policy == QuotaPolicy::EvictLeastRecentlyUsed
+        {
+            let mut candidates: Vec<_> = state
+                .files
+                .iter()
+                .filter(|(_id, tracked)| !tracked.file.is_pending())
+                .map(|(id, tracked)| (tracked.last_used, *id))
+                .collect();
+            candidates.sort_unstable();
+            for (_last_used, candidate) in candidates {
+                if used.saturating_add(reserve) <= self.inner.budget {
+                    break;
+                }
+                let tracked = state
+                    .files
+                    .remove(&candidate)
+                    .expect("the candidate is tracked");
+                used -= tracked.usage();
+                evicted.push((candidate, tracked));
+            }
+        }
+
+        if used.saturating_add(reserve) > self.inner.budget {
+            let available = self.inner.budget.saturating_sub(used);
+            if let Some(previous) = previous {
+                state.files.insert(id, previous);
+            }
+            // Eviction only happens if the reservation fits afterwards.
+            state.files.extend(evicted);
+            return Err(QuotaError::Exceeded {
+                requested: reserve,
+                available,
+            });
+        }
+
+        let last_used = state.tick();
+        state.files.insert(
+            id,
+            TrackedFile {
+                file: Box::new(file.clone()),
+                reserved: reserve,
+                last_used,
+            },
+        );
+        Ok(evicted)
+    }
+
+    /// Locks the tracked files.
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.inner.state.lock().expect("failed to lock disk quota")
+    }
+}
+
+impl Debug for DiskQuota {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DiskQuota")
+            .field("budget", &self.inner.budget)
+            .field("policy", &self.inner.policy)
+            .field("used", &self.used())
+            .finish()
+    }
+}
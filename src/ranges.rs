@@ -0,0 +1,121 @@
+//! Tracking of written byte ranges for out-of-order writes.
+
+use std::collections::BTreeMap;
+
+/// The byte ranges written to a file, used to determine the contiguously written prefix.
+#[derive(Debug, Default)]
+pub(crate) struct WrittenRanges {
+    /// The end of the contiguously written prefix of the file.
+    contiguous: u64,
+    /// The written ranges after the contiguous prefix, mapping their start to their end.
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl WrittenRanges {
+    /// Creates the tracking for a file whose first `contiguous` bytes are written.
+    pub(crate) fn new(contiguous: u64) -> Self {
+        Self {
+            contiguous,
+            ranges: BTreeMap::new(),
+        }
+    }
+
+    /// Gets the end of the contiguously written prefix of the file.
+    pub(crate) fn contiguous(&self) -> u64 {
+        self.contiguous
+    }
+
+    /// Records that the bytes from `start` (inclusive) to `end` (exclusive) were written.
+    pub(crate) fn insert(&mut self, start: u64, end: u64) {
+        if end <= self.contiguous {
+            return;
+        }
+
+        if start > self.contiguous {
+            self.ranges
+                .entry(start)
+                .and_modify(|e| *e = (*e).max(end))
+                .or_insert(end);
+            return;
+        }
+
+        self.contiguous = end;
+        while let Some((&start, &end)) = self.ranges.first_key_value() {
+            if start > self.contiguous {
+                break;
+            }
+            self.ranges.pop_first();
+            self.contiguous = self.contiguous.max(end);
+        }
+    }
+
+    /// Determines whether all bytes from `start` (inclusive) to `end` (exclusive) were written.
+    pub(crate) fn contains(&self, start: u64, end: u64) -> bool {
+        if end <= self.contiguous {
+            return true;
+        }
+
+        let mut covered = if start <= self.contiguous {
+            self.contiguous
+        } else {
+            start
+        };
+        for (&range_start, &range_end) in &self.ranges {
+            if range_start > covered {
+                break;
+            }
+            covered = covered.max(range_end);
+        }
+        covered >= end
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sequential() {
+        let mut ranges = WrittenRanges::default();
+        ranges.insert(0, 10);
+        ranges.insert(10, 20);
+        assert_eq!(ranges.contiguous(), 20);
+    }
+
+    #[test]
+    fn test_out_of_order() {
+        let mut ranges = WrittenRanges::default();
+        ranges.insert(20, 30);
+        ranges.insert(10, 15);
+        assert_eq!(ranges.contiguous(), 0);
+
+        ranges.insert(0, 10);
+        assert_eq!(ranges.contiguous(), 15);
+
+        ranges.insert(12, 25);
+        assert_eq!(ranges.contiguous(), 30);
+    }
+
+    #[test]
+    fn test_overwrite() {
+        let mut ranges = WrittenRanges::default();
+        ranges.insert(0, 100);
+        ranges.insert(0, 4);
+        assert_eq!(ranges.contiguous(), 100);
+    }
+
+    #[test]
+    fn test_contains() {
+        let mut ranges = WrittenRanges::default();
+        ranges.insert(0, 10);
+        ranges.insert(20, 30);
+        ranges.insert(25, 40);
+        assert!(ranges.contains(0, 10));
+        assert!(ranges.contains(5, 5));
+        assert!(!ranges.contains(5, 15));
+        assert!(ranges.contains(20, 40));
+        assert!(ranges.contains(22, 35));
+        assert!(!ranges.contains(15, 25));
+        assert!(!ranges.contains(35, 45));
+    }
+}
@@ -0,0 +1,117 @@
+//! Rate limiting of written data, notably the [`RateLimit`] type.
+
+use std::time::Duration;
+use tokio::time::Instant;
+
+/// A token bucket rate limit for writing data, see
+/// [`SharedFileWriter::with_rate_limit`](crate::SharedFileWriter::with_rate_limit).
+///
+/// Up to [`burst`](Self::burst) bytes can be written at once; the budget then refills at
+/// [`bytes_per_second`](Self::bytes_per_second). Writes exceeding the available budget are
+/// shortened, and writes are delayed while no budget is available.
+///
+/// Rate limiting uses [`tokio::time::sleep`] and therefore requires the time driver
+/// of the Tokio runtime to be enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimit {
+    /// The sustained number of bytes that may be written per second.
+    pub bytes_per_second: u64,
+    /// The maximum number of bytes that may be written at once.
+    pub burst: usize,
+}
+
+impl RateLimit {
+    /// Creates a new rate limit.
+    ///
+    /// ## Arguments
+    ///
+    /// * `bytes_per_second` - The sustained number of bytes that may be written per second.
+    /// * `burst` - The maximum number of bytes that may be written at once.
+    pub const fn new(bytes_per_second: u64, burst: usize) -> Self {
+        Self {
+            bytes_per_second,
+            burst,
+        }
+    }
+}
+
+/// The state of a [`RateLimit`].
+#[derive(Debug)]
+pub(crate) struct TokenBucket {
+    /// The rate limit.
+    limit: RateLimit,
+    /// The number of bytes that may currently be written.
+    tokens: f64,
+    /// The time the tokens were last refilled.
+    refilled: Instant,
+}
+
+impl TokenBucket {
+    /// Creates a full token bucket.
+    pub(crate) fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst as f64,
+            refilled: now,
+        }
+    }
+
+    /// Refills the tokens for the time elapsed since the last refill.
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.limit.bytes_per_second as f64)
+            .min(self.limit.burst as f64);
+        self.refilled = now;
+    }
+
+    /// Determines how many of `len` bytes may be written now, or how long to wait
+    /// until a write of `len` bytes (at most the burst size) is possible.
+    pub(crate) fn poll_acquire(&mut self, len: usize, now: Instant) -> Result<usize, Duration> {
+        self.refill(now);
+        let available = self.tokens.floor() as usize;
+        if available > 0 || len == 0 {
+            return Ok(available.min(len));
+        }
+
+        let needed = len.min(self.limit.burst).max(1) as f64 - self.tokens;
+        let rate = self.limit.bytes_per_second.max(1) as f64;
+        Err(Duration::from_secs_f64(needed / rate))
+    }
+
+    /// Consumes the tokens for `written` bytes.
+    pub(crate) fn consume(&mut self, written: usize) {
+        self.tokens = (self.tokens - written as f64).max(0.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_burst() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(100, 50), now);
+        assert_eq!(bucket.poll_acquire(80, now), Ok(50));
+        bucket.consume(50);
+        assert_eq!(
+            bucket.poll_acquire(80, now),
+            Err(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn test_refill() {
+        let now = Instant::now();
+        let mut bucket = TokenBucket::new(RateLimit::new(100, 50), now);
+        bucket.consume(50);
+        assert_eq!(
+            bucket.poll_acquire(10, now + Duration::from_millis(100)),
+            Ok(10)
+        );
+        assert_eq!(
+            bucket.poll_acquire(80, now + Duration::from_secs(10)),
+            Ok(50)
+        );
+    }
+}
@@ -1,94 +1,336 @@
 //! File reading functionality, notably the [`SharedFileReader`] type.
 
+use crate::digest::DigestCheck;
 use crate::errors::ReadError;
-use crate::{Sentinel, SharedFileType, WriteState};
+#[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+use crate::fadvise::DropBehind;
+use crate::tail_cache::{poll_seek, SeekFn};
+use crate::trailer::{decode_trailer, TrailerCheck, TRAILER_LEN};
+use crate::{ReaderPriority, RetryPolicy, Sentinel, SharedFileType, WriteState};
+use bytes::{BufMut, Bytes, BytesMut};
 use pin_project::{pin_project, pinned_drop};
+use std::future::{poll_fn, Future};
 use std::io::{ErrorKind, SeekFrom};
 use std::pin::Pin;
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::task::{ready, Context, Poll};
 use tokio::io;
-use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, ReadBuf};
+use tokio::time::{sleep, Sleep};
 use uuid::Uuid;
 
 /// A reader for the shared temporary file.
+///
+/// The reader reads from a handle of type `R`, as opened by
+/// [`SharedFileType::open_ro`] from the file of type `T`.
 #[pin_project(PinnedDrop)]
-pub struct SharedFileReader<T> {
+pub struct SharedFileReader<T, R = T> {
     /// The ID of the reader.
     id: Uuid,
     /// The file to read from.
     #[pin]
-    file: T,
+    file: R,
     /// The sentinel value to keep the file alive.
     sentinel: Arc<Sentinel<T>>,
-    /// The number of bytes read. Used to keep track
+    /// The number of bytes read, shared with the reader registry. Used to keep track
     /// of how many bytes need to be read from the underlying buffer.
-    read: AtomicUsize,
+    read: Arc<AtomicU64>,
+    /// The priority of the reader when it is woken up.
+    priority: ReaderPriority,
+    /// The policy for retrying transient read errors, if any.
+    retry: Option<RetryPolicy>,
+    /// The number of consecutive retries performed so far.
+    retry_attempt: u32,
+    /// The backoff to wait for before retrying a failed read.
+    backoff: Option<Pin<Box<Sleep>>>,
+    /// The state of the checksum trailer verification, if enabled.
+    trailer: Option<TrailerCheck>,
+    /// The state of the digest verification, if enabled.
+    digest: Option<DigestCheck>,
+    /// The number of truncations of the file already accounted for.
+    truncations: usize,
+    /// Whether the file was truncated below the read position.
+    truncated: bool,
+    /// Seeks the file after data was served from the tail cache, if reading from it is enabled.
+    tail_seek: Option<SeekFn<R>>,
+    /// Whether the file needs to be seeked to the read position before reading from it.
+    seek_pending: bool,
+    /// Whether a seek operation was started but not yet completed.
+    seek_started: bool,
+    /// The length of the frozen snapshot the reader is limited to, if reading one.
+    frozen: Option<u64>,
+    /// The state of dropping read data from the page cache, if enabled.
+    #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+    drop_behind: Option<DropBehind>,
+    /// The span the reader's work is recorded in.
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
 }
 
 /// These IDs never leave the current system, so the node ID is arbitrary.
-static NODE_ID: &[u8; 6] = &[2, 3, 0, 6, 1, 2];
+pub(crate) static NODE_ID: &[u8; 6] = &[2, 3, 0, 6, 1, 2];
 
-impl<T> SharedFileReader<T>
+impl<T> SharedFileReader<T, T::ReadHandle>
 where
-    T: SharedFileType<Type = T>,
+    T: SharedFileType,
 {
-    pub(crate) fn new(file: T, sentinel: Arc<Sentinel<T>>) -> Self {
+    pub(crate) fn new(file: T::ReadHandle, sentinel: Arc<Sentinel<T>>) -> Self {
+        let id = Uuid::now_v1(NODE_ID);
+        let read = Arc::new(AtomicU64::new(0));
+        sentinel.register_reader(id, Arc::clone(&read));
+        let truncations = sentinel.truncations.load(Ordering::Acquire);
         Self {
-            id: Uuid::now_v1(NODE_ID),
+            id,
+            #[cfg(feature = "tracing")]
+            span: sentinel.reader_span(&id),
             file,
             sentinel,
-            read: AtomicUsize::new(0),
+            read,
+            priority: ReaderPriority::default(),
+            retry: None,
+            retry_attempt: 0,
+            backoff: None,
+            trailer: None,
+            digest: None,
+            truncations,
+            truncated: false,
+            tail_seek: None,
+            seek_pending: false,
+            seek_started: false,
+            frozen: None,
+            #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+            drop_behind: None,
         }
     }
 
     /// Creates a new, independent reader.
+    ///
+    /// The new reader uses the same [`ReaderPriority`], [`RetryPolicy`], tail cache setting
+    /// and trailer verification as this one.
     pub async fn fork(&self) -> Result<Self, T::OpenError> {
-        Ok(Self {
-            id: Uuid::now_v1(NODE_ID),
-            file: self.sentinel.original.open_ro().await?,
-            sentinel: self.sentinel.clone(),
-            read: AtomicUsize::new(0),
-        })
+        let file = self.sentinel.original.open_ro().await?;
+        let mut reader = Self::new(file, self.sentinel.clone());
+        reader.priority = self.priority;
+        reader.retry = self.retry;
+        reader.tail_seek = self.tail_seek;
+        reader.trailer = self.trailer.as_ref().map(|_| TrailerCheck::default());
+        reader.frozen = self.frozen;
+        Ok(reader)
     }
 }
 
-impl<T> SharedFileReader<T> {
+impl<T, R> SharedFileReader<T, R> {
+    /// Sets the policy for retrying transient I/O errors of the backing file.
+    ///
+    /// By default, errors are returned to the caller immediately.
+    pub fn with_retry_policy(mut self, policy: RetryPolicy) -> Self {
+        self.retry = Some(policy);
+        self
+    }
+
+    /// Sets the priority of the reader when it is woken up after the writer committed data.
+    ///
+    /// By default, readers have [`ReaderPriority::Normal`].
+    pub fn with_priority(mut self, priority: ReaderPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Gets the priority of the reader.
+    pub fn priority(&self) -> ReaderPriority {
+        self.priority
+    }
+
+    /// Enables serving reads from the tail cache of the writer, see
+    /// [`SharedFileWriter::with_tail_cache`](crate::SharedFileWriter::with_tail_cache).
+    ///
+    /// Readers closely following the writer then read recently committed data from memory.
+    /// Once the reader falls behind the cached data, it seeks its file and continues reading from disk.
+    pub fn with_tail_cache(mut self) -> Self
+    where
+        R: AsyncSeek,
+    {
+        self.tail_seek = Some(poll_seek::<R>);
+        self
+    }
+
+    /// Gets the span the reader's work is recorded in, e.g. for instrumenting
+    /// the downstream processing of the read data.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Enables verification of the checksum trailer written by
+    /// [`SharedFileWriter::complete_with_trailer`](crate::SharedFileWriter::complete_with_trailer).
+    ///
+    /// When the reader reaches the end of the file, it reads the trailer and compares it
+    /// with the length and checksum of the data read. A mismatch is reported as an
+    /// [`InvalidData`](ErrorKind::InvalidData) error instead of the end of the file.
+    ///
+    /// This must be called before any data is read.
+    pub fn verify_trailer(mut self) -> Self {
+        debug_assert_eq!(
+            self.position(),
+            0,
+            "The trailer must be verified from the start"
+        );
+        self.trailer = Some(TrailerCheck::default());
+        self
+    }
+
+    /// Gets the ID of the reader, as reported by [`SharedFile::readers`](crate::SharedFile::readers).
+    pub fn id(&self) -> Uuid {
+        self.id
+    }
+
+    /// Gets the number of bytes read so far.
+    pub(crate) fn position(&self) -> u64 {
+        self.read.load(Ordering::Acquire)
+    }
+
+    /// Gets the number of committed bytes this reader has not yet read, i.e.
+    /// how far the reader is behind the writer.
+    ///
+    /// Returns [`None`] if writing the file failed.
+    pub fn lag(&self) -> Option<u64> {
+        let read = self.position();
+        match self.state() {
+            WriteState::Pending(committed, _written) => Some(committed.saturating_sub(read)),
+            WriteState::Completed(size) => Some(size.saturating_sub(read)),
+            WriteState::Failed => None,
+        }
+    }
+
+    /// Reports the reader as lagging once its [`lag`](Self::lag) exceeds `limit` bytes when
+    /// data is committed, e.g. to detect consumers that cannot keep up before data they did
+    /// not read yet is evicted from a [`RingFile`](crate::RingFile).
+    ///
+    /// Lagging readers are reported via [`EventHandler::on_reader_lagging`](crate::EventHandler::on_reader_lagging),
+    /// [`FileEvent::ReaderLagging`](crate::FileEvent::ReaderLagging) and, with the `tracing`
+    /// feature, a warning. A reader is reported once each time its lag crosses the limit.
+    pub fn with_lag_warning(self, limit: u64) -> Self {
+        self.sentinel.set_reader_lag_limit(&self.id, limit);
+        self
+    }
+
     /// Gets the (expected) size of the file to read.
     pub fn file_size(&self) -> FileSize {
-        match self.sentinel.state.load() {
-            WriteState::Pending(commited, _written) => FileSize::AtLeast(commited),
-            WriteState::Completed(size) => FileSize::Exactly(size),
-            WriteState::Failed => FileSize::Error,
+        match self.frozen {
+            Some(len) => FileSize::Exactly(len),
+            None => self.sentinel.file_size(),
         }
     }
+
+    /// Gets the state of the write operation as observed by the reader.
+    ///
+    /// Readers of a frozen snapshot observe a completed file of the snapshot's length.
+    pub(crate) fn state(&self) -> WriteState {
+        observed_state(&self.sentinel, self.frozen)
+    }
+
+    /// Gets the length of the frozen snapshot the reader is limited to, if reading one.
+    pub(crate) fn frozen_len(&self) -> Option<u64> {
+        self.frozen
+    }
+
+    /// Limits the reader to the first `len` bytes of the file, reporting them as a completed file.
+    pub(crate) fn set_frozen(&mut self, len: u64) {
+        self.frozen = Some(len);
+    }
+
+    /// Gets the file to read from.
+    #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+    pub(crate) fn file(&self) -> &R {
+        &self.file
+    }
+
+    /// Gets the file to read from, bypassing the committed watermark.
+    pub(crate) fn file_mut(&mut self) -> &mut R {
+        &mut self.file
+    }
+
+    /// Sets the state of dropping read data from the page cache.
+    #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+    pub(crate) fn set_drop_behind(&mut self, drop_behind: DropBehind) {
+        self.drop_behind = Some(drop_behind);
+    }
+
+    /// Sets the state of the digest verification.
+    pub(crate) fn set_digest_check(&mut self, check: DigestCheck) {
+        self.digest = Some(check);
+    }
+
+    /// Gets the sentinel of the file.
+    pub(crate) fn sentinel(&self) -> &Sentinel<T> {
+        &self.sentinel
+    }
+}
+
+/// Gets the state of the write operation of the file, or a completed state of
+/// `frozen` bytes if reading a frozen snapshot.
+fn observed_state<T>(sentinel: &Sentinel<T>, frozen: Option<u64>) -> WriteState {
+    match frozen {
+        Some(len) => WriteState::Completed(len),
+        None => sentinel.state.load(),
+    }
 }
 
 /// The file size of the file to read.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum FileSize {
     /// The file is not entirely written yet. The specified amount is the minimum
     /// number known to exist.
-    AtLeast(usize),
+    AtLeast(u64),
+    /// The file is not entirely written yet, but its total size is known in advance,
+    /// see [`SharedFileWriter::set_expected_len`](crate::SharedFileWriter::set_expected_len).
+    /// Contains the minimum number known to exist and the expected total.
+    AtLeastWithTotal(u64, u64),
     /// The file is completely written and has exactly the specified amount of bytes.
-    Exactly(usize),
+    Exactly(u64),
     /// An error occurred while writing the file; reading may not complete.
     Error,
 }
 
 impl FileSize {
+    /// Determines the file size from the state of the write operation.
+    ///
+    /// If the total size of the file is known in advance, it is reported while the file is pending.
+    pub(crate) fn from_state(state: WriteState, expected_len: Option<u64>) -> Self {
+        match state {
+            WriteState::Pending(commited, _written) => match expected_len {
+                Some(total) => FileSize::AtLeastWithTotal(commited, total),
+                None => FileSize::AtLeast(commited),
+            },
+            WriteState::Completed(size) => FileSize::Exactly(size),
+            WriteState::Failed => FileSize::Error,
+        }
+    }
+
     /// Returns the minimum or exact file size if it is known, or [`None`] otherwise.
-    pub fn minimum_size(&self) -> Option<usize> {
-        if let Self::AtLeast(len) = self {
-            Some(*len)
+    pub fn minimum_size(&self) -> Option<u64> {
+        match self {
+            Self::AtLeast(len) | Self::AtLeastWithTotal(len, _) => Some(*len),
+            _ => self.exact_size(),
+        }
+    }
+
+    /// Returns the expected total file size if it is known, or [`None`] otherwise.
+    ///
+    /// This is the exact file size of a completed file, or the expected total
+    /// of a file that is still being written.
+    pub fn expected_total(&self) -> Option<u64> {
+        if let Self::AtLeastWithTotal(_, total) = self {
+            Some(*total)
         } else {
             self.exact_size()
         }
     }
 
     /// Returns the exact file size if it is known, or [`None`] otherwise.
-    pub fn exact_size(&self) -> Option<usize> {
+    pub fn exact_size(&self) -> Option<u64> {
         if let Self::Exactly(len) = self {
             Some(*len)
         } else {
@@ -98,29 +340,62 @@ impl FileSize {
 }
 
 #[pinned_drop]
-impl<T> PinnedDrop for SharedFileReader<T> {
+impl<T, R> PinnedDrop for SharedFileReader<T, R> {
     fn drop(mut self: Pin<&mut Self>) {
-        self.sentinel.remove_reader_waker(&self.id)
+        self.sentinel.remove_reader_waker(&self.id);
+        self.sentinel.remove_reader(&self.id);
+        self.sentinel.apply_reader_retention();
+
+        #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+        {
+            let position = self.position();
+            if let Some(drop_behind) = self.as_mut().project().drop_behind {
+                drop_behind.advance(position, true);
+            }
+        }
     }
 }
 
-impl<T> AsyncRead for SharedFileReader<T>
+impl<T, R> AsyncRead for SharedFileReader<T, R>
 where
-    T: AsyncRead,
+    R: AsyncRead,
 {
     fn poll_read(
-        self: Pin<&mut Self>,
+        mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &mut ReadBuf<'_>,
     ) -> Poll<io::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
+
+        // Wait for the backoff of a previously failed read to elapse.
+        if let Some(backoff) = self.as_mut().project().backoff {
+            ready!(backoff.as_mut().poll(cx));
+            *self.as_mut().project().backoff = None;
+        }
+
+        if self.as_mut().check_truncated() {
+            return Poll::Ready(Err(io::Error::new(ErrorKind::Other, ReadError::Truncated)));
+        }
+
         let read_so_far = self.read.load(Ordering::Acquire);
 
-        let current_total = match self.sentinel.state.load() {
+        let state = self.state();
+        let was_pending = matches!(state, WriteState::Pending(_, _));
+        let current_total = match state {
             WriteState::Pending(committed, _written) => {
                 // If the number of committed bytes is the same as the number
                 // of bytes we have already read, try again later.
                 if read_so_far == committed {
-                    self.sentinel.register_reader_waker(self.id, cx.waker());
+                    #[cfg(feature = "tracing")]
+                    tracing::trace!(position = read_so_far, "waiting for data");
+                    self.sentinel
+                        .register_reader_waker(self.id, self.priority, cx.waker());
+
+                    // The writer may have committed data before the waker was registered.
+                    if !self.sentinel.state.load().is_pending_with(committed) {
+                        cx.waker().wake_by_ref();
+                    }
                     return Poll::Pending;
                 }
                 committed
@@ -128,57 +403,72 @@ where
             WriteState::Completed(count) => {
                 // If we have read all there is, we're done.
                 if read_so_far == count {
-                    return Poll::Ready(Ok(()));
+                    return self.poll_end_of_file(cx, count);
                 }
                 count
             }
-            WriteState::Failed => {
-                return Poll::Ready(Err(io::Error::new(
-                    ErrorKind::BrokenPipe,
-                    ReadError::FileClosed,
-                )))
-            }
+            WriteState::Failed => return Poll::Ready(Err(self.sentinel.read_error())),
         };
 
+        // Serve recently written data from the tail cache, if possible.
+        if let Some(data) = self.as_mut().take_cached(buf.remaining()) {
+            buf.put_slice(&data);
+            return Poll::Ready(Ok(()));
+        }
+        ready!(self.as_mut().poll_pending_seek(cx))?;
+
         // Ensure to not read more bytes than were actually written
         // by constraining the actual buffer to a smaller one if needed.
-        let read_at_most = (current_total - read_so_far).min(buf.remaining());
+        let read_at_most = (current_total - read_so_far).min(buf.remaining() as u64) as usize;
         let mut smaller_buf = buf.take(read_at_most);
         let read_offset = smaller_buf.filled().len();
 
-        let this = self.project();
+        let this = self.as_mut().project();
 
         if let Poll::Ready(result) = this.file.poll_read(cx, &mut smaller_buf) {
             this.sentinel.remove_reader_waker(this.id);
             if let Err(e) = result {
-                return Poll::Ready(Err(e));
+                let delay = this
+                    .retry
+                    .and_then(|p| p.retry_delay(&e, *this.retry_attempt));
+                let Some(delay) = delay else {
+                    return Poll::Ready(Err(e));
+                };
+
+                // Retry the read once the backoff has elapsed.
+                *this.retry_attempt += 1;
+                let mut backoff = Box::pin(sleep(delay));
+                if backoff.as_mut().poll(cx).is_ready() {
+                    cx.waker().wake_by_ref();
+                } else {
+                    *this.backoff = Some(backoff);
+                }
+                return Poll::Pending;
             }
 
+            *this.retry_attempt = 0;
+
             // If the buffer was advanced, return the result.
             let read_now = smaller_buf.filled().len();
             if read_now != read_offset {
+                let read_bytes = read_now - read_offset;
+                self.as_mut()
+                    .record_read(&smaller_buf.filled()[read_offset..], read_so_far);
+
                 // Advance the parent buffer.
                 unsafe {
-                    buf.assume_init(read_now);
+                    buf.assume_init(read_bytes);
                 }
-                buf.set_filled(read_now);
-
-                let read = read_so_far + (read_now - read_offset);
-                this.read.store(read, Ordering::Release);
+                buf.advance(read_bytes);
                 return Poll::Ready(result);
             }
 
             // If the buffer was not advanced and source file is completed (or in fail state),
             // return as-is. Otherwise, reset and wait.
-            match this.sentinel.state.load() {
+            match observed_state(this.sentinel, *this.frozen) {
                 WriteState::Pending(_, _) => {}
                 WriteState::Completed(_) => return Poll::Ready(Ok(())),
-                WriteState::Failed => {
-                    return Poll::Ready(Err(io::Error::new(
-                        ErrorKind::BrokenPipe,
-                        ReadError::FileClosed,
-                    )))
-                }
+                WriteState::Failed => return Poll::Ready(Err(this.sentinel.read_error())),
             }
         }
 
@@ -186,17 +476,239 @@ where
         buf.advance(0);
 
         // Re-register waker and try again.
-        this.sentinel.register_reader_waker(*this.id, cx.waker());
+        this.sentinel
+            .register_reader_waker(*this.id, *this.priority, cx.waker());
+
+        // The writer may have committed data before the waker was registered.
+        if was_pending && !this.sentinel.state.load().is_pending_with(current_total) {
+            cx.waker().wake_by_ref();
+        }
         Poll::Pending
     }
 }
 
-impl<T> AsyncSeek for SharedFileReader<T>
+impl<T, R> SharedFileReader<T, R>
 where
-    T: AsyncSeek,
+    R: AsyncRead,
+{
+    /// Determines whether the file was truncated below the read position.
+    fn check_truncated(self: Pin<&mut Self>) -> bool {
+        let this = self.project();
+        let truncations = this.sentinel.truncations.load(Ordering::Acquire);
+        if *this.truncations != truncations {
+            *this.truncations = truncations;
+            *this.truncated |= this.sentinel.is_reader_truncated(this.id);
+        }
+        *this.truncated
+    }
+
+    /// Records that `data` was read, starting at position `read_so_far`.
+    pub(crate) fn record_read(mut self: Pin<&mut Self>, data: &[u8], read_so_far: u64) {
+        self.as_mut().verify(data);
+        self.advance(read_so_far + data.len() as u64);
+    }
+
+    /// Records that `len` bytes of zeros were skipped, starting at position `read_so_far`.
+    ///
+    /// The zeros are only processed if the checksum or digest is verified.
+    pub(crate) fn record_skipped(mut self: Pin<&mut Self>, len: u64, read_so_far: u64) {
+        if self.trailer.is_some() || self.digest.is_some() {
+            let zeros = [0; 4096];
+            let mut remaining = len;
+            while remaining > 0 {
+                let data = &zeros[..remaining.min(zeros.len() as u64) as usize];
+                self.as_mut().verify(data);
+                remaining -= data.len() as u64;
+            }
+        }
+        self.advance(read_so_far + len);
+    }
+
+    /// Updates the checksum and digest verification, if enabled, with the read data.
+    fn verify(self: Pin<&mut Self>, data: &[u8]) {
+        let this = self.project();
+        if let Some(check) = this.trailer.as_mut() {
+            check.crc.update(data);
+        }
+        if let Some(check) = this.digest.as_mut() {
+            check.update(data);
+        }
+    }
+
+    /// Moves the read position to `read`, reporting the progress to the writer.
+    fn advance(self: Pin<&mut Self>, read: u64) {
+        let this = self.project();
+        // Pairs with the check for progress watchers, see `Sentinel::update_reader_position`.
+        let previous = this.read.swap(read, Ordering::SeqCst);
+        this.sentinel
+            .update_reader_position(this.id, previous, read);
+
+        #[cfg(all(feature = "fadvise", any(target_os = "linux", target_os = "android")))]
+        if let Some(drop_behind) = this.drop_behind.as_mut() {
+            drop_behind.advance(read, false);
+        }
+    }
+
+    /// Takes up to `max` committed bytes from the tail cache, if enabled and cached.
+    fn take_cached(mut self: Pin<&mut Self>, max: usize) -> Option<Bytes> {
+        self.tail_seek?;
+
+        let read_so_far = self.read.load(Ordering::Acquire);
+        let available = match self.state() {
+            WriteState::Pending(committed, _written) => committed,
+            WriteState::Completed(count) => count,
+            WriteState::Failed => return None,
+        }
+        .saturating_sub(read_so_far);
+
+        let data = self
+            .sentinel
+            .tail_cache
+            .lock()
+            .expect("failed to lock tail cache for reading")
+            .get(read_so_far, available.min(max as u64) as usize)?;
+
+        self.as_mut().record_read(&data, read_so_far);
+        *self.project().seek_pending = true;
+        Some(data)
+    }
+
+    /// Seeks the file to the read position after data was served from the tail cache.
+    fn poll_pending_seek(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = self.project();
+        let (Some(seek), true) = (*this.tail_seek, *this.seek_pending) else {
+            return Poll::Ready(Ok(()));
+        };
+
+        let position = this.read.load(Ordering::Acquire);
+        ready!(seek(this.file, cx, position, this.seek_started))?;
+        *this.seek_pending = false;
+        Poll::Ready(Ok(()))
+    }
+
+    /// Signals the end of the file once all `count` bytes of the file were read,
+    /// performing the enabled verifications first.
+    fn poll_end_of_file(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        count: u64,
+    ) -> Poll<io::Result<()>> {
+        if self.trailer.is_some() {
+            ready!(self.as_mut().poll_pending_seek(cx))?;
+        }
+        ready!(self.as_mut().poll_verify_trailer(cx, count))?;
+
+        if let Some(check) = self.project().digest.as_mut() {
+            if !check.verify() {
+                return Poll::Ready(Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    ReadError::DigestMismatch,
+                )));
+            }
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Reads and verifies the checksum trailer, if enabled.
+    fn poll_verify_trailer(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        count: u64,
+    ) -> Poll<io::Result<()>> {
+        let mut this = self.project();
+        let Some(check) = this.trailer.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+
+        while !check.failed && check.filled < TRAILER_LEN {
+            let mut buf = ReadBuf::new(&mut check.buffer[check.filled..]);
+            ready!(this.file.as_mut().poll_read(cx, &mut buf))?;
+            if buf.filled().is_empty() {
+                check.failed = true;
+                return Poll::Ready(Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    ReadError::InvalidTrailer,
+                )));
+            }
+            check.filled += buf.filled().len();
+        }
+
+        let error = match decode_trailer(&check.buffer) {
+            _ if check.failed => ReadError::InvalidTrailer,
+            None => ReadError::InvalidTrailer,
+            Some((len, crc)) if len == count && crc == check.crc.value() => {
+                // Successfully verified; subsequent reads report the end of the file.
+                *this.trailer = None;
+                return Poll::Ready(Ok(()));
+            }
+            Some(_) => ReadError::ChecksumMismatch,
+        };
+
+        check.failed = true;
+        Poll::Ready(Err(io::Error::new(ErrorKind::InvalidData, error)))
+    }
+}
+
+impl<T, R> SharedFileReader<T, R>
+where
+    R: AsyncRead + Unpin,
+{
+    /// Reads all remaining data into the buffer, resolving only after the writer completed
+    /// and all data was read. Waiting for pending data is handled internally.
+    ///
+    /// Returns the number of bytes read by this call. If writing the file failed,
+    /// the error is returned; data read so far remains in the buffer.
+    ///
+    /// ## Arguments
+    ///
+    /// * `buf` - The buffer to append to, e.g. a `Vec<u8>` or a [`BytesMut`](bytes::BytesMut).
+    pub async fn read_to_end_when_complete<B>(&mut self, buf: &mut B) -> io::Result<usize>
+    where
+        B: BufMut,
+    {
+        let mut total = 0;
+        loop {
+            match self.read_buf(buf).await? {
+                0 => return Ok(total),
+                read => total += read,
+            }
+        }
+    }
+
+    /// Reads up to `max` bytes, waiting for the writer to commit data.
+    ///
+    /// Data served from the tail cache (see [`with_tail_cache`](Self::with_tail_cache))
+    /// is returned without copying. Returns an empty buffer at the end of the file.
+    pub async fn read_bytes(&mut self, max: usize) -> io::Result<Bytes> {
+        let mut buffer = BytesMut::with_capacity(max);
+        poll_fn(|cx| {
+            let mut this = Pin::new(&mut *self);
+            if let Some(data) = this.as_mut().take_cached(max) {
+                return Poll::Ready(Ok(data));
+            }
+
+            let mut buf = ReadBuf::uninit(&mut buffer.spare_capacity_mut()[..max]);
+            ready!(this.poll_read(cx, &mut buf))?;
+            let read = buf.filled().len();
+
+            // SAFETY: The read bytes were initialized by the read operation.
+            unsafe {
+                buffer.set_len(read);
+            }
+            Poll::Ready(Ok(buffer.split().freeze()))
+        })
+        .await
+    }
+}
+
+impl<T, R> AsyncSeek for SharedFileReader<T, R>
+where
+    R: AsyncSeek,
 {
     fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
         let this = self.project();
+        *this.seek_pending = false;
         this.file.start_seek(position)
     }
 
@@ -221,6 +733,18 @@ mod tests {
     fn test_minimum_size() {
         assert_eq!(FileSize::Exactly(42).minimum_size(), Some(42));
         assert_eq!(FileSize::AtLeast(41).minimum_size(), Some(41));
+        assert_eq!(FileSize::AtLeastWithTotal(41, 42).minimum_size(), Some(41));
         assert_eq!(FileSize::Error.minimum_size(), None);
     }
+
+    #[test]
+    fn test_expected_total() {
+        assert_eq!(FileSize::Exactly(42).expected_total(), Some(42));
+        assert_eq!(FileSize::AtLeast(41).expected_total(), None);
+        assert_eq!(
+            FileSize::AtLeastWithTotal(41, 42).expected_total(),
+            Some(42)
+        );
+        assert_eq!(FileSize::Error.expected_total(), None);
+    }
 }
@@ -0,0 +1,299 @@
+//! Active reader introspection, notably the [`ReaderInfo`] type.
+
+use crate::{FileEvent, Sentinel, SharedFile};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::SystemTime;
+use uuid::Uuid;
+
+/// Information about an active reader, see [`SharedFile::readers`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReaderInfo {
+    /// The ID of the reader.
+    pub id: Uuid,
+    /// The number of bytes read so far.
+    pub position: u64,
+    /// The time the reader was created.
+    pub created: SystemTime,
+}
+
+/// An entry of the reader registry.
+#[derive(Debug)]
+pub(crate) struct RegisteredReader {
+    /// The registration order of the reader, used to order readers created at the same time.
+    sequence: u64,
+    /// The ID of the reader.
+    id: Uuid,
+    /// The number of bytes read so far, shared with the reader.
+    position: Arc<AtomicU64>,
+    /// The time the reader was created.
+    created: SystemTime,
+    /// Whether the file was truncated below the position of the reader.
+    truncated: bool,
+    /// The lag above which the reader is reported as lagging, if any.
+    lag_limit: Option<u64>,
+    /// Whether the reader was reported as lagging since its lag last was within the limit.
+    lagging: bool,
+}
+
+impl RegisteredReader {
+    /// Gets the number of bytes read so far.
+    fn position(&self) -> u64 {
+        // Pairs with the check for progress watchers, see `Sentinel::update_reader_position`.
+        self.position.load(Ordering::SeqCst)
+    }
+
+    /// Gets information about the reader.
+    fn info(&self) -> ReaderInfo {
+        ReaderInfo {
+            id: self.id,
+            position: self.position(),
+            created: self.created,
+        }
+    }
+}
+
+/// Keeps the progress of readers reported while alive, see [`Sentinel::watch_progress`].
+pub(crate) struct ProgressWatch<'a, T> {
+    /// The sentinel of the watched file.
+    sentinel: &'a Sentinel<T>,
+}
+
+impl<T> Drop for ProgressWatch<'_, T> {
+    fn drop(&mut self) {
+        self.sentinel.remove_progress_watcher();
+    }
+}
+
+/// The registration sequence number of the next reader.
+static NEXT_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+impl<T> SharedFile<T> {
+    /// Gets information about all active readers of the file, ordered by their creation time.
+    ///
+    /// The information is a snapshot; readers may progress or be dropped at any time.
+    pub fn readers(&self) -> Vec<ReaderInfo> {
+        let mut readers: Vec<_> = {
+            let lock = self
+                .sentinel
+                .readers
+                .lock()
+                .expect("failed to lock reader registry for reading");
+            lock.values()
+                .map(|entry| (entry.sequence, entry.info()))
+                .collect()
+        };
+        readers.sort_by_key(|(sequence, _)| *sequence);
+        readers.into_iter().map(|(_, info)| info).collect()
+    }
+
+    /// Gets the number of active readers of the file, e.g. to decide whether a cached
+    /// file can be evicted.
+    pub fn reader_count(&self) -> usize {
+        self.sentinel
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for reading")
+            .len()
+    }
+
+    /// Waits until all readers of the file were dropped, e.g. to safely delete or recycle it.
+    ///
+    /// Resolves immediately if there are no readers. Readers created afterwards are not awaited.
+    pub async fn all_readers_done(&self) {
+        let _watch = self.sentinel.watch_progress();
+        loop {
+            // Register for notifications before checking to avoid missing an update.
+            let notified = self.sentinel.readers_progressed.notified();
+            if self.reader_count() == 0 {
+                return;
+            }
+            notified.await;
+        }
+    }
+
+    /// Determines whether a writer of the file is currently active.
+    pub fn has_active_writer(&self) -> bool {
+        self.sentinel.active_writers.load(Ordering::Acquire) > 0
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Registers a reader whose read position is shared with the registry.
+    pub(crate) fn register_reader(&self, id: Uuid, position: Arc<AtomicU64>) {
+        let mut lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for writing");
+        lock.insert(
+            id,
+            RegisteredReader {
+                sequence: NEXT_SEQUENCE.fetch_add(1, Ordering::Relaxed),
+                id,
+                position,
+                created: SystemTime::now(),
+                truncated: false,
+                lag_limit: None,
+                lagging: false,
+            },
+        );
+        drop(lock);
+        self.emit_event(FileEvent::ReaderAttached(id));
+    }
+
+    /// Reports that the reader moved from `previous` to the position already stored in its
+    /// shared position, waking up the writer and waiters if the slowest reader progressed.
+    ///
+    /// This is called for every read; unless a writer bounds its lag or a task waits for
+    /// readers to progress, it does nothing.
+    pub(crate) fn update_reader_position(&self, id: &Uuid, previous: u64, position: u64) {
+        // Pairs with the registration of watchers, such that either the watcher observes the
+        // new position or the reader observes the watcher.
+        if self.progress_watchers.load(Ordering::SeqCst) == 0 {
+            return;
+        }
+
+        let slowest_progressed = {
+            let lock = self
+                .readers
+                .lock()
+                .expect("failed to lock reader registry for reading");
+            match min_position_except(&lock, id) {
+                Some(others) => previous.min(others) != position.min(others),
+                None => previous != position,
+            }
+        };
+        if slowest_progressed {
+            self.readers_progressed.notify_waiters();
+            self.wake_writer();
+        }
+    }
+
+    /// Registers interest in the progress of readers until the returned guard is dropped.
+    ///
+    /// Readers only report their progress while someone is interested in it.
+    pub(crate) fn watch_progress(&self) -> ProgressWatch<'_, T> {
+        self.add_progress_watcher();
+        ProgressWatch { sentinel: self }
+    }
+
+    /// Registers interest in the progress of readers, e.g. by a writer bounding its lag.
+    pub(crate) fn add_progress_watcher(&self) {
+        self.progress_watchers.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Removes interest in the progress of readers registered by
+    /// [`add_progress_watcher`](Self::add_progress_watcher).
+    pub(crate) fn remove_progress_watcher(&self) {
+        self.progress_watchers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Removes the reader, waking up the writer and waiters if it was the slowest reader.
+    pub(crate) fn remove_reader(&self, id: &Uuid) {
+        let slowest_removed = {
+            let mut lock = self
+                .readers
+                .lock()
+                .expect("failed to lock reader registry for writing");
+            let removed = lock.remove(id).map(|entry| {
+                if entry.lag_limit.is_some() {
+                    self.lag_limits.fetch_sub(1, Ordering::AcqRel);
+                }
+                entry.position()
+            });
+            match (removed, min_position_except(&lock, id)) {
+                (Some(removed), Some(others)) => removed < others,
+                (Some(_removed), None) => true,
+                (None, _) => false,
+            }
+        };
+        self.emit_event(FileEvent::ReaderDetached(*id));
+
+        if slowest_removed && self.progress_watchers.load(Ordering::SeqCst) > 0 {
+            self.readers_progressed.notify_waiters();
+            self.wake_writer();
+        }
+    }
+
+    /// Sets the lag above which the reader is reported as lagging, see
+    /// [`SharedFileReader::with_lag_warning`](crate::SharedFileReader::with_lag_warning).
+    pub(crate) fn set_reader_lag_limit(&self, id: &Uuid, limit: u64) {
+        let mut lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for writing");
+        if let Some(entry) = lock.get_mut(id) {
+            if entry.lag_limit.replace(limit).is_none() {
+                self.lag_limits.fetch_add(1, Ordering::AcqRel);
+            }
+            entry.lagging = false;
+        }
+    }
+
+    /// Gets the IDs and lags of the readers whose lag crossed their limit now that
+    /// `committed` bytes are committed.
+    ///
+    /// Each reader is reported once until its lag is within its limit again. Unless a
+    /// reader has a lag limit, this does nothing.
+    pub(crate) fn lagging_readers(&self, committed: u64) -> Vec<(Uuid, u64)> {
+        if self.lag_limits.load(Ordering::Acquire) == 0 {
+            return Vec::new();
+        }
+
+        let mut lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for writing");
+        let mut lagging = Vec::new();
+        for entry in lock.values_mut() {
+            let Some(limit) = entry.lag_limit else {
+                continue;
+            };
+            let lag = committed.saturating_sub(entry.position());
+            if lag <= limit {
+                entry.lagging = false;
+            } else if !entry.lagging {
+                entry.lagging = true;
+                lagging.push((entry.id, lag));
+            }
+        }
+        lagging
+    }
+
+    /// Marks all readers positioned after `len` as truncated.
+    pub(crate) fn truncate_readers(&self, len: u64) {
+        let mut lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for writing");
+        for entry in lock.values_mut() {
+            entry.truncated |= entry.position() > len;
+        }
+    }
+
+    pub(crate) fn is_reader_truncated(&self, id: &Uuid) -> bool {
+        let lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for reading");
+        lock.get(id).map_or(false, |entry| entry.truncated)
+    }
+
+    pub(crate) fn slowest_reader_position(&self) -> Option<u64> {
+        let lock = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for reading");
+        lock.values().map(RegisteredReader::position).min()
+    }
+}
+
+/// Gets the position of the slowest reader other than the one with the specified ID.
+fn min_position_except(readers: &HashMap<Uuid, RegisteredReader>, id: &Uuid) -> Option<u64> {
+    readers
+        .iter()
+        .filter(|(other, _entry)| *other != id)
+        .map(|(_other, entry)| entry.position())
+        .min()
+}
@@ -0,0 +1,49 @@
+//! Resuming failed writes, notably [`SharedFile::resume_writer`].
+
+use crate::errors::OpenWriterError;
+use crate::{SetLen, SharedFile, SharedFileType, SharedFileWriter, WriteState};
+use std::io::{Error, ErrorKind};
+use std::sync::atomic::Ordering;
+use tokio::io::AsyncSeek;
+
+impl<T> SharedFile<T>
+where
+    T: SharedFileType + SetLen,
+    T::SetLenError: std::error::Error + Send + Sync + 'static,
+    T::WriteHandle: AsyncSeek + Unpin,
+{
+    /// Creates a writer resuming a failed file at the last committed offset, e.g. after
+    /// a transient disk error, so that a large transfer does not have to start over.
+    ///
+    /// Uncommitted data is discarded by [truncating](Self::truncate) the file to the
+    /// [`resume_offset`](Self::resume_offset), and the file is reset to a pending state;
+    /// readers then wait for the resumed data instead of failing. If writing the file
+    /// did not fail, this is equivalent to [`append_writer`](Self::append_writer).
+    ///
+    /// Fails with [`OpenWriterError::WriterExists`] if the failed writer was not dropped yet.
+    pub async fn resume_writer(
+        &self,
+    ) -> Result<SharedFileWriter<T, T::WriteHandle>, OpenWriterError<T::OpenError>> {
+        if self.sentinel.active_writers.load(Ordering::Acquire) > 0 {
+            return Err(OpenWriterError::WriterExists);
+        }
+
+        if let WriteState::Failed = self.sentinel.state.load() {
+            self.truncate(self.resume_offset())
+                .await
+                .map_err(|e| OpenWriterError::Io(Error::new(ErrorKind::Other, e)))?;
+        }
+
+        self.append_writer().await
+    }
+}
+
+impl<T> SharedFile<T> {
+    /// Gets the number of bytes committed before writing the file failed, or the number
+    /// of bytes committed so far if it did not fail.
+    ///
+    /// See [`resume_writer`](Self::resume_writer).
+    pub fn resume_offset(&self) -> u64 {
+        self.sentinel.resume_offset.load(Ordering::Acquire)
+    }
+}
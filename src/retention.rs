@@ -0,0 +1,160 @@
+//! Retention of files once they are no longer used, notably the [`RetentionPolicy`] type.
+
+use crate::{FilePath, Sentinel, SharedFile, WriteState};
+use std::ffi::OsString;
+use std::mem::ManuallyDrop;
+use std::path::{Path, PathBuf};
+use std::sync::PoisonError;
+
+/// Determines what happens to the file of a [`SharedFile`] once it is no longer used,
+/// see [`SharedFile::set_retention_policy`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum RetentionPolicy {
+    /// Leaves the file to the backing type, e.g. a `TempFile` deletes it when dropped.
+    #[default]
+    Inherit,
+    /// Deletes the file when all handles to it were dropped.
+    DeleteOnDrop,
+    /// Deletes the file when the last reader of the completed file was dropped, or when all
+    /// handles to it were dropped. Readers created afterwards fail to open the file.
+    DeleteAfterLastReader,
+    /// Keeps the file when all handles to it were dropped.
+    Keep,
+    /// Keeps the file for debugging if writing it failed, and deletes it otherwise.
+    KeepOnFailure,
+}
+
+/// The retention policy of a file, along with the path it applies to.
+#[derive(Debug)]
+pub(crate) struct Retention {
+    /// The configured policy.
+    pub(crate) policy: RetentionPolicy,
+    /// The path of the original file.
+    pub(crate) path: PathBuf,
+}
+
+impl<T> SharedFile<T> {
+    /// Sets what happens to the file once it is no longer used, instead of leaving it
+    /// to the backing type.
+    ///
+    /// The policy applies to the path of the original file; a file moved elsewhere using
+    /// [`persist`](Self::persist) is always kept.
+    pub fn set_retention_policy(&self, policy: RetentionPolicy)
+    where
+        T: FilePath,
+    {
+        let path = self.sentinel.original.file_path().clone();
+        *self
+            .sentinel
+            .retention
+            .lock()
+            .expect("failed to lock retention policy for writing") =
+            Some(Retention { policy, path });
+    }
+
+    /// Gets the retention policy of the file.
+    pub fn retention_policy(&self) -> RetentionPolicy {
+        self.sentinel
+            .retention
+            .lock()
+            .expect("failed to lock retention policy for reading")
+            .as_ref()
+            .map_or(RetentionPolicy::Inherit, |retention| retention.policy)
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Deletes the file if the last reader of the completed file was dropped and the
+    /// retention policy asks for it.
+    pub(crate) fn apply_reader_retention(&self) {
+        let retention = self
+            .retention
+            .lock()
+            .expect("failed to lock retention policy for reading");
+        let Some(Retention {
+            policy: RetentionPolicy::DeleteAfterLastReader,
+            path,
+        }) = retention.as_ref()
+        else {
+            return;
+        };
+
+        if !matches!(self.state.load(), WriteState::Completed(_)) {
+            return;
+        }
+
+        let no_readers = self
+            .readers
+            .lock()
+            .expect("failed to lock reader registry for reading")
+            .is_empty();
+        if no_readers {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Takes the original file, skipping the retention policy.
+    pub(crate) fn into_original(mut self) -> T {
+        self.original_taken = true;
+        // SAFETY: The original file is taken exactly once and not dropped afterwards.
+        unsafe { ManuallyDrop::take(&mut self.original) }
+    }
+}
+
+impl<T> Drop for Sentinel<T> {
+    fn drop(&mut self) {
+        if self.original_taken {
+            return;
+        }
+
+        let retention = self
+            .retention
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+        let failed = matches!(self.state.load(), WriteState::Failed);
+        let (delete, keep) = match retention.as_ref().map(|retention| retention.policy) {
+            None | Some(RetentionPolicy::Inherit) => (false, false),
+            Some(RetentionPolicy::DeleteOnDrop | RetentionPolicy::DeleteAfterLastReader) => {
+                (true, false)
+            }
+            Some(RetentionPolicy::Keep) => (false, true),
+            Some(RetentionPolicy::KeepOnFailure) => (!failed, failed),
+        };
+
+        // Linking the file keeps it alive even if the backing type deletes it when dropped.
+        let staged = match &retention {
+            Some(retention) if keep => stage_link(&retention.path),
+            _ => None,
+        };
+
+        // SAFETY: The original file is dropped exactly once and not accessed afterwards.
+        unsafe { ManuallyDrop::drop(&mut self.original) };
+
+        let Some(retention) = retention else {
+            return;
+        };
+        if delete {
+            let _ = std::fs::remove_file(&retention.path);
+        }
+        if let Some(staged) = staged {
+            if retention.path.exists() {
+                let _ = std::fs::remove_file(&staged);
+            } else {
+                let _ = std::fs::rename(&staged, &retention.path);
+            }
+        }
+    }
+}
+
+/// Creates a hard link to the file next to it, returning its path.
+fn stage_link(path: &Path) -> Option<PathBuf> {
+    let mut name = OsString::from(".");
+    name.push(path.file_name()?);
+    name.push(".keep");
+    let staged = path.with_file_name(name);
+    std::fs::hard_link(path, &staged).ok()?;
+    Some(staged)
+}
@@ -0,0 +1,108 @@
+//! Retry functionality for transient read errors, notably the [`RetryPolicy`] type.
+
+use std::time::Duration;
+use tokio::io;
+use tokio::io::ErrorKind;
+
+/// A policy for retrying transient I/O errors when reading from the backing file.
+///
+/// An error is considered transient if its [`ErrorKind`] is one of
+/// [`Interrupted`](ErrorKind::Interrupted), [`WouldBlock`](ErrorKind::WouldBlock) or
+/// [`TimedOut`](ErrorKind::TimedOut). The delay between attempts starts at
+/// [`initial_backoff`](Self::initial_backoff) and doubles with every failed attempt
+/// up to [`max_backoff`](Self::max_backoff).
+///
+/// Retries use [`tokio::time::sleep`] and therefore require the time driver
+/// of the Tokio runtime to be enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// The maximum number of consecutive retries before the error is returned.
+    pub max_retries: u32,
+    /// The delay before the first retry.
+    pub initial_backoff: Duration,
+    /// The maximum delay between two retries.
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    /// Creates a new retry policy.
+    ///
+    /// ## Arguments
+    ///
+    /// * `max_retries` - The maximum number of consecutive retries.
+    /// * `initial_backoff` - The delay before the first retry.
+    /// * `max_backoff` - The maximum delay between two retries.
+    pub const fn new(max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    /// Determines whether the error is considered transient.
+    pub fn is_transient(error: &io::Error) -> bool {
+        matches!(
+            error.kind(),
+            ErrorKind::Interrupted | ErrorKind::WouldBlock | ErrorKind::TimedOut
+        )
+    }
+
+    /// Determines the delay before retrying after the specified error, or [`None`]
+    /// if the error should not be retried.
+    ///
+    /// ## Arguments
+    ///
+    /// * `error` - The error that occurred.
+    /// * `attempt` - The number of retries already performed for this error.
+    pub fn retry_delay(&self, error: &io::Error, attempt: u32) -> Option<Duration> {
+        if attempt >= self.max_retries || !Self::is_transient(error) {
+            return None;
+        }
+
+        let factor = 1_u32.checked_shl(attempt).unwrap_or(u32::MAX);
+        Some(
+            self.initial_backoff
+                .saturating_mul(factor)
+                .min(self.max_backoff),
+        )
+    }
+}
+
+impl Default for RetryPolicy {
+    /// Retries up to five times, starting at 10 ms and backing off up to one second.
+    fn default() -> Self {
+        Self::new(5, Duration::from_millis(10), Duration::from_secs(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_delay() {
+        let policy = RetryPolicy::new(3, Duration::from_millis(10), Duration::from_millis(25));
+        let error = io::Error::from(ErrorKind::Interrupted);
+        assert_eq!(
+            policy.retry_delay(&error, 0),
+            Some(Duration::from_millis(10))
+        );
+        assert_eq!(
+            policy.retry_delay(&error, 1),
+            Some(Duration::from_millis(20))
+        );
+        assert_eq!(
+            policy.retry_delay(&error, 2),
+            Some(Duration::from_millis(25))
+        );
+        assert_eq!(policy.retry_delay(&error, 3), None);
+    }
+
+    #[test]
+    fn test_permanent_errors_are_not_retried() {
+        let policy = RetryPolicy::default();
+        let error = io::Error::from(ErrorKind::PermissionDenied);
+        assert_eq!(policy.retry_delay(&error, 0), None);
+    }
+}
@@ -0,0 +1,319 @@
+//! A bounded backend recycling disk space, notably the [`RingFile`] type.
+
+use crate::errors::ReadError;
+use crate::{FilePath, SharedFile, SharedFileReader, SharedFileType};
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::fs::{File, OpenOptions};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncSeek, AsyncSeekExt, AsyncWrite, ReadBuf};
+
+/// A type alias for a [`SharedFile`] wrapping a [`RingFile`].
+pub type SharedRingFile = SharedFile<RingFile>;
+
+/// A file of a fixed capacity used as a ring buffer, usable as a backend of a [`SharedFile`],
+/// e.g. for continuously capturing a stream with capped disk usage.
+///
+/// Once the writer exceeds the capacity, it wraps around and overwrites the oldest data, so
+/// that only the most recent `capacity` bytes are retained. Readers falling behind this
+/// retention window, including readers created after the writer wrapped around, fail with
+/// [`ReadError::Evicted`] instead of reading overwritten data.
+///
+/// Use [`SharedFileReader::eviction_headroom`] or
+/// [`SharedFileReader::with_lag_warning`] to detect readers at risk of falling behind. An
+/// evicted reader can skip the lost data and resume at [`oldest_position`](Self::oldest_position)
+/// using [`SharedFileReader::seek_to_oldest`]. Handles seek by logical position.
+#[derive(Debug)]
+pub struct RingFile {
+    /// The state shared by all handles.
+    inner: Arc<Inner>,
+    /// The open file.
+    file: File,
+    /// The logical position of this handle.
+    position: u64,
+    /// The position in the file, if known.
+    file_position: Option<u64>,
+    /// Whether the file is being seeked to the position matching the logical position.
+    seeking: bool,
+}
+
+/// The state shared by all handles of a [`RingFile`].
+#[derive(Debug)]
+struct Inner {
+    /// The path of the file.
+    path: PathBuf,
+    /// The number of bytes retained.
+    capacity: u64,
+    /// The logical end of the data written or being written.
+    head: AtomicU64,
+}
+
+impl RingFile {
+    /// Creates the file at the specified path, truncating it if it already exists, retaining
+    /// the most recent `capacity` bytes written to it.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `capacity` is zero.
+    pub async fn create<P: AsRef<Path>>(path: P, capacity: u64) -> io::Result<Self> {
+        assert_ne!(capacity, 0, "The capacity must not be zero");
+        let path = path.as_ref().to_path_buf();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await?;
+        let inner = Arc::new(Inner {
+            path,
+            capacity,
+            head: AtomicU64::new(0),
+        });
+        Ok(Self::handle(inner, file))
+    }
+
+    /// Gets the path of the file.
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Gets the number of bytes retained.
+    pub fn capacity(&self) -> u64 {
+        self.inner.capacity
+    }
+
+    /// Gets the logical position of the oldest byte that was not evicted yet.
+    pub fn oldest_position(&self) -> u64 {
+        self.inner
+            .head
+            .load(Ordering::Acquire)
+            .saturating_sub(self.inner.capacity)
+    }
+
+    /// Creates a handle to the file, positioned at its logical start.
+    fn handle(inner: Arc<Inner>, file: File) -> Self {
+        Self {
+            inner,
+            file,
+            position: 0,
+            file_position: None,
+            seeking: false,
+        }
+    }
+
+    /// Opens another handle to the file.
+    async fn reopen(&self, write: bool) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(write)
+            .open(&self.inner.path)
+            .await?;
+        Ok(Self::handle(Arc::clone(&self.inner), file))
+    }
+
+    /// Seeks the file to the position matching the logical position, returning the number
+    /// of bytes until the end of the file's capacity.
+    fn poll_position(&mut self, cx: &mut Context<'_>) -> Poll<io::Result<usize>> {
+        let physical = self.position % self.inner.capacity;
+        if self.file_position != Some(physical) {
+            if !self.seeking {
+                // Pending operations must complete before seeking.
+                ready!(Pin::new(&mut self.file).poll_complete(cx))?;
+                Pin::new(&mut self.file).start_seek(SeekFrom::Start(physical))?;
+                self.seeking = true;
+            }
+            let result = ready!(Pin::new(&mut self.file).poll_complete(cx));
+            self.seeking = false;
+            self.file_position = Some(result?);
+        }
+
+        let remaining = self.inner.capacity - physical;
+        Poll::Ready(Ok(usize::try_from(remaining).unwrap_or(usize::MAX)))
+    }
+
+    /// Fails if the data at the position was overwritten.
+    fn check_evicted(&self, position: u64) -> io::Result<()> {
+        let oldest = self.oldest_position();
+        if oldest > position {
+            let lagged = oldest - position;
+            return Err(Error::new(
+                ErrorKind::Other,
+                ReadError::Evicted { position, lagged },
+            ));
+        }
+        Ok(())
+    }
+
+    /// Advances the logical position and the position in the file by `count` bytes.
+    fn advance(&mut self, count: usize) {
+        self.position += count as u64;
+        self.file_position = self.file_position.map(|p| p + count as u64);
+    }
+}
+
+impl<R> SharedFileReader<RingFile, R> {
+    /// Gets the number of bytes the writer can still write before data this reader has not
+    /// read yet is evicted, or `0` if it already was.
+    ///
+    /// Unlike [`lag`](Self::lag), this accounts for written but uncommitted data, which
+    /// already overwrites the oldest data. A headroom approaching zero is a warning that the
+    /// reader is about to fail with [`ReadError::Evicted`].
+    pub fn eviction_headroom(&self) -> u64 {
+        let ring = &self.sentinel().original;
+        let head = ring.inner.head.load(Ordering::Acquire);
+        (self.position() + ring.inner.capacity).saturating_sub(head)
+    }
+
+    /// Determines whether data this reader has not read yet was evicted, such that
+    /// the next read fails with [`ReadError::Evicted`].
+    pub fn is_evicted(&self) -> bool {
+        self.position() < self.sentinel().original.oldest_position()
+    }
+}
+
+impl<R> SharedFileReader<RingFile, R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Skips data evicted before this reader read it, resuming at the
+    /// [oldest retained position](RingFile::oldest_position), e.g. after a read failed with
+    /// [`ReadError::Evicted`].
+    ///
+    /// The skipped bytes are accounted for as if zeros were read, so checksum or digest
+    /// verification of the file fails. Returns the number of bytes skipped, which is zero
+    /// if no data was evicted.
+    pub async fn seek_to_oldest(&mut self) -> io::Result<u64> {
+        let position = self.position();
+        let oldest = self.sentinel().original.oldest_position();
+        if oldest <= position {
+            return Ok(0);
+        }
+
+        self.file_mut().seek(SeekFrom::Start(oldest)).await?;
+        Pin::new(&mut *self).record_skipped(oldest - position, position);
+        Ok(oldest - position)
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for RingFile {
+    type ReadHandle = RingFile;
+    type WriteHandle = RingFile;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        self.reopen(false).await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.reopen(true).await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(&self.inner.path)
+    }
+}
+
+impl FilePath for RingFile {
+    fn file_path(&self) -> &PathBuf {
+        &self.inner.path
+    }
+}
+
+impl AsyncRead for RingFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let position = this.position;
+        this.check_evicted(position)?;
+        let remaining = ready!(this.poll_position(cx))?;
+
+        let mut smaller_buf = buf.take(remaining);
+        ready!(Pin::new(&mut this.file).poll_read(cx, &mut smaller_buf))?;
+        let read = smaller_buf.filled().len();
+
+        // The writer may have overwritten the data while it was read.
+        this.check_evicted(position)?;
+
+        // SAFETY: The bytes were initialized by reading into the smaller buffer.
+        unsafe { buf.assume_init(read) };
+        buf.advance(read);
+        this.advance(read);
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for RingFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let remaining = ready!(this.poll_position(cx))?;
+        let len = buf.len().min(remaining);
+
+        // Readers must see the data as evicted before it is overwritten.
+        this.inner
+            .head
+            .fetch_max(this.position + len as u64, Ordering::AcqRel);
+        let written = ready!(Pin::new(&mut this.file).poll_write(cx, &buf[..len]))?;
+        this.advance(written);
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().file).poll_shutdown(cx)
+    }
+}
+
+impl AsyncSeek for RingFile {
+    /// Seeks the logical file. Seeking relative to its end seeks relative to the data written.
+    ///
+    /// The underlying file is seeked to the matching position on the next read or write.
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        let this = self.get_mut();
+        let target = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => this.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => this
+                .inner
+                .head
+                .load(Ordering::Acquire)
+                .checked_add_signed(offset),
+        };
+        let Some(target) = target else {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "Invalid seek to a negative or overflowing position",
+            ));
+        };
+        this.position = target;
+        Ok(())
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
@@ -0,0 +1,447 @@
+//! Writing a file in disjoint segments, notably [`SharedFile::segmented_writer`].
+
+use crate::errors::{CompleteWritingError, OpenWriterError, ReadError};
+use crate::ranges::WrittenRanges;
+use crate::{Sentinel, SharedFile, SharedFileReader, SharedFileType, WriteState};
+use std::collections::BTreeMap;
+use std::io::{Error, ErrorKind, SeekFrom};
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite};
+use tokio::time::Instant;
+
+/// The ranges claimed and committed by the segment writers of a file.
+#[derive(Debug)]
+pub(crate) struct Segments {
+    /// The total length of the file.
+    len: u64,
+    /// The ranges claimed by segment writers, mapping their start to their end.
+    claimed: BTreeMap<u64, u64>,
+    /// The committed ranges.
+    available: WrittenRanges,
+}
+
+impl Segments {
+    /// Creates the tracking for a file of `len` bytes.
+    fn new(len: u64) -> Self {
+        Self {
+            len,
+            claimed: BTreeMap::new(),
+            available: WrittenRanges::default(),
+        }
+    }
+
+    /// Claims the range for a segment writer unless it is empty, exceeds the file
+    /// or overlaps a previously claimed range.
+    fn claim(&mut self, range: &Range<u64>) -> bool {
+        if range.start >= range.end || range.end > self.len {
+            return false;
+        }
+
+        // Claimed ranges are disjoint, so only the closest preceding one can overlap.
+        if let Some((_start, &end)) = self.claimed.range(..range.end).next_back() {
+            if end > range.start {
+                return false;
+            }
+        }
+
+        self.claimed.insert(range.start, range.end);
+        true
+    }
+
+    /// Shrinks the claimed range starting at `start` to end at `end`, releasing the remainder.
+    fn release(&mut self, start: u64, end: u64) {
+        if end > start {
+            self.claimed.insert(start, end);
+        } else {
+            self.claimed.remove(&start);
+        }
+    }
+}
+
+/// A coordinator creating writers for disjoint segments of a file, see
+/// [`SharedFile::segmented_writer`].
+///
+/// The file is completed as soon as all of its bytes were committed by segment writers.
+/// Keep the coordinator alive until then; dropping it allows other writers to be created.
+#[derive(Debug)]
+pub struct SegmentedWriter<T> {
+    /// The sentinel value to keep the file alive.
+    sentinel: Arc<Sentinel<T>>,
+}
+
+/// A writer for a segment of a file, see [`SegmentedWriter::segment`].
+///
+/// Writes start at the beginning of the segment and must not exceed its end. Like
+/// [`SharedFileWriter`](crate::SharedFileWriter), written data is committed, waking up
+/// readers, when the writer is flushed.
+///
+/// ## Dropping the writer
+///
+/// When the writer is dropped before its segment was committed completely, the uncommitted
+/// remainder is released so that it can be claimed by another segment writer, e.g. to retry
+/// a failed download. Use [`SharedFile::abort`] to fail all readers instead.
+#[derive(Debug)]
+pub struct SegmentWriter<T, W = T> {
+    /// The file to write to.
+    file: W,
+    /// The sentinel value to keep the file alive.
+    sentinel: Arc<Sentinel<T>>,
+    /// The segment of the file to write.
+    range: Range<u64>,
+    /// The current write position in the file.
+    position: u64,
+    /// The end of the committed data of the segment.
+    committed: u64,
+}
+
+impl<T> SharedFile<T>
+where
+    T: SharedFileType,
+{
+    /// Prepares writing the file of `len` bytes in disjoint segments, e.g. when downloading
+    /// it in parallel, and returns a [`SegmentedWriter`] creating the writers for each segment.
+    ///
+    /// Instead of a single committed watermark, the file then tracks which ranges were
+    /// committed. Readers still read the file sequentially, observing the committed prefix
+    /// of the file; use [`SharedFileReader::read_exact_at`] to read any committed range.
+    ///
+    /// Segment writers created by a previously dropped coordinator keep their segments,
+    /// which the new coordinator does not hand out again.
+    ///
+    /// Fails with [`OpenWriterError::WriterExists`] if another writer is still active or
+    /// segment writers of a previous coordinator for a different length still hold their
+    /// segments, and with [`OpenWriterError::FileWritingFailed`] if the file was already
+    /// written to.
+    pub fn segmented_writer(
+        &self,
+        len: u64,
+    ) -> Result<SegmentedWriter<T>, OpenWriterError<T::OpenError>> {
+        if self
+            .sentinel
+            .active_writers
+            .compare_exchange(0, 1, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return Err(OpenWriterError::WriterExists);
+        }
+
+        if self.sentinel.state.load() != WriteState::Pending(0, 0) {
+            self.sentinel.active_writers.fetch_sub(1, Ordering::AcqRel);
+            return Err(OpenWriterError::FileWritingFailed);
+        }
+
+        {
+            let mut segments = self
+                .sentinel
+                .segments
+                .lock()
+                .expect("failed to lock segments for writing");
+            match segments.as_ref() {
+                // Segment writers of a previous coordinator still hold their claims.
+                Some(existing) if !existing.claimed.is_empty() => {
+                    if existing.len != len {
+                        self.sentinel.active_writers.fetch_sub(1, Ordering::AcqRel);
+                        return Err(OpenWriterError::WriterExists);
+                    }
+                }
+                _ if len == 0 => self.sentinel.state.store(WriteState::Completed(0)),
+                _ => *segments = Some(Segments::new(len)),
+            }
+        }
+
+        self.sentinel.expected_len.store(Some(len));
+        self.sentinel.wake_readers();
+        Ok(SegmentedWriter {
+            sentinel: self.sentinel.clone(),
+        })
+    }
+}
+
+impl<T> SegmentedWriter<T>
+where
+    T: SharedFileType,
+    T::WriteHandle: AsyncSeek + Unpin,
+{
+    /// Creates a writer for the specified segment of the file.
+    ///
+    /// Fails with [`OpenWriterError::InvalidRange`] if the range is empty, exceeds the file
+    /// or overlaps the segment of another writer.
+    pub async fn segment(
+        &self,
+        range: Range<u64>,
+    ) -> Result<SegmentWriter<T, T::WriteHandle>, OpenWriterError<T::OpenError>> {
+        if let WriteState::Failed = self.sentinel.state.load() {
+            return Err(OpenWriterError::FileWritingFailed);
+        }
+
+        let claimed = match self
+            .sentinel
+            .segments
+            .lock()
+            .expect("failed to lock segments for writing")
+            .as_mut()
+        {
+            Some(segments) => segments.claim(&range),
+            None => false,
+        };
+        if !claimed {
+            return Err(OpenWriterError::InvalidRange);
+        }
+
+        let file = match self.sentinel.original.open_rw().await {
+            Ok(file) => file,
+            Err(e) => {
+                self.sentinel.release_segment(range.start, range.start);
+                return Err(OpenWriterError::Open(e));
+            }
+        };
+
+        // From here on, dropping the writer releases the claim.
+        let mut writer = SegmentWriter {
+            file,
+            sentinel: self.sentinel.clone(),
+            position: range.start,
+            committed: range.start,
+            range,
+        };
+        writer
+            .file
+            .seek(SeekFrom::Start(writer.range.start))
+            .await
+            .map_err(OpenWriterError::Io)?;
+        Ok(writer)
+    }
+}
+
+impl<T> Drop for SegmentedWriter<T> {
+    fn drop(&mut self) {
+        self.sentinel.active_writers.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+impl<T, W> SegmentWriter<T, W> {
+    /// Gets the segment of the file this writer writes.
+    pub fn range(&self) -> Range<u64> {
+        self.range.clone()
+    }
+
+    /// Gets the number of bytes of the segment that are yet to be written.
+    pub fn remaining(&self) -> u64 {
+        self.range.end - self.position
+    }
+}
+
+impl<T, W> SegmentWriter<T, W>
+where
+    W: SharedFileType + AsyncWrite + Unpin,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Syncs the file to disk and commits all written data.
+    ///
+    /// Fails with [`CompleteWritingError::Io`] if the segment was not written completely;
+    /// the remainder is then released as if the writer was dropped.
+    pub async fn complete(mut self) -> Result<(), CompleteWritingError> {
+        // Data buffered by the file must be written before it can be synced.
+        std::future::poll_fn(|cx| Pin::new(&mut self.file).poll_flush(cx)).await?;
+        if let Err(e) = self.file.sync_data().await {
+            return Err(CompleteWritingError::SyncError(Box::new(e)));
+        }
+        std::future::poll_fn(|cx| Pin::new(&mut self).poll_flush(cx)).await?;
+
+        if self.position != self.range.end {
+            return Err(CompleteWritingError::Io(Error::new(
+                ErrorKind::UnexpectedEof,
+                "The segment was not written completely",
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl<T, W> AsyncWrite for SegmentWriter<T, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let len = (self.range.end - self.position).min(buf.len() as u64) as usize;
+        if len == 0 && !buf.is_empty() {
+            return Poll::Ready(Err(Error::new(
+                ErrorKind::WriteZero,
+                "The write exceeds the segment",
+            )));
+        }
+
+        let this = &mut *self;
+        match ready!(Pin::new(&mut this.file).poll_write(cx, &buf[..len])) {
+            Ok(written) => {
+                this.position += written as u64;
+                if let WriteState::Pending(committed, total) = this.sentinel.state.load() {
+                    let total = total.max(this.position);
+                    this.sentinel
+                        .state
+                        .store(WriteState::Pending(committed, total));
+                }
+                this.sentinel.last_progress.store(Instant::now());
+                Poll::Ready(Ok(written))
+            }
+            Err(e) => {
+                this.sentinel.fail_with_error(&e);
+                Poll::Ready(Err(e))
+            }
+        }
+    }
+
+    /// Flushes the file and commits all written data, waking up readers.
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let this = &mut *self;
+        if let Err(e) = ready!(Pin::new(&mut this.file).poll_flush(cx)) {
+            this.sentinel.fail_with_error(&e);
+            return Poll::Ready(Err(e));
+        }
+
+        if this.committed < this.position {
+            this.sentinel.commit_segment(this.committed, this.position);
+            this.committed = this.position;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_flush(cx))?;
+        Pin::new(&mut self.file).poll_shutdown(cx)
+    }
+}
+
+impl<T, W> Drop for SegmentWriter<T, W> {
+    fn drop(&mut self) {
+        self.sentinel
+            .release_segment(self.range.start, self.committed);
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Shrinks the claimed segment starting at `start` to end at `end`, releasing the remainder.
+    fn release_segment(&self, start: u64, end: u64) {
+        if let Some(segments) = self
+            .segments
+            .lock()
+            .expect("failed to lock segments for writing")
+            .as_mut()
+        {
+            segments.release(start, end);
+        }
+    }
+
+    /// Commits the bytes from `start` (inclusive) to `end` (exclusive) written by a segment
+    /// writer, completing the file once all bytes were committed.
+    fn commit_segment(&self, start: u64, end: u64) {
+        {
+            let mut lock = self
+                .segments
+                .lock()
+                .expect("failed to lock segments for writing");
+            let Some(segments) = lock.as_mut() else {
+                return;
+            };
+
+            segments.available.insert(start, end);
+            let contiguous = segments.available.contiguous();
+            match self.state.load() {
+                WriteState::Pending(_committed, _written) if contiguous == segments.len => {
+                    self.state.store(WriteState::Completed(contiguous));
+                    *lock = None;
+                }
+                WriteState::Pending(_committed, written) => {
+                    self.state.store(WriteState::Pending(contiguous, written));
+                }
+                WriteState::Completed(_) | WriteState::Failed => {}
+            }
+        }
+
+        self.wake_readers();
+    }
+
+    /// Determines whether the bytes of the range were committed, failing if they never will be.
+    fn is_range_available(&self, range: &Range<u64>) -> io::Result<bool> {
+        match self.state.load() {
+            WriteState::Pending(committed, _written) if range.end <= committed => Ok(true),
+            WriteState::Pending(_committed, _written) => {
+                match self
+                    .segments
+                    .lock()
+                    .expect("failed to lock segments for reading")
+                    .as_ref()
+                {
+                    Some(segments) => Ok(segments.available.contains(range.start, range.end)),
+                    None => Ok(false),
+                }
+            }
+            WriteState::Completed(count) if range.end <= count => Ok(true),
+            WriteState::Completed(_) => {
+                Err(Error::new(ErrorKind::UnexpectedEof, ReadError::FileClosed))
+            }
+            WriteState::Failed => Err(self.read_error()),
+        }
+    }
+}
+
+impl<T, R> SharedFileReader<T, R> {
+    /// Waits until all bytes of the range were committed.
+    ///
+    /// This is particularly useful for files written in segments (see
+    /// [`SharedFile::segmented_writer`]), where ranges may be committed out of order.
+    /// Fails if the file is completed before reaching the end of the range, or if
+    /// writing the file failed.
+    pub async fn wait_for_range(&self, range: Range<u64>) -> io::Result<()> {
+        // Readers of a frozen snapshot never observe data beyond it.
+        if let Some(len) = self.frozen_len() {
+            if range.end > len {
+                return Err(Error::new(ErrorKind::UnexpectedEof, ReadError::FileClosed));
+            }
+            return Ok(());
+        }
+
+        let sentinel = self.sentinel();
+        loop {
+            // Register for notifications before checking to avoid missing an update.
+            let notified = sentinel.committed.notified();
+            if sentinel.is_range_available(&range)? {
+                return Ok(());
+            }
+            notified.await;
+        }
+    }
+}
+
+impl<T, R> SharedFileReader<T, R>
+where
+    R: AsyncRead + AsyncSeek + Unpin,
+{
+    /// Reads the exact number of bytes required to fill `buf` at the specified offset,
+    /// waiting until they were committed. The read position of the reader is left unchanged.
+    ///
+    /// ## Arguments
+    ///
+    /// * `offset` - The offset in the file at which to read.
+    /// * `buf` - The buffer to fill.
+    pub async fn read_exact_at(&mut self, offset: u64, buf: &mut [u8]) -> io::Result<()> {
+        self.wait_for_range(offset..offset + buf.len() as u64)
+            .await?;
+
+        let file = self.file_mut();
+        let position = file.stream_position().await?;
+        file.seek(SeekFrom::Start(offset)).await?;
+        let result = file.read_exact(buf).await;
+        file.seek(SeekFrom::Start(position)).await?;
+        result.map(|_| ())
+    }
+}
@@ -0,0 +1,87 @@
+//! Shutdown behavior of writers, notably the [`ShutdownBehavior`] type.
+
+use crate::{SharedFileType, SharedFileWriter};
+use std::io::{Error, ErrorKind};
+use std::sync::Arc;
+use tokio::io;
+use tokio::task::JoinHandle;
+use tokio::time::Instant;
+
+/// Determines how [`AsyncWrite::poll_shutdown`](tokio::io::AsyncWrite::poll_shutdown)
+/// completes a [`SharedFileWriter`], see [`SharedFileWriter::with_shutdown_behavior`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ShutdownBehavior {
+    /// Completes the file after shutting down the underlying file, without syncing it to disk.
+    #[default]
+    Complete,
+    /// Syncs the file data to disk after shutting down the underlying file, then completes it.
+    SyncThenComplete,
+    /// Fails the file if written data was not committed, e.g. by flushing, before shutting down.
+    FailIfUncommitted,
+}
+
+/// A task syncing the file data to disk.
+pub(crate) type SyncTask = JoinHandle<io::Result<()>>;
+
+/// Spawns a [`SyncTask`] for the file.
+pub(crate) type SyncFn = Arc<dyn Fn() -> SyncTask + Send + Sync>;
+
+/// The state of the shutdown of a writer.
+#[derive(Default)]
+pub(crate) struct Shutdown {
+    /// The configured behavior.
+    pub(crate) behavior: ShutdownBehavior,
+    /// Spawns the task syncing the file, if required by the behavior.
+    pub(crate) sync: Option<SyncFn>,
+    /// The sync in progress, if any.
+    pub(crate) pending: Option<SyncTask>,
+}
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    T: SharedFileType<WriteHandle = W> + Send + Sync + 'static,
+    T::OpenError: std::error::Error + Send + Sync + 'static,
+    W: SharedFileType + Send + 'static,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+{
+    /// Sets how shutting down the writer via
+    /// [`AsyncWriteExt::shutdown`](tokio::io::AsyncWriteExt::shutdown) completes the file.
+    ///
+    /// By default, the file is completed without syncing it to disk ([`ShutdownBehavior::Complete`]).
+    ///
+    /// With [`ShutdownBehavior::SyncThenComplete`], the data is synced through a write handle
+    /// of the file, such that wrapper types sync the same way as the writer's own handle.
+    /// The sync runs on a spawned task, so shutting down requires a Tokio runtime.
+    pub fn with_shutdown_behavior(mut self, behavior: ShutdownBehavior) -> Self {
+        let sync = match behavior {
+            ShutdownBehavior::SyncThenComplete => {
+                let sentinel = Arc::clone(self.sentinel_arc());
+                let sync: SyncFn = Arc::new(move || {
+                    let sentinel = sentinel.clone();
+                    tokio::spawn(async move {
+                        let handle = sentinel
+                            .original
+                            .open_rw()
+                            .await
+                            .map_err(|e| Error::new(ErrorKind::Other, e))?;
+                        let started = Instant::now();
+                        handle
+                            .sync_data()
+                            .await
+                            .map_err(|e| Error::new(ErrorKind::Other, e))?;
+                        sentinel.stats.record_sync(started.elapsed());
+                        Ok(())
+                    })
+                });
+                Some(sync)
+            }
+            ShutdownBehavior::Complete | ShutdownBehavior::FailIfUncommitted => None,
+        };
+        self.set_shutdown(Shutdown {
+            behavior,
+            sync,
+            pending: None,
+        });
+        self
+    }
+}
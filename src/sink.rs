@@ -0,0 +1,65 @@
+//! [`Sink`] support for writers via the futures-sink crate.
+
+use crate::SharedFileWriter;
+use bytes::{Buf, Bytes};
+use futures_sink::Sink;
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::AsyncWrite;
+
+/// Writes buffers into the file.
+///
+/// Flushing the sink commits the written data and wakes up readers; closing it
+/// commits all data and completes the file.
+impl<T, W> Sink<Bytes> for SharedFileWriter<T, W>
+where
+    W: AsyncWrite,
+{
+    type Error = io::Error;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        while let Some(item) = self.as_mut().sink_item().take() {
+            if item.is_empty() {
+                continue;
+            }
+
+            let written = match self.as_mut().poll_write(cx, &item) {
+                Poll::Ready(result) => result?,
+                Poll::Pending => {
+                    *self.as_mut().sink_item() = Some(item);
+                    return Poll::Pending;
+                }
+            };
+            if written == 0 {
+                return Poll::Ready(Err(Error::from(ErrorKind::WriteZero)));
+            }
+
+            let mut item = item;
+            item.advance(written);
+            *self.as_mut().sink_item() = Some(item);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, item: Bytes) -> Result<(), Self::Error> {
+        debug_assert!(
+            self.as_mut().sink_item().is_none(),
+            "poll_ready must be called before start_send"
+        );
+        *self.sink_item() = Some(item);
+        Ok(())
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(Sink::<Bytes>::poll_ready(self.as_mut(), cx))?;
+        AsyncWrite::poll_flush(self, cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        ready!(Sink::<Bytes>::poll_flush(self.as_mut(), cx))?;
+        AsyncWrite::poll_shutdown(self, cx)
+    }
+}
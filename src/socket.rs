@@ -0,0 +1,133 @@
+//! Publishing the state of a file over a Unix domain socket, notably
+//! [`SharedFile::spawn_socket_notifier`] and the [`SocketEvents`] type.
+
+use crate::{FileEvent, SharedFile, WriteStatus};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader, ErrorKind};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+impl<T> SharedFile<T> {
+    /// Spawns a task publishing the state of the file over a Unix domain socket bound to the
+    /// specified path, such that readers in other processes can follow the file without
+    /// polling its size, e.g. using [`SocketEvents`].
+    ///
+    /// Every client receives the current state right after connecting, followed by every
+    /// update, as lines of text: `committed <bytes>` whenever data is committed,
+    /// `completed <bytes>` once the file is completed and `failed` if writing it fails.
+    /// Intermediate updates may be skipped if a client is slower than the writer. The
+    /// connection is closed after the file was completed or failed.
+    ///
+    /// Binding fails if the path already exists. The task ends and removes the socket once the
+    /// file was dropped. This must be called from within a Tokio runtime.
+    pub fn spawn_socket_notifier<P: AsRef<Path>>(&self, path: P) -> io::Result<JoinHandle<()>> {
+        let path = path.as_ref().to_path_buf();
+        let listener = UnixListener::bind(&path)?;
+        let mut status = self.subscribe();
+        Ok(tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    accepted = listener.accept() => {
+                        if let Ok((stream, _address)) = accepted {
+                            tokio::spawn(notify_client(stream, status.clone()));
+                        }
+                    }
+                    changed = status.changed() => {
+                        // The file was dropped.
+                        if changed.is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+            let _ = tokio::fs::remove_file(&path).await;
+        }))
+    }
+}
+
+/// Sends the state of the file to a client until it was completed, failed or dropped.
+async fn notify_client(mut stream: UnixStream, mut status: watch::Receiver<WriteStatus>) {
+    loop {
+        let current = *status.borrow_and_update();
+        let (line, last) = match current {
+            WriteStatus::Pending { committed: 0, .. } => (None, false),
+            WriteStatus::Pending { committed, .. } => {
+                (Some(format!("committed {committed}\n")), false)
+            }
+            WriteStatus::Completed { len } => (Some(format!("completed {len}\n")), true),
+            WriteStatus::Failed => (Some(String::from("failed\n")), true),
+        };
+        if let Some(line) = line {
+            if stream.write_all(line.as_bytes()).await.is_err() {
+                return;
+            }
+        }
+        if last || status.changed().await.is_err() {
+            let _ = stream.shutdown().await;
+            return;
+        }
+    }
+}
+
+/// A stream of the events of a file published over a Unix domain socket, see
+/// [`SharedFile::spawn_socket_notifier`].
+///
+/// The stream yields [`FileEvent::Committed`], [`FileEvent::Completed`] and
+/// [`FileEvent::Failed`] events.
+#[derive(Debug)]
+pub struct SocketEvents {
+    /// The connection to the notifier.
+    stream: BufReader<UnixStream>,
+    /// The line being received.
+    line: String,
+}
+
+impl SocketEvents {
+    /// Connects to the notifier listening on the socket at the specified path.
+    pub async fn connect<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let stream = UnixStream::connect(path).await?;
+        Ok(Self {
+            stream: BufReader::new(stream),
+            line: String::new(),
+        })
+    }
+
+    /// Receives the next event, waiting for it if required.
+    ///
+    /// Returns [`None`] once the connection was closed, e.g. after the file was completed.
+    pub async fn recv(&mut self) -> io::Result<Option<FileEvent>> {
+        self.line.clear();
+        if self.stream.read_line(&mut self.line).await? == 0 {
+            return Ok(None);
+        }
+
+        let line = self.line.trim_end();
+        let (kind, value) = line.split_once(' ').unwrap_or((line, ""));
+        let len = || {
+            value.parse::<u64>().map_err(|_| {
+                io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid notification: {line}"),
+                )
+            })
+        };
+        let event = match kind {
+            "committed" => FileEvent::Committed(len()?),
+            "completed" => FileEvent::Completed(len()?),
+            "failed" => FileEvent::Failed(Arc::new(io::Error::new(
+                ErrorKind::Other,
+                "Writing the file failed in another process",
+            ))),
+            _ => {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Invalid notification: {line}"),
+                ))
+            }
+        };
+        Ok(Some(event))
+    }
+}
@@ -0,0 +1,94 @@
+//! Integration with the [`tracing`] crate, linking reader and writer spans to the file.
+//!
+//! Spans record the path of the file, as reported by [`SharedFileType::diagnostic_path`].
+
+use crate::{Sentinel, SharedFile, SharedFileType};
+use std::path::Path;
+use tracing::field::{display, Empty};
+use tracing::{info_span, Span};
+use uuid::Uuid;
+
+impl<T> SharedFile<T> {
+    /// Attaches a span of the application context, e.g. of the request uploading the file.
+    ///
+    /// Readers and writers created afterwards record their work in child spans
+    /// of it, such that a single trace shows the upload as well as every
+    /// consumer of the same file.
+    pub fn set_span(&self, span: Span) {
+        *self.sentinel.span.lock().expect("failed to lock span") = span;
+    }
+
+    /// Gets the span attached via [`set_span`](Self::set_span).
+    ///
+    /// Returns a disabled span if no span was attached.
+    pub fn span(&self) -> Span {
+        self.sentinel.span()
+    }
+}
+
+impl<T> Sentinel<T> {
+    pub(crate) fn span(&self) -> Span {
+        self.span.lock().expect("failed to lock span").clone()
+    }
+}
+
+impl<T> Sentinel<T>
+where
+    T: SharedFileType,
+{
+    /// Gets the path the file was persisted to, or the path of the original file, if any.
+    fn diagnostic_path(&self) -> Option<&Path> {
+        match self.persisted_path.get() {
+            Some(path) => Some(path.as_path()),
+            None => self.original.diagnostic_path(),
+        }
+    }
+
+    /// Records the path of the file in the span, if known.
+    fn record_path(&self, span: Span) -> Span {
+        if let Some(path) = self.diagnostic_path() {
+            span.record("file_path", display(path.display()));
+        }
+        span
+    }
+
+    /// Creates the span of a reader. Without an attached span,
+    /// the span is a child of the current span.
+    pub(crate) fn reader_span(&self, id: &Uuid) -> Span {
+        let parent = self.span();
+        let span = if parent.is_none() {
+            info_span!(
+                "shared_file_reader",
+                reader_id = %id,
+                file_id = %self.id,
+                file_path = Empty
+            )
+        } else {
+            info_span!(
+                parent: &parent,
+                "shared_file_reader",
+                reader_id = %id,
+                file_id = %self.id,
+                file_path = Empty
+            )
+        };
+        self.record_path(span)
+    }
+
+    /// Creates the span of a writer. Without an attached span,
+    /// the span is a child of the current span.
+    pub(crate) fn writer_span(&self) -> Span {
+        let parent = self.span();
+        let span = if parent.is_none() {
+            info_span!("shared_file_writer", file_id = %self.id, file_path = Empty)
+        } else {
+            info_span!(
+                parent: &parent,
+                "shared_file_writer",
+                file_id = %self.id,
+                file_path = Empty
+            )
+        };
+        self.record_path(span)
+    }
+}
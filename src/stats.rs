@@ -0,0 +1,80 @@
+//! Writer throughput metrics, notably the [`WriterStats`] type.
+
+use crate::{SharedFile, SharedFileWriter};
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::time::Duration;
+
+/// Counters describing the work of the writers of a file, see [`SharedFile::writer_stats`].
+///
+/// The counters accumulate over all writers of the file, e.g. when appending in multiple sessions.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct WriterStats {
+    /// The number of bytes written.
+    pub bytes_written: usize,
+    /// The number of successful write operations.
+    pub writes: usize,
+    /// The number of times written data was committed, e.g. by flushing.
+    pub commits: usize,
+    /// The number of times the file was synced to disk.
+    pub syncs: usize,
+    /// The total time spent syncing the file to disk.
+    pub sync_time: Duration,
+}
+
+/// The atomically updated counters behind [`WriterStats`].
+#[derive(Debug, Default)]
+pub(crate) struct WriterCounters {
+    bytes_written: AtomicUsize,
+    writes: AtomicUsize,
+    commits: AtomicUsize,
+    syncs: AtomicUsize,
+    sync_nanos: AtomicU64,
+}
+
+impl<T> SharedFile<T> {
+    /// Gets the counters describing the work of the writers of the file, e.g. to alert
+    /// on abnormally slow ingestion.
+    pub fn writer_stats(&self) -> WriterStats {
+        self.sentinel.stats.snapshot()
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Gets the counters describing the work of the writers of the file.
+    ///
+    /// See [`SharedFile::writer_stats`].
+    pub fn stats(&self) -> WriterStats {
+        self.sentinel().stats.snapshot()
+    }
+}
+
+impl WriterCounters {
+    /// Records a successful write of `len` bytes.
+    pub(crate) fn record_write(&self, len: usize) {
+        self.bytes_written.fetch_add(len, Ordering::Relaxed);
+        self.writes.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a commit of written data.
+    pub(crate) fn record_commit(&self) {
+        self.commits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Records a sync to disk that took `elapsed`.
+    pub(crate) fn record_sync(&self, elapsed: Duration) {
+        let nanos = u64::try_from(elapsed.as_nanos()).unwrap_or(u64::MAX);
+        self.syncs.fetch_add(1, Ordering::Relaxed);
+        self.sync_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    /// Takes a snapshot of the counters.
+    pub(crate) fn snapshot(&self) -> WriterStats {
+        WriterStats {
+            bytes_written: self.bytes_written.load(Ordering::Relaxed),
+            writes: self.writes.load(Ordering::Relaxed),
+            commits: self.commits.load(Ordering::Relaxed),
+            syncs: self.syncs.load(Ordering::Relaxed),
+            sync_time: Duration::from_nanos(self.sync_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
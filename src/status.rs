@@ -0,0 +1,86 @@
+//! State inspection, notably the [`WriteStatus`] type.
+
+use crate::{SharedFile, WriteState};
+use tokio::io;
+use tokio::sync::watch;
+
+/// The state of writing a file, see [`SharedFile::status`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum WriteStatus {
+    /// The file is still being written.
+    Pending {
+        /// The number of bytes committed and visible to readers.
+        committed: u64,
+        /// The number of bytes written, including uncommitted data.
+        written: u64,
+    },
+    /// The file was completely written.
+    Completed {
+        /// The size of the file in bytes.
+        len: u64,
+    },
+    /// Writing the file failed.
+    Failed,
+}
+
+impl From<WriteState> for WriteStatus {
+    fn from(state: WriteState) -> Self {
+        match state {
+            WriteState::Pending(committed, written) => WriteStatus::Pending { committed, written },
+            WriteState::Completed(len) => WriteStatus::Completed { len },
+            WriteState::Failed => WriteStatus::Failed,
+        }
+    }
+}
+
+impl<T> SharedFile<T> {
+    /// Gets the state of writing the file, e.g. to respond differently to requests
+    /// for files that are still being written.
+    ///
+    /// The state is a snapshot; the writer may progress at any time.
+    pub fn status(&self) -> WriteStatus {
+        self.sentinel.state.load().into()
+    }
+
+    /// Gets the number of bytes readers can read, i.e. the committed bytes of a file that
+    /// is still being written, or the size of a completed file.
+    ///
+    /// If writing the file failed, this is the number of bytes committed before the failure.
+    pub fn len(&self) -> u64 {
+        match self.sentinel.state.load() {
+            WriteState::Pending(committed, _written) => committed,
+            WriteState::Completed(len) => len,
+            WriteState::Failed => self.resume_offset(),
+        }
+    }
+
+    /// Determines whether readers cannot read any bytes yet, see [`len`](Self::len).
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Determines whether the file was completely written.
+    pub fn is_complete(&self) -> bool {
+        matches!(self.sentinel.state.load(), WriteState::Completed(_))
+    }
+
+    /// Subscribes to the state of writing the file, e.g. to react to the file being
+    /// completed or failing without reading it.
+    ///
+    /// Updates are published whenever the writer commits data, completes or fails.
+    /// Intermediate updates may be skipped if the subscriber is slower than the writer.
+    pub fn subscribe(&self) -> watch::Receiver<WriteStatus> {
+        self.sentinel.status.subscribe()
+    }
+
+    /// Waits until the writer completed the file, without reading it.
+    ///
+    /// Returns the size of the file in bytes, or the error readers would see if writing
+    /// the file failed.
+    ///
+    /// See [`completion`](Self::completion) for a named future, e.g. to store it in a struct.
+    pub async fn wait_until_complete(&self) -> io::Result<u64> {
+        self.completion().await
+    }
+}
@@ -0,0 +1,196 @@
+//! In-memory caching of recently written data, see [`SharedFileWriter::with_tail_cache`](crate::SharedFileWriter::with_tail_cache).
+
+use bytes::{Buf, Bytes, BytesMut};
+use std::collections::VecDeque;
+use std::io::SeekFrom;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::AsyncSeek;
+
+/// A bounded cache of the most recently written data of a file.
+#[derive(Debug, Default)]
+pub(crate) struct TailCache {
+    /// The maximum number of bytes to retain; zero if caching is disabled.
+    capacity: usize,
+    /// The file offset of the first retained byte.
+    start: u64,
+    /// The committed chunks, in file order.
+    chunks: VecDeque<Bytes>,
+    /// The number of bytes in the committed chunks.
+    committed: usize,
+    /// The data written but not yet committed, following the committed chunks.
+    pending: BytesMut,
+}
+
+impl TailCache {
+    /// Enables caching of up to `capacity` bytes written after the specified file offset.
+    pub(crate) fn enable(&mut self, capacity: usize, offset: u64) {
+        *self = Self {
+            capacity,
+            start: offset,
+            ..Self::default()
+        };
+    }
+
+    /// Discards all cached data and continues caching data written after the specified file offset.
+    pub(crate) fn truncate(&mut self, offset: u64) {
+        self.enable(self.capacity, offset);
+    }
+
+    /// Records written, uncommitted data at the specified file offset.
+    ///
+    /// Only sequentially written data is cached; writing anywhere else invalidates the cache.
+    pub(crate) fn write(&mut self, offset: u64, data: &[u8]) {
+        if self.capacity == 0 {
+            return;
+        }
+
+        if offset != self.start + (self.committed + self.pending.len()) as u64 {
+            self.enable(self.capacity, offset + data.len() as u64);
+            return;
+        }
+
+        self.pending.extend_from_slice(data);
+        self.evict();
+    }
+
+    /// Commits all written data, making it available to readers.
+    pub(crate) fn commit(&mut self) {
+        if self.pending.is_empty() {
+            return;
+        }
+
+        let chunk = self.pending.split().freeze();
+        self.committed += chunk.len();
+        self.chunks.push_back(chunk);
+    }
+
+    /// Gets up to `max` committed bytes starting at the specified file offset, if cached.
+    pub(crate) fn get(&self, position: u64, max: usize) -> Option<Bytes> {
+        if max == 0 || position < self.start || position >= self.start + self.committed as u64 {
+            return None;
+        }
+
+        let mut offset = self.start;
+        for chunk in &self.chunks {
+            if position < offset + chunk.len() as u64 {
+                let from = (position - offset) as usize;
+                let to = chunk.len().min(from + max);
+                return Some(chunk.slice(from..to));
+            }
+            offset += chunk.len() as u64;
+        }
+
+        None
+    }
+
+    /// Drops the oldest data until at most `capacity` bytes are retained.
+    fn evict(&mut self) {
+        while self.committed + self.pending.len() > self.capacity {
+            let Some(chunk) = self.chunks.pop_front() else {
+                // Only the most recently written data can be retained.
+                let excess = self.pending.len() - self.capacity;
+                self.pending.advance(excess);
+                self.start += excess as u64;
+                return;
+            };
+
+            self.committed -= chunk.len();
+            self.start += chunk.len() as u64;
+        }
+    }
+}
+
+/// A function seeking the file of a reader to an absolute position, see [`poll_seek`].
+pub(crate) type SeekFn<T> =
+    fn(Pin<&mut T>, &mut Context<'_>, u64, &mut bool) -> Poll<io::Result<()>>;
+
+/// Seeks the file to an absolute position. `started` keeps track of whether the seek
+/// operation was already started in a previous call.
+///
+/// This allows readers to seek their files from code not bounded by [`AsyncSeek`].
+pub(crate) fn poll_seek<T>(
+    mut file: Pin<&mut T>,
+    cx: &mut Context<'_>,
+    position: u64,
+    started: &mut bool,
+) -> Poll<io::Result<()>>
+where
+    T: AsyncSeek,
+{
+    if !*started {
+        file.as_mut().start_seek(SeekFrom::Start(position))?;
+        *started = true;
+    }
+
+    let result = ready!(file.poll_complete(cx));
+    *started = false;
+    Poll::Ready(result.map(|_| ()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_disabled() {
+        let mut cache = TailCache::default();
+        cache.write(0, b"hello");
+        cache.commit();
+        assert_eq!(cache.get(0, 5), None);
+    }
+
+    #[test]
+    fn test_only_committed_data_is_served() {
+        let mut cache = TailCache::default();
+        cache.enable(16, 0);
+        cache.write(0, b"hello");
+        assert_eq!(cache.get(0, 5), None);
+
+        cache.commit();
+        cache.write(5, b"world");
+        assert_eq!(cache.get(0, 10).as_deref(), Some(&b"hello"[..]));
+        assert_eq!(cache.get(2, 2).as_deref(), Some(&b"ll"[..]));
+        assert_eq!(cache.get(5, 5), None);
+
+        cache.commit();
+        assert_eq!(cache.get(5, 10).as_deref(), Some(&b"world"[..]));
+    }
+
+    #[test]
+    fn test_eviction() {
+        let mut cache = TailCache::default();
+        cache.enable(8, 0);
+        cache.write(0, b"hello");
+        cache.commit();
+        cache.write(5, b"world");
+        cache.commit();
+        assert_eq!(cache.get(0, 5), None);
+        assert_eq!(cache.get(5, 5).as_deref(), Some(&b"world"[..]));
+
+        // Data larger than the capacity retains its tail only.
+        cache.write(10, b"0123456789");
+        cache.commit();
+        assert_eq!(cache.get(10, 5), None);
+        assert_eq!(cache.get(12, 10).as_deref(), Some(&b"23456789"[..]));
+    }
+
+    #[test]
+    fn test_non_sequential_write_invalidates() {
+        let mut cache = TailCache::default();
+        cache.enable(16, 0);
+        cache.write(0, b"hello");
+        cache.commit();
+
+        // Overwriting cached data.
+        cache.write(0, b"J");
+        cache.commit();
+        assert_eq!(cache.get(0, 5), None);
+
+        // Caching resumes with sequential writes after the overwritten data.
+        cache.write(1, b"ello");
+        cache.commit();
+        assert_eq!(cache.get(1, 4).as_deref(), Some(&b"ello"[..]));
+    }
+}
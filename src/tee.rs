@@ -0,0 +1,197 @@
+//! Tee functionality, notably the [`TeeWriter`] type.
+
+use crate::errors::CompleteWritingError;
+use crate::{SharedFileType, SharedFileWriter};
+use pin_project::pin_project;
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+/// Determines how a [`TeeWriter`] handles errors of the secondary writer.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TeeErrorPolicy {
+    /// Returns the error and marks the file as failed, such that readers do not
+    /// consume data that was not forwarded.
+    #[default]
+    Fail,
+    /// Detaches the secondary writer and continues writing the file only.
+    Detach,
+}
+
+/// A writer adapter forwarding all data written to the file to a secondary writer,
+/// see [`SharedFileWriter::tee`].
+///
+/// Data is written to the file first and then forwarded to the secondary writer, e.g. an
+/// upstream connection. Data not yet forwarded is retained and forwarded before the next
+/// write, as well as when the writer is flushed or shut down.
+#[pin_project]
+pub struct TeeWriter<T, S, W = T> {
+    /// The writer to write to.
+    #[pin]
+    writer: SharedFileWriter<T, W>,
+    /// The secondary writer, unless it was detached.
+    secondary: Option<S>,
+    /// The data written to the file but not yet forwarded.
+    pending: Vec<u8>,
+    /// The number of pending bytes already forwarded.
+    forwarded: usize,
+    /// The policy for handling errors of the secondary writer.
+    policy: TeeErrorPolicy,
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Converts the writer into a [`TeeWriter`] forwarding all written data to `secondary`.
+    ///
+    /// ## Arguments
+    ///
+    /// * `secondary` - The writer to forward the data to.
+    /// * `policy` - The policy for handling errors of the secondary writer.
+    pub fn tee<S>(self, secondary: S, policy: TeeErrorPolicy) -> TeeWriter<T, S, W> {
+        TeeWriter {
+            writer: self,
+            secondary: Some(secondary),
+            pending: Vec::new(),
+            forwarded: 0,
+            policy,
+        }
+    }
+}
+
+impl<T, S, W> TeeWriter<T, S, W> {
+    /// Gets a reference to the underlying writer.
+    pub fn get_ref(&self) -> &SharedFileWriter<T, W> {
+        &self.writer
+    }
+
+    /// Gets a reference to the secondary writer, or [`None`] if it was detached.
+    pub fn secondary(&self) -> Option<&S> {
+        self.secondary.as_ref()
+    }
+}
+
+impl<T, S, W> TeeWriter<T, S, W>
+where
+    W: SharedFileType + AsyncWrite + Unpin,
+    W::SyncError: std::error::Error + Send + Sync + 'static,
+    S: AsyncWrite + Unpin,
+{
+    /// Forwards all pending data, shuts down the secondary writer and completes the
+    /// writing operation.
+    ///
+    /// See [`SharedFileWriter::complete`].
+    pub async fn complete(mut self) -> Result<(), CompleteWritingError> {
+        self.flush().await?;
+        if let Some(secondary) = self.secondary.as_mut() {
+            if let Err(e) = secondary.shutdown().await {
+                if self.policy == TeeErrorPolicy::Fail {
+                    self.writer.fail(&e);
+                    return Err(e.into());
+                }
+            }
+        }
+        self.writer.complete().await
+    }
+}
+
+impl<T, S, W> TeeWriter<T, S, W>
+where
+    S: AsyncWrite + Unpin,
+{
+    /// Handles an error of the secondary writer according to the policy.
+    fn handle_secondary_error(self: Pin<&mut Self>, error: Error) -> io::Result<()> {
+        let this = self.project();
+        match this.policy {
+            TeeErrorPolicy::Fail => {
+                this.writer.fail(&error);
+                Err(error)
+            }
+            TeeErrorPolicy::Detach => {
+                *this.secondary = None;
+                this.pending.clear();
+                *this.forwarded = 0;
+                Ok(())
+            }
+        }
+    }
+
+    /// Forwards all pending data to the secondary writer.
+    fn poll_forward(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        loop {
+            let this = self.as_mut().project();
+            let Some(secondary) = this.secondary.as_mut() else {
+                return Poll::Ready(Ok(()));
+            };
+            if *this.forwarded == this.pending.len() {
+                this.pending.clear();
+                *this.forwarded = 0;
+                return Poll::Ready(Ok(()));
+            }
+
+            let result =
+                ready!(Pin::new(secondary).poll_write(cx, &this.pending[*this.forwarded..]));
+            match result {
+                Ok(0) => {
+                    let error = Error::from(ErrorKind::WriteZero);
+                    return Poll::Ready(self.handle_secondary_error(error));
+                }
+                Ok(written) => *this.forwarded += written,
+                Err(e) => return Poll::Ready(self.handle_secondary_error(e)),
+            }
+        }
+    }
+
+    /// Polls an operation of the secondary writer, handling errors according to the policy.
+    fn poll_secondary(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        op: fn(Pin<&mut S>, &mut Context<'_>) -> Poll<io::Result<()>>,
+    ) -> Poll<io::Result<()>> {
+        let Some(secondary) = self.as_mut().project().secondary.as_mut() else {
+            return Poll::Ready(Ok(()));
+        };
+        match ready!(op(Pin::new(secondary), cx)) {
+            Ok(()) => Poll::Ready(Ok(())),
+            Err(e) => Poll::Ready(self.handle_secondary_error(e)),
+        }
+    }
+}
+
+impl<T, S, W> AsyncWrite for TeeWriter<T, S, W>
+where
+    W: AsyncWrite,
+    S: AsyncWrite + Unpin,
+{
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+
+        let this = self.as_mut().project();
+        let written = ready!(this.writer.poll_write(cx, buf))?;
+        if this.secondary.is_some() {
+            this.pending.extend_from_slice(&buf[..written]);
+        }
+
+        // Forward eagerly; data not forwarded yet is forwarded by the next operation.
+        if let Poll::Ready(Err(e)) = self.poll_forward(cx) {
+            return Poll::Ready(Err(e));
+        }
+        Poll::Ready(Ok(written))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+        ready!(self.as_mut().poll_secondary(cx, S::poll_flush))?;
+        self.project().writer.poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        ready!(self.as_mut().poll_forward(cx))?;
+        ready!(self.as_mut().poll_secondary(cx, S::poll_shutdown))?;
+        self.project().writer.poll_shutdown(cx)
+    }
+}
@@ -1,12 +1,15 @@
 //! Implementations for [`TempFile`](TempFile).
 
 use crate::errors::CompleteWritingError;
+use crate::reader::NODE_ID;
 use crate::{
-    AsyncNewFile, FilePath, SharedFile, SharedFileReader, SharedFileType, SharedFileWriter,
+    fastest_dir, AsyncNewFile, FilePath, Persist, SetLen, SharedFile, SharedFileReader,
+    SharedFileType, SharedFileWriter,
 };
 use async_tempfile::{Ownership, TempFile};
+use std::ffi::OsString;
 use std::ops::Deref;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use tokio::fs::File;
 use uuid::Uuid;
 
@@ -21,15 +24,16 @@ pub type SharedTemporaryFileWriter = SharedFileWriter<TempFile>;
 
 #[async_trait::async_trait]
 impl SharedFileType for TempFile {
-    type Type = TempFile;
+    type ReadHandle = TempFile;
+    type WriteHandle = TempFile;
     type OpenError = async_tempfile::Error;
     type SyncError = CompleteWritingError;
 
-    async fn open_ro(&self) -> Result<Self::Type, Self::OpenError> {
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
         self.open_ro().await
     }
 
-    async fn open_rw(&self) -> Result<Self::Type, Self::OpenError> {
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
         self.open_rw().await
     }
 
@@ -42,6 +46,60 @@ impl SharedFileType for TempFile {
         let file: &File = self.deref();
         Ok(file.sync_data().await?)
     }
+
+    fn diagnostic_path(&self) -> Option<&Path> {
+        Some(self.file_path())
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for TempFile {
+    type SetLenError = std::io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        let file: &File = self.deref();
+        file.set_len(size).await
+    }
+}
+
+#[async_trait::async_trait]
+impl Persist for TempFile {
+    type PersistError = std::io::Error;
+
+    async fn persist(&self, path: &Path) -> Result<(), Self::PersistError> {
+        // Hard-linking keeps the temporary path valid for readers opened later. It is removed
+        // when the last handle is dropped, leaving the persisted file behind. Renaming a staged
+        // link over the destination replaces an existing file atomically.
+        let name = self.file_path().file_name().unwrap_or_default();
+        let mut staged_name = OsString::from(".");
+        staged_name.push(name);
+        staged_name.push(".persist");
+        let staged = path.with_file_name(staged_name);
+
+        tokio::fs::hard_link(self.file_path(), &staged).await?;
+        if let Err(e) = tokio::fs::rename(&staged, path).await {
+            let _ = tokio::fs::remove_file(&staged).await;
+            return Err(e);
+        }
+
+        sync_parent_dir(path).await
+    }
+}
+
+/// Syncs the directory containing `path` so that a rename into it is durable.
+#[cfg(unix)]
+async fn sync_parent_dir(path: &Path) -> std::io::Result<()> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    File::open(parent).await?.sync_all().await
+}
+
+/// Directories cannot be synced on this platform.
+#[cfg(not(unix))]
+async fn sync_parent_dir(_path: &Path) -> std::io::Result<()> {
+    Ok(())
 }
 
 #[async_trait::async_trait]
@@ -72,6 +130,66 @@ impl SharedTemporaryFile {
         Ok(Self::from(file))
     }
 
+    /// Creates a new temporary file in the specified directory.
+    /// Convenience wrapper around [`TempFile::new_in`] and [`SharedFile::from`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `dir` - The directory to create the file in.
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> Result<Self, async_tempfile::Error> {
+        let file = TempFile::new_in(dir.as_ref().to_path_buf()).await?;
+        Ok(Self::from(file))
+    }
+
+    /// Creates a new temporary file with the specified name in the specified directory.
+    /// Convenience wrapper around [`TempFile::new_with_name_in`] and [`SharedFile::from`].
+    ///
+    /// ## Arguments
+    ///
+    /// * `name` - The name of the file.
+    /// * `dir` - The directory to create the file in.
+    pub async fn new_with_name_in<N: AsRef<str>, P: AsRef<Path>>(
+        name: N,
+        dir: P,
+    ) -> Result<Self, async_tempfile::Error> {
+        let file = TempFile::new_with_name_in(name, dir.as_ref().to_path_buf()).await?;
+        Ok(Self::from(file))
+    }
+
+    /// Creates a new temporary file in the specified directory, named using the specified
+    /// prefix and suffix around a unique ID, e.g. to identify the file while debugging.
+    ///
+    /// ## Arguments
+    ///
+    /// * `prefix` - The beginning of the file name.
+    /// * `suffix` - The end of the file name, e.g. an extension such as `.json`.
+    /// * `dir` - The directory to create the file in.
+    pub async fn new_with_affixes_in<P: AsRef<Path>>(
+        prefix: &str,
+        suffix: &str,
+        dir: P,
+    ) -> Result<Self, async_tempfile::Error> {
+        let name = format!("{prefix}{}{suffix}", Uuid::now_v1(NODE_ID));
+        Self::new_with_name_in(name, dir).await
+    }
+
+    /// Creates a new temporary file in the first of the candidate directories that is on a
+    /// memory-backed file system, such as tmpfs, or else in the first one that exists,
+    /// see [`fastest_dir`]. Falls back to the default location if none of them exist.
+    ///
+    /// ## Arguments
+    ///
+    /// * `candidates` - The directories to choose from, in order of preference.
+    pub async fn new_in_fastest<P: AsRef<Path>>(
+        candidates: &[P],
+    ) -> Result<Self, async_tempfile::Error> {
+        let file = match fastest_dir(candidates).await {
+            Some(dir) => TempFile::new_in(dir).await?,
+            None => TempFile::new().await?,
+        };
+        Ok(Self::from(file))
+    }
+
     /// Wraps a new instance of this type around an existing file. This is a convenience
     /// wrapper around [`TempFile::from_existing`] and [`SharedFile::from`].
     ///
@@ -90,8 +208,8 @@ impl SharedTemporaryFile {
         Ok(Self::from(file))
     }
 
-    /// Returns the path of the underlying temporary file.
+    /// Returns the path of the underlying temporary file, or the path it was persisted to.
     pub fn file_path(&self) -> &PathBuf {
-        self.sentinel.original.file_path()
+        self.sentinel.file_path()
     }
 }
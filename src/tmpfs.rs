@@ -0,0 +1,89 @@
+//! Detection of memory-backed file systems, notably [`is_memory_backed`] and [`fastest_dir`].
+
+use std::path::{Path, PathBuf};
+use tokio::io;
+
+/// The file system types keeping their data in memory.
+#[cfg(target_os = "linux")]
+const MEMORY_FILE_SYSTEMS: [&str; 2] = ["tmpfs", "ramfs"];
+
+/// Determines whether the directory is on a memory-backed file system, such as tmpfs or ramfs.
+///
+/// On Linux, this looks up the file system the directory is mounted on in `/proc/self/mounts`.
+/// On other platforms, this always returns `false`.
+#[cfg(target_os = "linux")]
+pub async fn is_memory_backed<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    let dir = tokio::fs::canonicalize(dir).await?;
+    let mounts = tokio::fs::read_to_string("/proc/self/mounts").await?;
+
+    // The most specific mount point containing the directory determines its file system.
+    let mut best: Option<(PathBuf, &str)> = None;
+    for line in mounts.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(_device), Some(mount_point), Some(fs_type)) =
+            (fields.next(), fields.next(), fields.next())
+        else {
+            continue;
+        };
+        let mount_point = PathBuf::from(unescape_mount_point(mount_point));
+        if !dir.starts_with(&mount_point) {
+            continue;
+        }
+        // Later entries shadow earlier ones mounted at the same point.
+        if best.as_ref().map_or(true, |(best, _)| {
+            mount_point.as_os_str().len() >= best.as_os_str().len()
+        }) {
+            best = Some((mount_point, fs_type));
+        }
+    }
+
+    Ok(best.map_or(false, |(_, fs_type)| MEMORY_FILE_SYSTEMS.contains(&fs_type)))
+}
+
+/// Determines whether the directory is on a memory-backed file system, such as tmpfs or ramfs.
+///
+/// On Linux, this looks up the file system the directory is mounted on in `/proc/self/mounts`.
+/// On other platforms, this always returns `false`.
+#[cfg(not(target_os = "linux"))]
+pub async fn is_memory_backed<P: AsRef<Path>>(dir: P) -> io::Result<bool> {
+    tokio::fs::metadata(dir).await?;
+    Ok(false)
+}
+
+/// Selects the first of the candidate directories that is on a memory-backed file system,
+/// see [`is_memory_backed`], or else the first one that exists.
+///
+/// Returns `None` if none of the directories exist.
+pub async fn fastest_dir<P: AsRef<Path>>(candidates: &[P]) -> Option<PathBuf> {
+    let mut fallback = None;
+    for dir in candidates {
+        let dir = dir.as_ref();
+        match is_memory_backed(dir).await {
+            Ok(true) => return Some(dir.to_path_buf()),
+            Ok(false) if fallback.is_none() && dir.is_dir() => fallback = Some(dir.to_path_buf()),
+            _ => {}
+        }
+    }
+    fallback
+}
+
+/// Replaces the octal escapes of whitespace and backslashes in a mount point.
+#[cfg(target_os = "linux")]
+fn unescape_mount_point(mount_point: &str) -> String {
+    mount_point
+        .replace("\\040", " ")
+        .replace("\\011", "\t")
+        .replace("\\012", "\n")
+        .replace("\\134", "\\")
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_mount_point() {
+        assert_eq!(unescape_mount_point("/mnt/my\\040disk"), "/mnt/my disk");
+        assert_eq!(unescape_mount_point("/mnt/a\\134b"), "/mnt/a\\b");
+    }
+}
@@ -0,0 +1,67 @@
+//! Implementations for [`tokio::fs::File`].
+
+use crate::{SetLen, SharedFileType};
+use tokio::fs::File;
+use tokio::io;
+
+/// Allows sharing an already open file, e.g. `SharedFile::from(file)`.
+///
+/// Readers and writers reopen the file through `/proc/self/fd` so that every handle has
+/// its own position. This also works for files that were already unlinked. On platforms
+/// other than Linux and Android, opening handles fails with [`ErrorKind::Unsupported`](std::io::ErrorKind::Unsupported).
+#[async_trait::async_trait]
+impl SharedFileType for File {
+    type ReadHandle = File;
+    type WriteHandle = File;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        reopen(self, false).await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        reopen(self, true).await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        File::sync_all(self).await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        File::sync_data(self).await
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for File {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        File::set_len(self, size).await
+    }
+}
+
+/// Opens a new handle to the file with its own position.
+///
+/// Duplicating the file descriptor is not sufficient since duplicates share their position.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+async fn reopen(file: &File, write: bool) -> io::Result<File> {
+    use std::os::fd::AsRawFd;
+
+    let path = format!("/proc/self/fd/{}", file.as_raw_fd());
+    tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(write)
+        .open(path)
+        .await
+}
+
+/// Opening a new handle with its own position is not supported on this platform.
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+async fn reopen(_file: &File, _write: bool) -> io::Result<File> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "Reopening a file is not supported on this platform",
+    ))
+}
@@ -0,0 +1,68 @@
+//! Checksum trailers appended by [`SharedFileWriter::complete_with_trailer`](crate::SharedFileWriter::complete_with_trailer)
+//! and verified by [`SharedFileReader::verify_trailer`](crate::SharedFileReader::verify_trailer).
+//!
+//! The trailer is stored after the logical end of the file and consists of a magic
+//! value, the logical length as a little-endian `u64` and the CRC-32 checksum of the
+//! logical content as a little-endian `u32`.
+
+use crate::crc32::Crc32;
+
+/// The number of bytes occupied by a trailer.
+pub const TRAILER_LEN: usize = 16;
+
+/// The magic value identifying a trailer.
+const TRAILER_MAGIC: [u8; 4] = *b"SFCK";
+
+/// Encodes a trailer for content of the specified length and checksum.
+pub(crate) fn encode_trailer(len: u64, crc: u32) -> [u8; TRAILER_LEN] {
+    let mut trailer = [0u8; TRAILER_LEN];
+    trailer[..4].copy_from_slice(&TRAILER_MAGIC);
+    trailer[4..12].copy_from_slice(&len.to_le_bytes());
+    trailer[12..].copy_from_slice(&crc.to_le_bytes());
+    trailer
+}
+
+/// Decodes a trailer into the length and checksum of the content, or [`None`]
+/// if the bytes do not form a trailer.
+pub(crate) fn decode_trailer(trailer: &[u8; TRAILER_LEN]) -> Option<(u64, u32)> {
+    if trailer[..4] != TRAILER_MAGIC {
+        return None;
+    }
+
+    let mut len = [0u8; 8];
+    len.copy_from_slice(&trailer[4..12]);
+    let mut crc = [0u8; 4];
+    crc.copy_from_slice(&trailer[12..]);
+    Some((u64::from_le_bytes(len), u32::from_le_bytes(crc)))
+}
+
+/// The state of a reader verifying the trailer.
+#[derive(Debug, Default)]
+pub(crate) struct TrailerCheck {
+    /// The checksum of all bytes read so far.
+    pub(crate) crc: Crc32,
+    /// The trailer bytes read so far.
+    pub(crate) buffer: [u8; TRAILER_LEN],
+    /// The number of trailer bytes read so far.
+    pub(crate) filled: usize,
+    /// Whether the verification failed.
+    pub(crate) failed: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_roundtrip() {
+        let trailer = encode_trailer(1234, 0xDEAD_BEEF);
+        assert_eq!(decode_trailer(&trailer), Some((1234, 0xDEAD_BEEF)));
+    }
+
+    #[test]
+    fn test_invalid_magic() {
+        let mut trailer = encode_trailer(1234, 0xDEAD_BEEF);
+        trailer[0] = 0;
+        assert_eq!(decode_trailer(&trailer), None);
+    }
+}
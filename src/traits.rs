@@ -1,13 +1,18 @@
 //! Contains public traits.
 
-use std::path::PathBuf;
-use tokio::io::{AsyncRead, AsyncWrite};
+use crate::ReaderPriority;
+use std::path::{Path, PathBuf};
+use std::task::Waker;
+use uuid::Uuid;
 
 /// Trait for types used as a file storage backend.
 #[async_trait::async_trait]
-pub trait SharedFileType: AsyncRead + AsyncWrite {
-    /// The type created when producing a reader or writer. Typically `Self`.
-    type Type;
+pub trait SharedFileType {
+    /// The type created when producing a reader. Typically `Self`.
+    type ReadHandle;
+
+    /// The type created when producing a writer. Typically `Self`.
+    type WriteHandle;
 
     /// The error type.
     type OpenError;
@@ -15,17 +20,24 @@ pub trait SharedFileType: AsyncRead + AsyncWrite {
     /// The error type.
     type SyncError;
 
-    /// Opens a new [`Type`](Self::Type) instance in read-only mode.
-    async fn open_ro(&self) -> Result<Self::Type, Self::OpenError>;
+    /// Opens a new [`ReadHandle`](Self::ReadHandle) instance in read-only mode.
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError>;
 
-    /// Opens a new [`Type`](Self::Type) instance in read-write mode.
-    async fn open_rw(&self) -> Result<Self::Type, Self::OpenError>;
+    /// Opens a new [`WriteHandle`](Self::WriteHandle) instance in read-write mode.
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError>;
 
     /// Synchronizes data and metadata with the underlying buffer.
     async fn sync_all(&self) -> Result<(), Self::SyncError>;
 
     /// Synchronizes data with the underlying buffer.
     async fn sync_data(&self) -> Result<(), Self::SyncError>;
+
+    /// Gets the path of the file, if any, to identify it in diagnostics such as traces.
+    ///
+    /// Returns [`None`] by default.
+    fn diagnostic_path(&self) -> Option<&Path> {
+        None
+    }
 }
 
 /// Trait for types that can be newly constructed asynchronously.
@@ -51,6 +63,78 @@ pub trait NewFile {
     fn new() -> Result<Self::Target, Self::Error>;
 }
 
+/// Trait for file types that can be truncated, see [`SharedFile::truncate`](crate::SharedFile::truncate).
+#[async_trait::async_trait]
+pub trait SetLen {
+    /// The error type.
+    type SetLenError;
+
+    /// Truncates or extends the underlying file to the specified size in bytes.
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError>;
+}
+
+/// Trait for file types that can be moved to a final destination, see
+/// [`SharedFile::persist`](crate::SharedFile::persist).
+#[async_trait::async_trait]
+pub trait Persist {
+    /// The error type.
+    type PersistError;
+
+    /// Atomically moves the file to the specified path, replacing any existing file.
+    ///
+    /// Handles opened before or after persisting must remain valid, and the file at the
+    /// specified path must not be deleted when the instance is dropped.
+    async fn persist(&self, path: &Path) -> Result<(), Self::PersistError>;
+}
+
+/// Trait for incrementally computed digests, such as checksums or cryptographic hashes.
+pub trait Digest: Send + Sync {
+    /// Updates the digest with the specified bytes.
+    fn update(&mut self, data: &[u8]);
+
+    /// Finalizes the digest and returns its value.
+    fn finalize(self: Box<Self>) -> Vec<u8>;
+}
+
+/// Trait for handling state changes of a file, see
+/// [`SharedFile::add_event_handler`](crate::SharedFile::add_event_handler).
+///
+/// Handlers run inline, e.g. while the writer is flushed on an executor thread, and must
+/// not block. Hand off slow work such as file I/O to a task instead.
+///
+/// All methods do nothing by default.
+pub trait EventHandler: Send + Sync {
+    /// Called with the number of committed bytes whenever data is committed.
+    fn on_commit(&self, _committed: u64) {}
+
+    /// Called with the size of the file once it is completed.
+    fn on_complete(&self, _len: u64) {}
+
+    /// Called with the error readers see once writing the file fails.
+    fn on_failure(&self, _error: &std::io::Error) {}
+
+    /// Called with the ID and lag of a reader once its lag exceeds its limit, see
+    /// [`SharedFileReader::with_lag_warning`](crate::SharedFileReader::with_lag_warning).
+    fn on_reader_lagging(&self, _reader: Uuid, _lag: u64) {}
+}
+
+/// Trait for strategies waking up readers waiting for data, see
+/// [`SharedFileBuilder::notifier`](crate::SharedFileBuilder::notifier).
+///
+/// The default strategy is [`WakerMap`](crate::WakerMap).
+pub trait ReaderNotifier: Send + Sync {
+    /// Registers the waker of a reader waiting for data, replacing its previously registered waker.
+    fn register(&self, id: Uuid, priority: ReaderPriority, waker: &Waker);
+
+    /// Removes the waker of a reader, e.g. because it was dropped.
+    fn remove(&self, id: &Uuid);
+
+    /// Wakes up all registered readers after data was committed or the state of the file changed.
+    ///
+    /// Readers register their wakers again if they need to wait further.
+    fn notify_all(&self);
+}
+
 /// Trait for types that can synchronously determine the file path.
 pub trait FilePath {
     /// Obtains the path of the temporary file.
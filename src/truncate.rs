@@ -0,0 +1,40 @@
+//! Truncation functionality, notably [`SharedFile::truncate`].
+
+use crate::{SetLen, SharedFile, WriteState};
+use std::sync::atomic::Ordering;
+
+impl<T> SharedFile<T>
+where
+    T: SetLen,
+{
+    /// Truncates the file to `len` bytes and resets it to a pending state, e.g. to retry
+    /// a failed transcode into the same file.
+    ///
+    /// Afterwards, the file contains `len` committed bytes and can be written again using
+    /// [`writer`](Self::writer) (when truncated to zero) or [`append_writer`](Self::append_writer).
+    /// If `len` exceeds the size of the file, the file is extended with zeros.
+    ///
+    /// Readers positioned at or before `len` continue reading the new data. Readers that
+    /// already read past `len` fail with [`ReadError::Truncated`](crate::prelude::ReadError::Truncated).
+    ///
+    /// <div class="warning">No writer must be active while the file is truncated.</div>
+    pub async fn truncate(&self, len: u64) -> Result<(), T::SetLenError> {
+        self.sentinel.original.set_len(len).await?;
+
+        self.sentinel.truncate_readers(len);
+        self.sentinel.truncate_holes(len);
+        self.sentinel.truncate_checkpoints(len);
+        self.sentinel
+            .tail_cache
+            .lock()
+            .expect("failed to lock tail cache for writing")
+            .truncate(len);
+        self.sentinel.clear_failure();
+        self.sentinel.clear_digest();
+        self.sentinel.expected_len.store(None);
+        self.sentinel.state.store(WriteState::Pending(len, len));
+        self.sentinel.truncations.fetch_add(1, Ordering::AcqRel);
+        self.sentinel.wake_readers();
+        Ok(())
+    }
+}
@@ -0,0 +1,155 @@
+//! Unnamed temporary files created with `O_TMPFILE`, notably the [`UnnamedTempFile`] type.
+
+use crate::{
+    AsyncNewFile, Persist, SetLen, SharedFile, SharedFileReader, SharedFileType, SharedFileWriter,
+};
+use std::ffi::{CString, OsString};
+use std::os::fd::AsRawFd;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use tokio::fs::{File, OpenOptions};
+use tokio::io;
+
+/// A type alias for a [`SharedFile`] wrapping an [`UnnamedTempFile`].
+pub type SharedUnnamedTempFile = SharedFile<UnnamedTempFile>;
+
+/// A type alias for a [`SharedFileReader`] wrapping an [`UnnamedTempFile`].
+pub type SharedUnnamedTempFileReader = SharedFileReader<UnnamedTempFile, File>;
+
+/// A type alias for a [`SharedFileWriter`] wrapping an [`UnnamedTempFile`].
+pub type SharedUnnamedTempFileWriter = SharedFileWriter<UnnamedTempFile, File>;
+
+/// A temporary file created with `O_TMPFILE`, see `open(2)`.
+///
+/// The file never appears in a directory listing and is removed by the kernel once the last
+/// handle to it is closed, even if the process crashes. It can be given a name using
+/// [`SharedFile::persist`], keeping the permissions `0o600`. Requires a file system supporting
+/// `O_TMPFILE`, such as ext4, XFS, Btrfs or tmpfs.
+#[derive(Debug)]
+pub struct UnnamedTempFile {
+    /// The open file.
+    file: File,
+}
+
+impl UnnamedTempFile {
+    /// Creates an unnamed temporary file in the default temporary directory.
+    pub async fn new() -> io::Result<Self> {
+        Self::new_in(std::env::temp_dir()).await
+    }
+
+    /// Creates an unnamed temporary file on the file system of the specified directory.
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(libc::O_TMPFILE)
+            .mode(0o600)
+            .open(dir)
+            .await?;
+        Ok(Self { file })
+    }
+
+    /// Gets the open file.
+    pub fn file(&self) -> &File {
+        &self.file
+    }
+}
+
+impl SharedUnnamedTempFile {
+    /// Creates a shared unnamed temporary file on the file system of the specified directory.
+    /// Convenience wrapper around [`UnnamedTempFile::new_in`] and [`SharedFile::from`].
+    pub async fn new_in<P: AsRef<Path>>(dir: P) -> io::Result<Self> {
+        Ok(Self::from(UnnamedTempFile::new_in(dir).await?))
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for UnnamedTempFile {
+    type ReadHandle = File;
+    type WriteHandle = File;
+    type OpenError = io::Error;
+    type SyncError = io::Error;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        self.file.open_ro().await
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.file.open_rw().await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_all().await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        self.file.sync_data().await
+    }
+}
+
+#[async_trait::async_trait]
+impl SetLen for UnnamedTempFile {
+    type SetLenError = io::Error;
+
+    async fn set_len(&self, size: u64) -> Result<(), Self::SetLenError> {
+        self.file.set_len(size).await
+    }
+}
+
+#[async_trait::async_trait]
+impl AsyncNewFile for UnnamedTempFile {
+    type Target = UnnamedTempFile;
+    type Error = io::Error;
+
+    async fn new_async() -> Result<Self::Target, Self::Error> {
+        Self::new().await
+    }
+}
+
+#[async_trait::async_trait]
+impl Persist for UnnamedTempFile {
+    type PersistError = io::Error;
+
+    async fn persist(&self, path: &Path) -> Result<(), Self::PersistError> {
+        // linkat(2) does not replace existing files. Linking to a staged name first and renaming
+        // it over the destination replaces an existing file atomically.
+        let name = path.file_name().unwrap_or_default();
+        let mut staged_name = OsString::from(".");
+        staged_name.push(name);
+        staged_name.push(".persist");
+        let staged = path.with_file_name(staged_name);
+
+        let source = CString::new(format!("/proc/self/fd/{}", self.file.as_raw_fd()))?;
+        let target = CString::new(staged.as_os_str().as_bytes())?;
+        tokio::task::spawn_blocking(move || link(&source, &target)).await??;
+        if let Err(e) = tokio::fs::rename(&staged, path).await {
+            let _ = tokio::fs::remove_file(&staged).await;
+            return Err(e);
+        }
+
+        let parent = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        File::open(parent).await?.sync_all().await
+    }
+}
+
+/// Gives the file referred to by the `/proc/self/fd` entry `source` the name `target`.
+fn link(source: &CString, target: &CString) -> io::Result<()> {
+    // SAFETY: Both paths are valid, NUL-terminated strings.
+    let result = unsafe {
+        libc::linkat(
+            libc::AT_FDCWD,
+            source.as_ptr(),
+            libc::AT_FDCWD,
+            target.as_ptr(),
+            libc::AT_SYMLINK_FOLLOW,
+        )
+    };
+    if result == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::last_os_error())
+    }
+}
@@ -0,0 +1,48 @@
+//! Detection of stalled writers, notably [`SharedFile::spawn_watchdog`].
+
+use crate::{FailureReason, SharedFile, WriteState};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep_until, Instant};
+
+impl<T> SharedFile<T>
+where
+    T: Send + Sync + 'static,
+{
+    /// Spawns a task marking the file as failed if the writer makes no progress for `timeout`,
+    /// e.g. because the upstream source silently died.
+    ///
+    /// Readers of a stalled file fail with
+    /// [`ReadError::WriterStalled`](crate::prelude::ReadError::WriterStalled) instead of
+    /// waiting forever. The task ends once the file is completed, failed or dropped.
+    ///
+    /// This must be called from within a Tokio runtime.
+    pub fn spawn_watchdog(&self, timeout: Duration) -> JoinHandle<()> {
+        // The watchdog must not keep the file alive.
+        let sentinel = Arc::downgrade(&self.sentinel);
+        let started = Instant::now();
+        tokio::spawn(async move {
+            loop {
+                let Some(last_progress) = sentinel
+                    .upgrade()
+                    .map(|sentinel| sentinel.last_progress.load().max(started))
+                else {
+                    return;
+                };
+                sleep_until(last_progress + timeout).await;
+
+                let Some(sentinel) = sentinel.upgrade() else {
+                    return;
+                };
+                if !matches!(sentinel.state.load(), WriteState::Pending(_, _)) {
+                    return;
+                }
+                if sentinel.last_progress.load() <= last_progress {
+                    sentinel.fail_with(FailureReason::Stalled(timeout));
+                    return;
+                }
+            }
+        })
+    }
+}
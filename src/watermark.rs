@@ -0,0 +1,68 @@
+//! Reader position tracking, notably [`SharedFile::slowest_reader_position`].
+
+use crate::{Sentinel, SharedFile, SharedFileWriter};
+use std::task::Waker;
+
+impl<T> SharedFile<T> {
+    /// Gets the read position of the slowest active reader, i.e. the minimum number
+    /// of bytes read across all readers, or `None` if there are no active readers.
+    ///
+    /// All data before this position was read by every active reader. Note that readers
+    /// created later start at the beginning of the file again.
+    pub fn slowest_reader_position(&self) -> Option<u64> {
+        self.sentinel.slowest_reader_position()
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W> {
+    /// Gets the read position of the slowest active reader, or `None` if there are no active readers.
+    ///
+    /// See [`SharedFile::slowest_reader_position`] for details.
+    pub fn slowest_reader_position(&self) -> Option<u64> {
+        self.sentinel().slowest_reader_position()
+    }
+
+    /// Waits until all active readers have read at least `position` bytes.
+    ///
+    /// This allows the writer to apply backpressure by not running too far ahead of its readers.
+    /// Returns immediately if there are no active readers; readers that are dropped
+    /// no longer hold back the writer.
+    pub async fn wait_for_readers(&self, position: u64) {
+        let sentinel = self.sentinel();
+        let _watch = sentinel.watch_progress();
+        loop {
+            // Register for notifications before checking to avoid missing an update.
+            let notified = sentinel.readers_progressed.notified();
+            match sentinel.slowest_reader_position() {
+                Some(slowest) if slowest < position => notified.await,
+                _ => return,
+            }
+        }
+    }
+}
+
+impl<T> Sentinel<T> {
+    /// Registers the waker of a writer waiting for readers to catch up.
+    pub(crate) fn register_writer_waker(&self, waker: &Waker) {
+        let mut lock = self
+            .writer_waker
+            .lock()
+            .expect("failed to lock writer waker for writing");
+        match lock.as_mut() {
+            Some(current) if current.will_wake(waker) => {}
+            _ => *lock = Some(waker.clone()),
+        }
+    }
+
+    /// Wakes up a writer waiting for readers to catch up, if any.
+    pub(crate) fn wake_writer(&self) {
+        let waker = self
+            .writer_waker
+            .lock()
+            .expect("failed to lock writer waker for writing")
+            .take();
+        if let Some(waker) = waker {
+            waker.wake();
+        }
+    }
+}
@@ -0,0 +1,60 @@
+//! Weak handles to shared files, notably the [`SharedFileWeak`] type.
+
+use crate::{Sentinel, SharedFile};
+use std::sync::{Arc, Weak};
+
+/// A weak handle to a [`SharedFile`] that does not keep the file alive,
+/// see [`SharedFile::downgrade`].
+///
+/// This allows registries and caches to reference shared files without keeping them,
+/// and their disk space, alive once all strong handles, readers and writers were dropped.
+#[derive(Debug)]
+pub struct SharedFileWeak<T> {
+    /// The sentinel of the file, if still alive.
+    sentinel: Weak<Sentinel<T>>,
+}
+
+impl<T> SharedFile<T> {
+    /// Creates a weak handle to the file.
+    pub fn downgrade(&self) -> SharedFileWeak<T> {
+        SharedFileWeak {
+            sentinel: Arc::downgrade(&self.sentinel),
+        }
+    }
+}
+
+impl<T> SharedFileWeak<T> {
+    /// Creates a weak handle that never upgrades, e.g. as a placeholder.
+    pub fn new() -> Self {
+        Self {
+            sentinel: Weak::new(),
+        }
+    }
+
+    /// Attempts to obtain a strong handle to the file, returning [`None`] if the file
+    /// was already dropped.
+    pub fn upgrade(&self) -> Option<SharedFile<T>> {
+        self.sentinel
+            .upgrade()
+            .map(|sentinel| SharedFile { sentinel })
+    }
+
+    /// Determines whether both handles refer to the same file.
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.sentinel.ptr_eq(&other.sentinel)
+    }
+}
+
+impl<T> Clone for SharedFileWeak<T> {
+    fn clone(&self) -> Self {
+        Self {
+            sentinel: Weak::clone(&self.sentinel),
+        }
+    }
+}
+
+impl<T> Default for SharedFileWeak<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
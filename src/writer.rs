@@ -1,16 +1,25 @@
 //! File writing functionality, notably the [`SharedFileWriter`] type.
 
+use crate::auto_sync::AutoSyncPolicy;
+use crate::crc32::Crc32;
 use crate::errors::{CompleteWritingError, WriteError};
-use crate::{FilePath, Sentinel, SharedFileType, WriteState};
+use crate::ranges::WrittenRanges;
+use crate::rate_limit::{RateLimit, TokenBucket};
+use crate::shutdown::{Shutdown, ShutdownBehavior};
+use crate::trailer::encode_trailer;
+use crate::{Digest, FailureReason, FilePath, Sentinel, SetLen, SharedFileType, WriteState};
 use crossbeam::atomic::AtomicCell;
 use pin_project::{pin_project, pinned_drop};
-use std::io::{Error, ErrorKind, IoSlice};
+use std::future::{poll_fn, Future};
+use std::io::{Error, ErrorKind, IoSlice, SeekFrom};
 use std::path::PathBuf;
-use std::pin::Pin;
+use std::pin::{pin, Pin};
+use std::sync::atomic::Ordering;
 use std::sync::Arc;
-use std::task::{Context, Poll};
+use std::task::{ready, Context, Poll};
 use tokio::io;
-use tokio::io::AsyncWrite;
+use tokio::io::{AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+use tokio::time::{sleep, Instant, Sleep};
 
 /// A writer for the shared temporary file.
 ///
@@ -18,47 +27,272 @@ use tokio::io::AsyncWrite;
 ///
 /// Note that while dropping the writer while implicitly change it to "completed",
 /// you must manually call [`SharedFileWriter::sync_all`] or [`SharedFileWriter::sync_data`]
-/// to ensure all content is flushed to the underlying buffer.
-#[pin_project(PinnedDrop)]
-pub struct SharedFileWriter<T> {
+/// to ensure all content is flushed to the underlying buffer. If the writer is dropped
+/// with uncommitted data, the file is marked as failed instead and readers fail with
+/// [`ReadError::UncommittedData`](crate::prelude::ReadError::UncommittedData).
+///
+/// The writer writes to a handle of type `W`, as opened by
+/// [`SharedFileType::open_rw`] from the file of type `T`.
+#[pin_project(PinnedDrop, project = SharedFileWriterProjection)]
+pub struct SharedFileWriter<T, W = T> {
     /// The file to write to.
     #[pin]
-    file: T,
+    file: W,
     /// The sentinel value to keep the file alive.
     sentinel: Arc<Sentinel<T>>,
+    /// The current write position in the file.
+    position: u64,
+    /// The byte ranges written so far; only the contiguous prefix is committed.
+    ranges: WrittenRanges,
+    /// The checksum of all bytes written, if tracked.
+    checksum: Option<Crc32>,
+    /// The digest of all bytes written, if tracked.
+    digest: Option<Box<dyn Digest>>,
+    /// The maximum number of bytes the writer may be ahead of the slowest reader, if bounded.
+    max_lag: Option<u64>,
+    /// The rate limit of the writer, if any.
+    rate_limit: Option<TokenBucket>,
+    /// The delay to wait for until writing is allowed by the rate limit.
+    rate_delay: Option<Pin<Box<Sleep>>>,
+    /// The behavior and state of shutting down the writer.
+    shutdown: Shutdown,
+    /// Whether written data is retained in the tail cache.
+    tail_cache: bool,
+    /// The policy for automatically committing written data, if any.
+    auto_sync: Option<AutoSyncPolicy>,
+    /// The time the oldest uncommitted data was written, if known.
+    uncommitted_since: Option<Instant>,
+    /// Whether an automatic commit was started but not yet completed.
+    auto_commit_pending: bool,
+    /// The remainder of the item sent to the sink that was not yet written.
+    #[cfg(feature = "futures-sink")]
+    sink_item: Option<bytes::Bytes>,
+    /// The span the writer's work is recorded in.
+    #[cfg(feature = "tracing")]
+    span: tracing::Span,
+}
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    T: SharedFileType,
+{
+    pub(crate) fn new(file: W, sentinel: Arc<Sentinel<T>>) -> Self {
+        Self::new_at(file, sentinel, 0)
+    }
+
+    /// Creates a writer whose file is positioned at `position`, after the already written data.
+    pub(crate) fn new_at(file: W, sentinel: Arc<Sentinel<T>>, position: u64) -> Self {
+        sentinel.active_writers.fetch_add(1, Ordering::AcqRel);
+        let options = sentinel.writer_options;
+        if options.max_lag.is_some() {
+            sentinel.add_progress_watcher();
+        }
+        let writer = Self {
+            #[cfg(feature = "tracing")]
+            span: sentinel.writer_span(),
+            file,
+            sentinel,
+            position,
+            ranges: WrittenRanges::new(position),
+            checksum: None,
+            digest: None,
+            max_lag: options.max_lag,
+            rate_limit: options
+                .rate_limit
+                .map(|limit| TokenBucket::new(limit, Instant::now())),
+            rate_delay: None,
+            shutdown: Shutdown::default(),
+            tail_cache: false,
+            auto_sync: options.auto_sync,
+            uncommitted_since: None,
+            auto_commit_pending: false,
+            #[cfg(feature = "futures-sink")]
+            sink_item: None,
+        };
+
+        match options.tail_cache {
+            Some(capacity) => writer.with_tail_cache(capacity),
+            None => writer,
+        }
+    }
 }
 
-impl<T> SharedFileWriter<T> {
-    pub(crate) fn new(file: T, sentinel: Arc<Sentinel<T>>) -> Self {
-        Self { file, sentinel }
+impl<T, W> SharedFileWriter<T, W> {
+    /// Enables tracking a CRC-32 checksum of the written data, as required by
+    /// [`complete_with_trailer`](Self::complete_with_trailer).
+    ///
+    /// This must be called before any data is written. The checksum can only be tracked
+    /// for sequentially written data; it is discarded when data is written anywhere but
+    /// at the end of the file, e.g. after seeking.
+    pub fn with_checksum(mut self) -> Self {
+        debug_assert!(
+            matches!(self.sentinel.state.load(), WriteState::Pending(0, 0)),
+            "The checksum must be tracked before any data is written"
+        );
+        self.checksum = Some(Crc32::new());
+        self
+    }
+
+    /// Sets the policy for automatically committing written data and waking up readers.
+    ///
+    /// By default, data is only committed when the writer is flushed or synced.
+    pub fn with_auto_sync(mut self, policy: AutoSyncPolicy) -> Self {
+        self.auto_sync = Some(policy);
+        self
+    }
+
+    /// Bounds the number of bytes the writer may be ahead of the slowest active reader.
+    ///
+    /// Once the writer is `max_lag` or more bytes ahead, writes return [`Poll::Pending`]
+    /// until readers catch up, giving channel-like backpressure. Data not yet committed is
+    /// committed before waiting, since readers can only read committed data. Without active
+    /// readers, writes are not limited. Note that a single write may exceed the limit.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `max_lag` is zero.
+    pub fn with_max_lag(mut self, max_lag: u64) -> Self {
+        assert_ne!(max_lag, 0, "The maximum lag must not be zero");
+        if self.max_lag.replace(max_lag).is_none() {
+            self.sentinel.add_progress_watcher();
+        }
+        self
+    }
+
+    /// Limits the rate at which data is written, e.g. so that background ingestion
+    /// does not saturate the disk bandwidth needed by readers.
+    ///
+    /// See [`RateLimit`] for details.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> Self {
+        self.rate_limit = Some(TokenBucket::new(limit, Instant::now()));
+        self
+    }
+
+    /// Enables retaining up to `capacity` bytes of the most recently written data in memory.
+    ///
+    /// Readers that opted in using [`SharedFileReader::with_tail_cache`](crate::SharedFileReader::with_tail_cache)
+    /// are served committed data from this cache instead of reading it back from disk,
+    /// as long as they closely follow the writer. Only data written after this call is cached.
+    pub fn with_tail_cache(mut self, capacity: usize) -> Self {
+        let offset = match self.sentinel.state.load() {
+            WriteState::Pending(_committed, written) => written,
+            WriteState::Completed(count) => count,
+            WriteState::Failed => 0,
+        };
+        self.sentinel
+            .tail_cache
+            .lock()
+            .expect("failed to lock tail cache for writing")
+            .enable(capacity, offset);
+        self.tail_cache = capacity > 0;
+        self
+    }
+
+    /// Gets the span the writer's work is recorded in.
+    #[cfg_attr(docsrs, doc(cfg(feature = "tracing")))]
+    #[cfg(feature = "tracing")]
+    pub fn span(&self) -> &tracing::Span {
+        &self.span
+    }
+
+    /// Sets the digest to track for all bytes written.
+    pub(crate) fn set_digest(&mut self, digest: Box<dyn Digest>) {
+        self.digest = Some(digest);
+    }
+
+    /// Gets the sentinel of the file.
+    pub(crate) fn sentinel(&self) -> &Sentinel<T> {
+        &self.sentinel
+    }
+
+    /// Gets the shared sentinel of the file.
+    pub(crate) fn sentinel_arc(&self) -> &Arc<Sentinel<T>> {
+        &self.sentinel
+    }
+
+    /// Gets the current write position in the file.
+    pub(crate) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Sets the behavior of shutting down the writer.
+    pub(crate) fn set_shutdown(&mut self, shutdown: Shutdown) {
+        self.shutdown = shutdown;
+    }
+
+    /// Marks the file as failed due to the specified error and wakes up all readers.
+    pub(crate) fn fail(&self, error: &Error) {
+        self.sentinel.fail_with_error(error);
+    }
+
+    /// Gets the remainder of the item sent to the sink that was not yet written.
+    #[cfg(feature = "futures-sink")]
+    pub(crate) fn sink_item(self: Pin<&mut Self>) -> &mut Option<bytes::Bytes> {
+        self.project().sink_item
     }
 
     /// Gets the file path.
     pub fn file_path(&self) -> &PathBuf
     where
-        T: FilePath,
+        W: FilePath,
     {
         self.file.file_path()
     }
 
+    /// Preallocates the file to `len` bytes and records `len` as the expected total size.
+    ///
+    /// Readers then report the expected total via [`FileSize::AtLeastWithTotal`](crate::FileSize::AtLeastWithTotal), e.g. to send
+    /// a `Content-Length` header while the file is still being written. The expected total
+    /// is informational; the file size after completion is determined by the data written.
+    pub async fn set_expected_len(&mut self, len: u64) -> Result<(), W::SetLenError>
+    where
+        W: SetLen,
+    {
+        // Never cut off data that was already written.
+        if let WriteState::Pending(_committed, written) = self.sentinel.state.load() {
+            if len > written {
+                self.file.set_len(len).await?;
+            }
+        }
+        self.sentinel.expected_len.store(Some(len));
+        self.sentinel.wake_readers();
+        Ok(())
+    }
+
     /// Synchronizes data and metadata with the disk buffer.
-    pub async fn sync_all(&self) -> Result<(), T::SyncError>
+    pub async fn sync_all(&self) -> Result<(), W::SyncError>
     where
-        T: SharedFileType,
+        W: SharedFileType,
     {
-        self.file.sync_all().await?;
-        Self::sync_committed_and_written(&self.sentinel);
+        let started = Instant::now();
+        let sync = self.file.sync_all();
+        #[cfg(feature = "tracing")]
+        let sync = tracing::Instrument::instrument(sync, self.span.clone());
+        sync.await?;
+        let elapsed = started.elapsed();
+        self.sentinel.stats.record_sync(elapsed);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(parent: &self.span, ?elapsed, "synced file data and metadata");
+        Self::sync_committed_and_written(&self.sentinel, self.ranges.contiguous());
         self.sentinel.wake_readers();
         Ok(())
     }
 
     /// Synchronizes data with the disk buffer.
-    pub async fn sync_data(&self) -> Result<(), T::SyncError>
+    pub async fn sync_data(&self) -> Result<(), W::SyncError>
     where
-        T: SharedFileType,
+        W: SharedFileType,
     {
-        self.file.sync_data().await?;
-        Self::sync_committed_and_written(&self.sentinel);
+        let started = Instant::now();
+        let sync = self.file.sync_data();
+        #[cfg(feature = "tracing")]
+        let sync = tracing::Instrument::instrument(sync, self.span.clone());
+        sync.await?;
+        let elapsed = started.elapsed();
+        self.sentinel.stats.record_sync(elapsed);
+        #[cfg(feature = "tracing")]
+        tracing::debug!(parent: &self.span, ?elapsed, "synced file data");
+        Self::sync_committed_and_written(&self.sentinel, self.ranges.contiguous());
         self.sentinel.wake_readers();
         Ok(())
     }
@@ -69,27 +303,88 @@ impl<T> SharedFileWriter<T> {
     /// to sync the file to disk.
     pub async fn complete(self) -> Result<(), CompleteWritingError>
     where
-        T: SharedFileType,
+        W: SharedFileType + AsyncWrite,
+        W::SyncError: std::error::Error + Send + Sync + 'static,
     {
-        if self.sync_all().await.is_err() {
-            return Err(CompleteWritingError::SyncError);
+        pin!(self).complete_pinned().await
+    }
+
+    /// Completes the writing operation of a pinned writer, see [`complete`](Self::complete).
+    pub(crate) async fn complete_pinned(
+        mut self: Pin<&mut Self>,
+    ) -> Result<(), CompleteWritingError>
+    where
+        W: SharedFileType + AsyncWrite,
+        W::SyncError: std::error::Error + Send + Sync + 'static,
+    {
+        // Data may be buffered by the file itself, e.g. when compressing it.
+        poll_fn(|cx| self.as_mut().project().file.poll_flush(cx)).await?;
+        if let Err(e) = self.sync_all().await {
+            return Err(CompleteWritingError::SyncError(Box::new(e)));
         }
-        self.complete_no_sync()
+        self.finalize_state()
+    }
+
+    /// Completes the writing operation after appending a checksum trailer to the file.
+    ///
+    /// The trailer of [`TRAILER_LEN`](crate::TRAILER_LEN) bytes contains the length and
+    /// CRC-32 checksum of the written data. It is stored after the logical end of the
+    /// file and is therefore not visible to readers, unless they opt into verifying it
+    /// using [`SharedFileReader::verify_trailer`](crate::SharedFileReader::verify_trailer).
+    ///
+    /// Requires the checksum to be tracked using [`with_checksum`](Self::with_checksum).
+    pub async fn complete_with_trailer(mut self) -> Result<(), CompleteWritingError>
+    where
+        W: SharedFileType + AsyncWrite + Unpin,
+        W::SyncError: std::error::Error + Send + Sync + 'static,
+    {
+        let Some(checksum) = self.checksum else {
+            return Err(CompleteWritingError::ChecksumNotTracked);
+        };
+
+        let written = match self.sentinel.state.load() {
+            WriteState::Pending(_committed, written) => written,
+            WriteState::Completed(count) => count,
+            WriteState::Failed => return Err(CompleteWritingError::FileWritingFailed),
+        };
+        if self.position != written {
+            return Err(CompleteWritingError::Io(Error::new(
+                ErrorKind::InvalidInput,
+                "The writer is not positioned at the end of the file",
+            )));
+        }
+
+        // Writing to the file directly keeps the trailer out of the byte count.
+        let trailer = encode_trailer(written, checksum.value());
+        self.file.write_all(&trailer).await?;
+        self.file.flush().await?;
+        self.complete().await
     }
 
     /// Completes the writing operation.
     ///
     /// If you need to sync the file to disk, consider calling
-    /// [`complete`](Self::complete) instead.
+    /// [`complete`](Self::complete) instead. Fails with
+    /// [`CompleteWritingError::UncommittedData`] and marks the file as failed
+    /// if data was written but not committed, e.g. by flushing.
     pub fn complete_no_sync(self) -> Result<(), CompleteWritingError> {
-        self.finalize_state()
+        pin!(self).finalize_state()
     }
 
-    /// Synchronizes the number of written bytes with the number of committed bytes.
-    fn sync_committed_and_written(sentinel: &Arc<Sentinel<T>>) {
+    /// Synchronizes the number of committed bytes with the number of written bytes.
+    ///
+    /// Only the `contiguous` prefix of the file without holes is committed.
+    fn sync_committed_and_written(sentinel: &Arc<Sentinel<T>>, contiguous: u64) {
         match sentinel.state.load() {
             WriteState::Pending(_committed, written) => {
-                sentinel.state.store(WriteState::Pending(written, written));
+                sentinel
+                    .tail_cache
+                    .lock()
+                    .expect("failed to lock tail cache for writing")
+                    .commit();
+                sentinel
+                    .state
+                    .store(WriteState::Pending(contiguous.min(written), written));
             }
             WriteState::Completed(_) => {}
             WriteState::Failed => {}
@@ -99,32 +394,55 @@ impl<T> SharedFileWriter<T> {
     /// Sets the state to finalized.
     ///
     /// See also [`update_state`](Self::update_state) for increasing the byte count.
-    fn finalize_state(&self) -> Result<(), CompleteWritingError> {
-        let result = match self.sentinel.state.load() {
+    fn finalize_state(self: Pin<&mut Self>) -> Result<(), CompleteWritingError> {
+        let this = self.project();
+        let result = match this.sentinel.state.load() {
+            WriteState::Pending(committed, written)
+                if committed < this.ranges.contiguous().min(written) =>
+            {
+                // Readers must not mistake the committed prefix for the complete file.
+                this.sentinel.fail_with(FailureReason::UncommittedData);
+                return Err(CompleteWritingError::UncommittedData);
+            }
             WriteState::Pending(_committed, written) => {
-                assert_eq!(_committed, written, "The number of committed bytes is less than the number of written bytes - call sync before dropping");
-                self.sentinel.state.store(WriteState::Completed(written));
+                Self::store_completed(this.sentinel, this.digest, written);
                 Ok(())
             }
             WriteState::Completed(_) => Ok(()),
             WriteState::Failed => Err(CompleteWritingError::FileWritingFailed),
         };
 
-        self.sentinel.wake_readers();
+        this.sentinel.wake_readers();
         result
     }
 
-    /// Updates the internal byte count with the specified number of bytes written.
-    /// Will produce an error if the update failed.
+    /// Sets the state to completed with the specified number of bytes, publishing the
+    /// finalized digest, if tracked.
+    fn store_completed(sentinel: &Sentinel<T>, digest: &mut Option<Box<dyn Digest>>, written: u64) {
+        if let Some(digest) = digest.take() {
+            sentinel.set_digest(digest.finalize());
+        }
+        sentinel.state.store(WriteState::Completed(written));
+    }
+
+    /// Updates the internal byte count with the specified number of bytes written at
+    /// the specified offset. Will produce an error if the update failed.
+    ///
+    /// The byte count reflects the highest written offset, such that overwriting
+    /// existing data does not increase it.
     ///
     /// ## Returns
     /// Returns the number of bytes written in total.
     ///
     /// See also [`finalize_state`](Self::finalize_state) for finalizing the write.
-    fn update_state(state: &AtomicCell<WriteState>, written: usize) -> Result<usize, Error> {
+    fn update_state(
+        state: &AtomicCell<WriteState>,
+        offset: u64,
+        written: u64,
+    ) -> Result<u64, Error> {
         match state.load() {
             WriteState::Pending(committed, previously_written) => {
-                let count = previously_written + written;
+                let count = previously_written.max(offset + written);
                 state.store(WriteState::Pending(committed, count));
                 Ok(count)
             }
@@ -140,90 +458,364 @@ impl<T> SharedFileWriter<T> {
         }
     }
 
-    /// Processes a [`Poll`] result from a write operation.
+    /// Processes a [`Poll`] result from a write operation at the specified position.
     ///
-    /// This will update the internal byte count and produce an error
-    /// if the update failed.
+    /// This will update the internal byte count and the position, and produce
+    /// an error if the update failed.
     fn handle_poll_write_result(
         sentinel: &Sentinel<T>,
+        position: &mut u64,
+        ranges: &mut WrittenRanges,
         poll: Poll<Result<usize, Error>>,
     ) -> Poll<Result<usize, Error>> {
         match poll {
             Poll::Ready(result) => match result {
-                Ok(written) => match Self::update_state(&sentinel.state, written) {
-                    Ok(_) => Poll::Ready(Ok(written)),
+                Ok(written) => match Self::update_state(&sentinel.state, *position, written as u64)
+                {
+                    Ok(_) => {
+                        sentinel.stats.record_write(written);
+                        sentinel.last_progress.store(Instant::now());
+                        ranges.insert(*position, *position + written as u64);
+                        *position += written as u64;
+                        Poll::Ready(Ok(written))
+                    }
                     Err(e) => Poll::Ready(Err(e)),
                 },
                 Err(e) => {
-                    sentinel.state.store(WriteState::Failed);
+                    sentinel.fail_with_error(&e);
+                    Poll::Ready(Err(e))
+                }
+            },
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    W: AsyncWrite,
+{
+    /// Flushes the file and commits all written data, waking up readers.
+    fn poll_commit(
+        file: Pin<&mut W>,
+        sentinel: &Arc<Sentinel<T>>,
+        ranges: &WrittenRanges,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        match file.poll_flush(cx) {
+            Poll::Ready(result) => match result {
+                Ok(()) => {
+                    Self::sync_committed_and_written(sentinel, ranges.contiguous());
+                    sentinel.stats.record_commit();
                     sentinel.wake_readers();
+                    Poll::Ready(Ok(()))
+                }
+                Err(e) => {
+                    sentinel.fail_with_error(&e);
                     Poll::Ready(Err(e))
                 }
             },
             Poll::Pending => Poll::Pending,
         }
     }
+
+    /// Completes a previously started automatic commit, if any.
+    fn poll_pending_auto_commit(
+        this: &mut SharedFileWriterProjection<'_, T, W>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        if !*this.auto_commit_pending {
+            return Poll::Ready(Ok(()));
+        }
+
+        let result = ready!(Self::poll_commit(
+            this.file.as_mut(),
+            this.sentinel,
+            this.ranges,
+            cx
+        ));
+        *this.auto_commit_pending = false;
+        *this.uncommitted_since = None;
+        Poll::Ready(result)
+    }
+
+    /// Waits until the slowest reader is less than the maximum lag behind, if bounded.
+    fn poll_backpressure(
+        this: &mut SharedFileWriterProjection<'_, T, W>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        let Some(max_lag) = *this.max_lag else {
+            return Poll::Ready(Ok(()));
+        };
+        let WriteState::Pending(committed, written) = this.sentinel.state.load() else {
+            return Poll::Ready(Ok(()));
+        };
+        let is_behind = |sentinel: &Sentinel<T>| {
+            sentinel
+                .slowest_reader_position()
+                .map_or(false, |slowest| written.saturating_sub(slowest) >= max_lag)
+        };
+        if !is_behind(this.sentinel) {
+            return Poll::Ready(Ok(()));
+        }
+
+        // Readers can only catch up with committed data.
+        if committed < this.ranges.contiguous().min(written) {
+            ready!(Self::poll_commit(
+                this.file.as_mut(),
+                this.sentinel,
+                this.ranges,
+                cx
+            ))?;
+            *this.auto_commit_pending = false;
+            *this.uncommitted_since = None;
+        }
+
+        this.sentinel.register_writer_waker(cx.waker());
+
+        // Readers may have progressed before the waker was registered.
+        if is_behind(this.sentinel) {
+            Poll::Pending
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Waits until the rate limit allows writing, if any, and determines how many
+    /// of `len` bytes may be written.
+    fn poll_rate_limit(
+        this: &mut SharedFileWriterProjection<'_, T, W>,
+        cx: &mut Context<'_>,
+        len: usize,
+    ) -> Poll<usize> {
+        let Some(bucket) = this.rate_limit.as_mut() else {
+            return Poll::Ready(len);
+        };
+
+        loop {
+            if let Some(delay) = this.rate_delay.as_mut() {
+                ready!(delay.as_mut().poll(cx));
+                *this.rate_delay = None;
+            }
+
+            match bucket.poll_acquire(len, Instant::now()) {
+                Ok(allowed) => return Poll::Ready(allowed),
+                Err(wait) => *this.rate_delay = Some(Box::pin(sleep(wait))),
+            }
+        }
+    }
+
+    /// Records the first `written` bytes of the buffers as written at the current position,
+    /// updating the checksum and the tail cache.
+    fn record_write<'a>(
+        this: &mut SharedFileWriterProjection<'_, T, W>,
+        bufs: impl IntoIterator<Item = &'a [u8]>,
+        written: u64,
+    ) {
+        Self::discard_checksum_unless_appending(this);
+
+        let mut cache = if *this.tail_cache {
+            Some(
+                this.sentinel
+                    .tail_cache
+                    .lock()
+                    .expect("failed to lock tail cache for writing"),
+            )
+        } else {
+            None
+        };
+
+        let mut offset = *this.position;
+        let mut remaining = written;
+        for buf in bufs {
+            if remaining == 0 {
+                break;
+            }
+
+            let data = &buf[..remaining.min(buf.len() as u64) as usize];
+            if let Some(checksum) = this.checksum.as_mut() {
+                checksum.update(data);
+            }
+            if let Some(digest) = this.digest.as_mut() {
+                digest.update(data);
+            }
+            if let Some(cache) = cache.as_mut() {
+                cache.write(offset, data);
+            }
+            offset += data.len() as u64;
+            remaining -= data.len() as u64;
+        }
+    }
+
+    /// Records that a hole of `len` bytes was declared at the write position.
+    ///
+    /// The zeros of the hole are only processed if a checksum or digest is tracked;
+    /// the tail cache continues after the hole, which is read from the file instead.
+    fn record_hole(this: &mut SharedFileWriterProjection<'_, T, W>, len: u64) {
+        Self::discard_checksum_unless_appending(this);
+
+        if this.checksum.is_some() || this.digest.is_some() {
+            let zeros = [0; 4096];
+            let mut remaining = len;
+            while remaining > 0 {
+                let data = &zeros[..remaining.min(zeros.len() as u64) as usize];
+                if let Some(checksum) = this.checksum.as_mut() {
+                    checksum.update(data);
+                }
+                if let Some(digest) = this.digest.as_mut() {
+                    digest.update(data);
+                }
+                remaining -= data.len() as u64;
+            }
+        }
+
+        if *this.tail_cache {
+            this.sentinel
+                .tail_cache
+                .lock()
+                .expect("failed to lock tail cache for writing")
+                .truncate(*this.position + len);
+        }
+    }
+
+    /// Discards the checksum and digest unless data is written at the end of the file.
+    fn discard_checksum_unless_appending(this: &mut SharedFileWriterProjection<'_, T, W>) {
+        // The checksum only covers sequentially written data.
+        let appending = matches!(
+            this.sentinel.state.load(),
+            WriteState::Pending(_committed, end) if end == *this.position
+        );
+        if !appending {
+            *this.checksum = None;
+            *this.digest = None;
+        }
+    }
+
+    /// Starts an automatic commit after `written` bytes were written, if due according to the policy.
+    ///
+    /// Errors are not reported here since the data was already written; they
+    /// fail the file and are therefore reported by the next operation.
+    fn auto_commit(
+        this: &mut SharedFileWriterProjection<'_, T, W>,
+        cx: &mut Context<'_>,
+        written: usize,
+    ) {
+        let Some(policy) = this.auto_sync else {
+            return;
+        };
+        let WriteState::Pending(committed, total) = this.sentinel.state.load() else {
+            return;
+        };
+
+        // If only the data just written is uncommitted, a commit happened in the meantime.
+        let uncommitted = total - committed;
+        if uncommitted <= written as u64 || this.uncommitted_since.is_none() {
+            *this.uncommitted_since = Some(Instant::now());
+        }
+
+        if !policy.is_due(uncommitted, *this.uncommitted_since) {
+            return;
+        }
+
+        *this.auto_commit_pending = true;
+        let _ = Self::poll_pending_auto_commit(this, cx);
+    }
 }
 
 #[pinned_drop]
-impl<T> PinnedDrop for SharedFileWriter<T> {
+impl<T, W> PinnedDrop for SharedFileWriter<T, W> {
     fn drop(mut self: Pin<&mut Self>) {
-        self.finalize_state().ok();
+        self.as_mut().finalize_state().ok();
+        self.sentinel.active_writers.fetch_sub(1, Ordering::AcqRel);
+        if self.max_lag.is_some() {
+            self.sentinel.remove_progress_watcher();
+        }
     }
 }
 
-impl<T> AsyncWrite for SharedFileWriter<T>
+impl<T, W> AsyncWrite for SharedFileWriter<T, W>
 where
-    T: AsyncWrite,
+    W: AsyncWrite,
 {
     fn poll_write(
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
         buf: &[u8],
     ) -> Poll<io::Result<usize>> {
-        let this = self.project();
-        let poll = this.file.poll_write(cx, buf);
-        Self::handle_poll_write_result(this.sentinel, poll)
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
+        let mut this = self.project();
+        ready!(Self::poll_pending_auto_commit(&mut this, cx))?;
+        ready!(Self::poll_backpressure(&mut this, cx))?;
+        let len = ready!(Self::poll_rate_limit(&mut this, cx, buf.len()));
+        let buf = &buf[..len];
+        let poll = this.file.as_mut().poll_write(cx, buf);
+        if let Poll::Ready(Ok(written)) = poll {
+            Self::record_write(&mut this, [buf], written as u64);
+            if let Some(bucket) = this.rate_limit.as_mut() {
+                bucket.consume(written);
+            }
+        }
+        let poll = Self::handle_poll_write_result(this.sentinel, this.position, this.ranges, poll);
+        if let Poll::Ready(Ok(written)) = poll {
+            Self::auto_commit(&mut this, cx, written);
+        }
+        poll
     }
 
     fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
         let this = self.project();
-        match this.file.poll_flush(cx) {
-            Poll::Ready(result) => match result {
-                Ok(()) => {
-                    Self::sync_committed_and_written(this.sentinel);
-                    this.sentinel.wake_readers();
-                    Poll::Ready(Ok(()))
-                }
-                Err(e) => {
-                    this.sentinel.state.store(WriteState::Failed);
-                    this.sentinel.wake_readers();
-                    Poll::Ready(Err(e))
-                }
-            },
-            Poll::Pending => Poll::Pending,
-        }
+        let result = ready!(Self::poll_commit(this.file, this.sentinel, this.ranges, cx));
+        *this.auto_commit_pending = false;
+        *this.uncommitted_since = None;
+        Poll::Ready(result)
     }
 
     fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
-        let this = self.project();
-        match this.file.poll_shutdown(cx) {
-            Poll::Ready(result) => match result {
-                Ok(()) => {
-                    if let WriteState::Pending(_committed, written) = this.sentinel.state.load() {
-                        debug_assert_eq!(_committed, written);
-                        this.sentinel.state.store(WriteState::Completed(written));
-                    }
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
+        let mut this = self.project();
+        ready!(Self::poll_pending_auto_commit(&mut this, cx))?;
 
-                    Poll::Ready(Ok(()))
-                }
-                Err(e) => {
-                    this.sentinel.state.store(WriteState::Failed);
-                    Poll::Ready(Err(e))
+        if this.shutdown.behavior == ShutdownBehavior::FailIfUncommitted {
+            if let WriteState::Pending(committed, written) = this.sentinel.state.load() {
+                if committed < this.ranges.contiguous().min(written) {
+                    this.sentinel.fail_with(FailureReason::UncommittedData);
+                    return Poll::Ready(Err(Error::new(
+                        ErrorKind::Other,
+                        CompleteWritingError::UncommittedData,
+                    )));
                 }
-            },
-            Poll::Pending => Poll::Pending,
+            }
+        }
+
+        let pending = &mut this.shutdown.pending;
+        if pending.is_none() {
+            if let Err(e) = ready!(this.file.poll_shutdown(cx)) {
+                this.sentinel.fail_with_error(&e);
+                return Poll::Ready(Err(e));
+            }
+            *pending = this.shutdown.sync.as_ref().map(|sync| sync());
+        }
+
+        if let Some(sync) = pending.as_mut() {
+            let result = ready!(Pin::new(sync).poll(cx)).unwrap_or_else(|e| Err(e.into()));
+            *pending = None;
+            if let Err(e) = result {
+                this.sentinel.fail_with_error(&e);
+                return Poll::Ready(Err(e));
+            }
+        }
+
+        // Shutting down flushed the file, so all written data is committed.
+        if let WriteState::Pending(_committed, written) = this.sentinel.state.load() {
+            Self::store_completed(this.sentinel, this.digest, written);
         }
+
+        this.sentinel.wake_readers();
+        Poll::Ready(Ok(()))
     }
 
     fn poll_write_vectored(
@@ -231,12 +823,184 @@ where
         cx: &mut Context<'_>,
         bufs: &[IoSlice<'_>],
     ) -> Poll<Result<usize, Error>> {
-        let this = self.project();
-        let poll = this.file.poll_write_vectored(cx, bufs);
-        Self::handle_poll_write_result(this.sentinel, poll)
+        // Rate limited writes are shortened to the available budget.
+        if self.rate_limit.is_some() {
+            let buf = bufs
+                .iter()
+                .find(|buf| !buf.is_empty())
+                .map_or(&[][..], |buf| &buf[..]);
+            return self.poll_write(cx, buf);
+        }
+
+        #[cfg(feature = "tracing")]
+        let _entered = self.span.clone().entered();
+        let mut this = self.project();
+        ready!(Self::poll_pending_auto_commit(&mut this, cx))?;
+        ready!(Self::poll_backpressure(&mut this, cx))?;
+        let poll = this.file.as_mut().poll_write_vectored(cx, bufs);
+        if let Poll::Ready(Ok(written)) = poll {
+            Self::record_write(&mut this, bufs.iter().map(|buf| &buf[..]), written as u64);
+        }
+        let poll = Self::handle_poll_write_result(this.sentinel, this.position, this.ranges, poll);
+        if let Poll::Ready(Ok(written)) = poll {
+            Self::auto_commit(&mut this, cx, written);
+        }
+        poll
     }
 
     fn is_write_vectored(&self) -> bool {
-        self.file.is_write_vectored()
+        self.rate_limit.is_none() && self.file.is_write_vectored()
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    /// Commits all written data, waking up readers, without syncing the file to disk.
+    ///
+    /// This only makes the data visible to readers, which read it from the page cache;
+    /// use [`sync_data`](Self::sync_data) if the data must also be durable.
+    /// This is equivalent to flushing the writer.
+    ///
+    /// Returns the number of committed bytes.
+    pub async fn commit(&mut self) -> io::Result<u64> {
+        self.flush().await?;
+        Ok(self.committed())
+    }
+
+    /// Commits written data up to the specified offset, waking up readers, without
+    /// syncing the file to disk.
+    ///
+    /// The committed watermark never exceeds the contiguously written prefix of the file
+    /// and never moves backwards, so committing to an offset below it has no effect.
+    /// Data after the offset remains uncommitted; see [`commit`](Self::commit).
+    ///
+    /// Returns the number of committed bytes.
+    ///
+    /// ## Arguments
+    ///
+    /// * `offset` - The offset up to which to commit.
+    pub async fn commit_to(&mut self, offset: u64) -> io::Result<u64> {
+        if let Err(e) = self.file.flush().await {
+            self.fail(&e);
+            return Err(e);
+        }
+
+        if let WriteState::Pending(committed, written) = self.sentinel.state.load() {
+            let limit = offset.min(self.ranges.contiguous()).min(written);
+            if limit > committed {
+                self.sentinel
+                    .tail_cache
+                    .lock()
+                    .expect("failed to lock tail cache for writing")
+                    .commit();
+                self.sentinel
+                    .state
+                    .store(WriteState::Pending(limit, written));
+                self.sentinel.stats.record_commit();
+                self.sentinel.wake_readers();
+            }
+        }
+
+        Ok(self.committed())
+    }
+
+    /// Gets the number of committed bytes.
+    fn committed(&self) -> u64 {
+        match self.sentinel.state.load() {
+            WriteState::Pending(committed, _written) => committed,
+            WriteState::Completed(count) => count,
+            WriteState::Failed => 0,
+        }
+    }
+}
+
+impl<T, W> SharedFileWriter<T, W>
+where
+    W: AsyncWrite + AsyncSeek + Unpin,
+{
+    /// Writes the entire buffer at the specified offset, leaving the write position unchanged.
+    ///
+    /// Writes may happen out of order, e.g. when filling a file from multiple downloaded
+    /// chunks. Readers only observe the contiguously written prefix of the file; data after
+    /// a hole is committed once the hole is filled. Holes remaining when the writer completes
+    /// are read as zeros.
+    ///
+    /// ## Arguments
+    ///
+    /// * `offset` - The offset in the file at which to write.
+    /// * `buf` - The data to write.
+    pub async fn write_at(&mut self, offset: u64, buf: &[u8]) -> io::Result<()> {
+        let position = self.position;
+        self.seek(SeekFrom::Start(offset)).await?;
+        self.write_all(buf).await?;
+        self.seek(SeekFrom::Start(position)).await?;
+        Ok(())
+    }
+
+    /// Declares the next `len` bytes a hole that will never contain data and moves the
+    /// write position past it.
+    ///
+    /// The hole is not written; if it extends the file, the file is extended by setting its
+    /// length, creating a sparse region on file systems supporting it. The hole reads as zeros
+    /// and is committed like written data, so readers do not wait for it. Readers may skip it
+    /// using [`SharedFileReader::skip_hole`](crate::SharedFileReader::skip_hole), and the holes
+    /// of a file are available via [`SharedFile::holes`](crate::SharedFile::holes).
+    ///
+    /// <div class="warning">Writing into a declared hole afterwards is not supported.</div>
+    pub async fn write_hole(&mut self, len: u64) -> io::Result<()>
+    where
+        W: SetLen,
+        W::SetLenError: std::error::Error + Send + Sync + 'static,
+    {
+        let start = self.position;
+        let end = start + len;
+        match self.sentinel.state.load() {
+            WriteState::Pending(_committed, written) if end > written => {
+                self.file
+                    .set_len(end)
+                    .await
+                    .map_err(|e| Error::new(ErrorKind::Other, e))?;
+            }
+            WriteState::Pending(_committed, _written) => {}
+            WriteState::Completed(_) => {
+                return Err(Error::new(ErrorKind::BrokenPipe, WriteError::FileClosed))
+            }
+            WriteState::Failed => return Err(Error::from(ErrorKind::Other)),
+        }
+
+        {
+            let mut this = Pin::new(&mut *self).project();
+            Self::record_hole(&mut this, len);
+            Self::update_state(&this.sentinel.state, start, len)?;
+            this.ranges.insert(start, end);
+            this.sentinel.last_progress.store(Instant::now());
+        }
+
+        self.sentinel.add_hole(start, end);
+        self.seek(SeekFrom::Start(end)).await?;
+        Ok(())
+    }
+}
+
+impl<T, W> AsyncSeek for SharedFileWriter<T, W>
+where
+    W: AsyncSeek,
+{
+    /// Seeks the file, e.g. to backpatch previously written data.
+    ///
+    /// The number of bytes written reflects the highest written offset. Note that readers
+    /// that already read overwritten data do not observe the change, and that seeking
+    /// past the end of the file does not increase the file size until data is written.
+    fn start_seek(self: Pin<&mut Self>, position: SeekFrom) -> io::Result<()> {
+        self.project().file.start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        let this = self.project();
+        let position = ready!(this.file.poll_complete(cx))?;
+        *this.position = position;
+        Poll::Ready(Ok(position))
     }
 }
@@ -0,0 +1,34 @@
+//! This test aborts writing a file while a reader waits for data.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn aborting_fails_waiting_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move { reader.read_to_end(&mut Vec::new()).await });
+
+    writer.abort("upload cancelled");
+
+    let error = read
+        .await
+        .expect("failed to join reader")
+        .expect_err("reading an aborted file succeeded");
+    match error.get_ref().and_then(|e| e.downcast_ref()) {
+        Some(ReadError::Aborted(reason)) => assert_eq!(reason.to_string(), "upload cancelled"),
+        _ => panic!("unexpected error: {error}"),
+    }
+}
@@ -0,0 +1,58 @@
+//! This test extends a completed file in a second writing session.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn completed_file_can_be_appended_to() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello");
+
+    let mut writer = file
+        .append_writer()
+        .await
+        .expect("failed to create append writer");
+
+    // The reader at the end of the file now waits for the appended data.
+    let read = tokio::spawn(async move {
+        let mut buf = [0; 6];
+        reader
+            .read_exact(&mut buf)
+            .await
+            .expect("failed to read appended data");
+        buf
+    });
+
+    writer
+        .write_all(b" world")
+        .await
+        .expect("failed to append data");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(&read.await.expect("failed to join reader"), b" world");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
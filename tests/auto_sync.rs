@@ -0,0 +1,39 @@
+//! This test commits written data automatically without explicitly flushing the writer.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{AutoSyncPolicy, SharedTemporaryFile};
+
+/// The size of each chunk.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn writes_are_committed_automatically() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_auto_sync(AutoSyncPolicy::every_bytes(CHUNK_SIZE as u64));
+    for chunk in 0..3 {
+        writer
+            .write_all(&[chunk; CHUNK_SIZE])
+            .await
+            .expect("failed to write");
+    }
+
+    // Commits are completed by the next write at the latest.
+    let committed = reader.file_size().minimum_size().expect("writing failed");
+    assert!(committed >= 2 * CHUNK_SIZE as u64);
+
+    let mut data = vec![0; 2 * CHUNK_SIZE];
+    reader.read_exact(&mut data).await.expect("failed to read");
+    assert_eq!(&data[..CHUNK_SIZE], &[0; CHUNK_SIZE]);
+    assert_eq!(&data[CHUNK_SIZE..], &[1; CHUNK_SIZE]);
+
+    writer.complete().await.expect("failed to complete");
+}
@@ -0,0 +1,46 @@
+//! This test writes a file from a synchronous producer while it is read concurrently.
+
+use std::io::Write;
+use tokio::io::AsyncReadExt;
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 100_000;
+
+/// The size of each write.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn blocking_writer_feeds_async_reader() {
+    let data: Vec<u8> = (0..NUM_BYTES).map(|i| (i % 251) as u8).collect();
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .into_blocking();
+
+    let producer = {
+        let data = data.clone();
+        tokio::task::spawn_blocking(move || {
+            for chunk in data.chunks(CHUNK_SIZE) {
+                writer.write_all(chunk).expect("failed to write");
+                writer.flush().expect("failed to flush");
+            }
+            writer.complete().expect("failed to complete");
+        })
+    };
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    producer.await.expect("failed to join producer");
+    assert_eq!(buf, data);
+}
@@ -0,0 +1,84 @@
+//! This test writes many small records through a buffered writer while reading them back.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+/// The number of records to write.
+const NUM_RECORDS: usize = 10_000;
+
+/// The capacity of the write buffer.
+const CAPACITY: usize = 4_096;
+
+#[tokio::test]
+async fn buffered_writes_are_committed() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await.expect("failed to read");
+        data
+    });
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .buffered(CAPACITY);
+    let mut expected = Vec::new();
+    for record in 0..NUM_RECORDS {
+        let record = (record as u32).to_le_bytes();
+        writer.write_all(&record).await.expect("failed to write");
+        expected.extend_from_slice(&record);
+
+        // Data is committed whenever the buffer is full.
+        assert!(writer.buffer().len() <= CAPACITY);
+    }
+
+    // A large write bypasses the buffer.
+    let large = vec![0xAB; 2 * CAPACITY];
+    writer.write_all(&large).await.expect("failed to write");
+    expected.extend_from_slice(&large);
+
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(read.await.expect("failed to join"), expected);
+}
+
+#[tokio::test]
+async fn dropping_with_buffered_data_fails_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .buffered(CAPACITY);
+    writer
+        .write_all(b"committed")
+        .await
+        .expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer
+        .write_all(b"buffered")
+        .await
+        .expect("failed to write");
+    assert_eq!(writer.buffer(), b"buffered");
+    drop(writer);
+
+    let mut data = Vec::new();
+    let error = reader
+        .read_to_end(&mut data)
+        .await
+        .expect_err("reading a truncated file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::UncommittedData)
+    ));
+}
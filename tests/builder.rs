@@ -0,0 +1,43 @@
+//! This test configures a file and its writers using the builder.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::{AutoSyncPolicy, FileSize, RetentionPolicy, SharedTemporaryFile};
+
+#[tokio::test]
+async fn builder_applies_options() {
+    let file = SharedTemporaryFile::builder()
+        .auto_sync(AutoSyncPolicy::every_bytes(4))
+        .expected_len(11)
+        .retention_policy(RetentionPolicy::DeleteOnDrop)
+        .build_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.retention_policy(), RetentionPolicy::DeleteOnDrop);
+
+    let reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::AtLeastWithTotal(0, 11));
+
+    // Writers automatically commit data according to the configured policy.
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer
+        .write_all(b" world")
+        .await
+        .expect("failed to write data");
+
+    // Commits are completed by the next write at the latest.
+    let committed = reader.file_size().minimum_size().expect("writing failed");
+    assert!(committed >= 5);
+
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(reader.file_size(), FileSize::Exactly(11));
+    drop(reader);
+
+    let path = file.file_path().clone();
+    drop(file);
+    assert!(!path.exists());
+}
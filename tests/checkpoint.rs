@@ -0,0 +1,64 @@
+//! This test awaits named checkpoints while the file is written concurrently.
+
+use std::io::ErrorKind;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn checkpoint_is_awaited() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    let waiting = tokio::spawn(async move {
+        let offset = reader
+            .await_checkpoint("header")
+            .await
+            .expect("failed to await checkpoint");
+        let mut header = vec![0; offset as usize];
+        reader
+            .read_exact(&mut header)
+            .await
+            .expect("failed to read header");
+        header
+    });
+
+    writer.write_all(b"header").await.expect("failed to write");
+    let offset = writer
+        .checkpoint("header")
+        .await
+        .expect("failed to publish checkpoint");
+    assert_eq!(offset, 6);
+    assert_eq!(file.checkpoint("header"), Some(6));
+
+    let header = waiting.await.expect("failed to join reader");
+    assert_eq!(header, b"header");
+
+    writer.write_all(b"body").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+}
+
+#[tokio::test]
+async fn missing_checkpoint_is_reported() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"data").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let error = reader
+        .await_checkpoint("index")
+        .await
+        .expect_err("checkpoint was found");
+    assert_eq!(error.kind(), ErrorKind::NotFound);
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::CheckpointNotFound(name)) if name == "index"
+    ));
+}
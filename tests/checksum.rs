@@ -0,0 +1,54 @@
+//! This test verifies the checksums of blocks while reading a file.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::{ChecksummedFile, FsFile, SharedFile};
+
+#[tokio::test]
+async fn corrupt_block_fails_reader() {
+    let path =
+        std::env::temp_dir().join(format!("shared-files-checksum-{}.bin", std::process::id()));
+    let file = FsFile::create(&path).await.expect("failed to create file");
+    let file = SharedFile::from(ChecksummedFile::with_block_size(file, 4));
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    file.reader()
+        .await
+        .expect("failed to create reader")
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+
+    // Flips a bit in the data of the second block ("o"), following the first block of
+    // four bytes and the prefix and checksum of eight bytes.
+    let mut data = tokio::fs::read(&path).await.expect("failed to read file");
+    data[16] ^= 1;
+    tokio::fs::write(&path, &data)
+        .await
+        .expect("failed to write file");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    let error = reader
+        .read_to_end(&mut buf)
+        .await
+        .expect_err("read corrupt block");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::CorruptBlock(1))
+    ));
+    assert_eq!(buf, b"hell");
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
@@ -0,0 +1,77 @@
+//! This test writes a file in small, odd-sized commits while simultaneously
+//! reading it back in aligned chunks from a different thread.
+
+use async_tempfile::TempFile;
+use tokio::io::AsyncWriteExt;
+
+use shared_files::{ChunkedReader, SharedTemporaryFile};
+
+/// The size of each write.
+const WRITE_SIZE: usize = 7;
+
+/// The number of writes.
+const NUM_WRITES: usize = 1_000;
+
+/// The number of bytes written in total.
+const NUM_BYTES: usize = WRITE_SIZE * NUM_WRITES;
+
+/// The target chunk size.
+const CHUNK_SIZE: usize = 1_000;
+
+/// The alignment of the chunks.
+const ALIGNMENT: usize = 512;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn chunked() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let reader = file.reader().await.expect("failed to create reader");
+    let chunks = reader.chunked(CHUNK_SIZE, ALIGNMENT);
+    assert_eq!(chunks.chunk_size(), 1_024);
+
+    let reader_future = tokio::spawn(read_chunks(chunks));
+    let writer_future = tokio::spawn(write(file));
+
+    let (writer_result, reader_result) = tokio::join!(writer_future, reader_future);
+    assert!(writer_result.is_ok());
+    let chunks = reader_result.expect("reader failed");
+
+    // All chunks but the last have the aligned size.
+    let (last, full) = chunks.split_last().expect("no chunks were read");
+    assert!(full.iter().all(|chunk| chunk.len() == 1_024));
+    assert_eq!(last.len(), NUM_BYTES % 1_024);
+
+    // The content is intact.
+    let data = chunks.concat();
+    assert_eq!(data.len(), NUM_BYTES);
+    assert!(data
+        .chunks_exact(WRITE_SIZE)
+        .enumerate()
+        .all(|(i, write)| write.iter().all(|&b| b == i as u8)));
+}
+
+/// Writes small blocks, committing after each one.
+async fn write(file: SharedTemporaryFile) {
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    for i in 0..NUM_WRITES {
+        writer
+            .write_all(&[i as u8; WRITE_SIZE])
+            .await
+            .expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+
+    writer.complete().await.expect("failed to complete write");
+}
+
+/// Reads all chunks (while the writer is still active).
+async fn read_chunks(mut chunks: ChunkedReader<TempFile>) -> Vec<Vec<u8>> {
+    let mut results = Vec::default();
+    while let Some(chunk) = chunks.next_chunk().await.expect("failed to read chunk") {
+        results.push(chunk);
+    }
+    results
+}
@@ -0,0 +1,35 @@
+//! This test shares a file between tasks by cloning its handle.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn clones_share_the_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let clone = file.clone();
+    assert_eq!(clone.file_path(), file.file_path());
+
+    let reading_task = tokio::spawn(async move {
+        let mut reader = clone.reader().await.expect("failed to create reader");
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    drop(file);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let buf = reading_task.await.expect("failed to join task");
+    assert_eq!(buf, b"hello world");
+}
@@ -0,0 +1,66 @@
+//! This test commits written data up to specific offsets without syncing the file.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FileSize, SharedTemporaryFile};
+
+#[tokio::test]
+async fn commit_to_advances_watermark() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    assert_eq!(reader.file_size(), FileSize::AtLeast(0));
+
+    let committed = writer.commit_to(5).await.expect("failed to commit");
+    assert_eq!(committed, 5);
+    assert_eq!(reader.file_size(), FileSize::AtLeast(5));
+
+    let mut buf = [0; 5];
+    reader
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read data");
+    assert_eq!(&buf, b"hello");
+
+    // The watermark never moves backwards.
+    let committed = writer.commit_to(2).await.expect("failed to commit");
+    assert_eq!(committed, 5);
+
+    // The watermark never exceeds the written data.
+    let committed = writer.commit_to(100).await.expect("failed to commit");
+    assert_eq!(committed, 11);
+
+    writer.complete_no_sync().expect("failed to complete");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b" world");
+}
+
+#[tokio::test]
+async fn commit_commits_all_written_data() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    let committed = writer.commit().await.expect("failed to commit");
+    assert_eq!(committed, 11);
+
+    let reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::AtLeast(11));
+    writer.complete_no_sync().expect("failed to complete");
+}
@@ -0,0 +1,61 @@
+//! This test compresses a file while it is read concurrently.
+
+use async_tempfile::TempFile;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{CompressedFile, Compression, FilePath, SharedFile};
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 100_000;
+
+/// The size of each write.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn gzip_compressed_file_is_read() {
+    read_compressed(Compression::Gzip).await;
+}
+
+#[tokio::test]
+async fn zstd_compressed_file_is_read() {
+    read_compressed(Compression::Zstd).await;
+}
+
+/// Writes a compressed file while reading it concurrently.
+async fn read_compressed(compression: Compression) {
+    let data: Vec<u8> = (0..NUM_BYTES).map(|i| (i % 7) as u8).collect();
+
+    let temp_file = TempFile::new().await.expect("failed to create file");
+    let file = SharedFile::from(CompressedFile::new(temp_file, compression));
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    for chunk in data.chunks(CHUNK_SIZE) {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    writer.shutdown().await.expect("failed to shut down");
+
+    assert_eq!(read.await.expect("failed to join reader"), data);
+
+    // Readers see the uncompressed data, while the file on disk is smaller.
+    let size = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .file_size();
+    assert_eq!(size.exact_size(), Some(NUM_BYTES as u64));
+    let raw = tokio::fs::metadata(file.file_path())
+        .await
+        .expect("failed to get metadata");
+    assert!((raw.len() as usize) < NUM_BYTES);
+}
@@ -0,0 +1,94 @@
+//! These tests copy a source into a file while it is being read.
+
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, ReadBuf};
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of bytes to copy.
+const NUM_BYTES: usize = 1_000_000;
+
+/// The number of bytes after which to commit.
+const COMMIT_EVERY: u64 = 100_000;
+
+#[tokio::test]
+async fn copy_from_source() {
+    let data: Vec<u8> = (0..NUM_BYTES).map(|i| (i % 251) as u8).collect();
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).await.expect("failed to read");
+        data
+    });
+
+    let writer = file.writer().await.expect("failed to create writer");
+    let copied = writer
+        .copy_from(&mut data.as_slice(), COMMIT_EVERY)
+        .await
+        .expect("failed to copy");
+    assert_eq!(copied, NUM_BYTES as u64);
+    assert_eq!(read.await.expect("failed to join"), data);
+}
+
+#[tokio::test]
+async fn spool_from_source() {
+    let data: Vec<u8> = (0..NUM_BYTES).map(|i| (i % 251) as u8).collect();
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let spool = file
+        .spool_from(io::Cursor::new(data.clone()), COMMIT_EVERY)
+        .await
+        .expect("failed to create writer");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut read = Vec::new();
+    reader.read_to_end(&mut read).await.expect("failed to read");
+    assert_eq!(read, data);
+
+    let copied = spool
+        .await
+        .expect("failed to join")
+        .expect("failed to copy");
+    assert_eq!(copied, NUM_BYTES as u64);
+}
+
+#[tokio::test]
+async fn failing_source_fails_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let writer = file.writer().await.expect("failed to create writer");
+    writer
+        .copy_from(&mut FailingSource, COMMIT_EVERY)
+        .await
+        .expect_err("copying did not fail");
+
+    let mut data = Vec::new();
+    reader
+        .read_to_end(&mut data)
+        .await
+        .expect_err("reading did not fail");
+}
+
+/// A source that fails on the first read.
+struct FailingSource;
+
+impl AsyncRead for FailingSource {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Poll::Ready(Err(io::Error::from(io::ErrorKind::ConnectionReset)))
+    }
+}
@@ -0,0 +1,60 @@
+//! This test follows the state of a file published through a state file, as a reader
+//! in another process would.
+#![cfg(unix)]
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FsFile, SharedFile};
+
+#[tokio::test]
+async fn follower_reads_published_data() {
+    let path = std::env::temp_dir().join(format!("shared-files-xp-{}.bin", std::process::id()));
+    let state_path = path.with_extension("state");
+    let file = SharedFile::<FsFile>::create(&path)
+        .await
+        .expect("failed to create file");
+    file.publish_state(&state_path)
+        .await
+        .expect("failed to publish state");
+
+    let follower = SharedFile::<FsFile>::open(&path)
+        .await
+        .expect("failed to open file");
+    let task = follower
+        .spawn_state_follower(&state_path, Duration::from_millis(5))
+        .await
+        .expect("failed to follow state");
+    let mut reader = follower.reader().await.expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+
+    let mut buf = [0; 5];
+    reader
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(&buf, b"hello");
+
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut rest = Vec::new();
+    reader
+        .read_to_end(&mut rest)
+        .await
+        .expect("failed to read file");
+    assert_eq!(rest, b" world");
+    task.await.expect("failed to join follower");
+    assert_eq!(follower.len(), 11);
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+    tokio::fs::remove_file(&state_path)
+        .await
+        .expect("failed to remove state file");
+}
@@ -0,0 +1,100 @@
+//! This test verifies the data read against an expected digest
+//! while the file is being written concurrently, and publishes the
+//! digest of the written data.
+
+use std::io::ErrorKind;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{Crc32, SharedTemporaryFile};
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 100_000;
+
+/// The size of each write.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn digest_is_verified() {
+    let data = test_data();
+    let mut crc = Crc32::new();
+    crc.update(&data);
+    let expected = crc.value().to_be_bytes();
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .verify_digest(Crc32::new(), expected);
+    let read = tokio::spawn(read_all(reader));
+
+    write_file(&file, &data).await;
+
+    let read = read.await.expect("failed to join reader");
+    assert_eq!(read.expect("failed to verify"), data);
+}
+
+#[tokio::test]
+async fn digest_mismatch_is_detected() {
+    let data = test_data();
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    write_file(&file, &data).await;
+
+    let reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .verify_digest(Crc32::new(), [0u8; 4]);
+    let error = read_all(reader)
+        .await
+        .expect_err("mismatch was not detected");
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn written_digest_is_published() {
+    let data = test_data();
+    let mut crc = Crc32::new();
+    crc.update(&data);
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_digest(Crc32::new());
+    writer.write_all(&data).await.expect("failed to write");
+    assert_eq!(file.digest(), None);
+
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(file.digest(), Some(crc.value().to_be_bytes().to_vec()));
+}
+
+/// Generates the test data.
+fn test_data() -> Vec<u8> {
+    (0..NUM_BYTES).map(|i| (i % 251) as u8).collect()
+}
+
+/// Writes the data to the file in chunks and completes it.
+async fn write_file(file: &SharedTemporaryFile, data: &[u8]) {
+    let mut writer = file.writer().await.expect("failed to create writer");
+    for chunk in data.chunks(CHUNK_SIZE) {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    writer.complete().await.expect("failed to complete");
+}
+
+/// Reads the reader to the end.
+async fn read_all<R: AsyncReadExt + Unpin>(mut reader: R) -> std::io::Result<Vec<u8>> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await?;
+    Ok(data)
+}
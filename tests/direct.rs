@@ -0,0 +1,51 @@
+//! This test shares a file bypassing the page cache using unaligned reads and writes.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{DirectFile, SharedDirectFile};
+
+#[tokio::test]
+async fn unaligned_io_round_trips() {
+    let path = std::env::temp_dir().join(format!("shared-files-direct-{}.bin", std::process::id()));
+    let file = SharedDirectFile::from(
+        DirectFile::create(&path)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    let data: Vec<u8> = (0..200_000u32).map(|i| (i % 251) as u8).collect();
+    let mut read = Vec::new();
+    for chunk in data.chunks(7_919) {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+
+        // Reads see committed data only, never the padding of the last block.
+        let mut buf = vec![0; chunk.len()];
+        reader
+            .read_exact(&mut buf)
+            .await
+            .expect("failed to read file");
+        read.extend_from_slice(&buf);
+    }
+    writer.complete().await.expect("failed to complete");
+
+    let mut rest = Vec::new();
+    reader
+        .read_to_end(&mut rest)
+        .await
+        .expect("failed to read file");
+    assert!(rest.is_empty());
+    assert_eq!(read, data);
+
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .expect("failed to get metadata");
+    assert_eq!(metadata.len(), data.len() as u64);
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
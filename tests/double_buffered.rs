@@ -0,0 +1,48 @@
+//! This test reads a file with a double-buffered reader while it is being written.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of chunks to write.
+const NUM_CHUNKS: usize = 100;
+
+/// The size of each written chunk.
+const CHUNK_SIZE: usize = 1_000;
+
+/// The size of each read buffer.
+const BUFFER_SIZE: usize = 4_096;
+
+#[tokio::test]
+async fn double_buffered_read() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .double_buffered(BUFFER_SIZE);
+
+    let read = tokio::spawn(async move {
+        let mut data = Vec::new();
+        while let Some(buffer) = reader.next_buffer().await.expect("failed to read") {
+            assert!(buffer.len() <= BUFFER_SIZE);
+            data.extend_from_slice(&buffer);
+        }
+        data
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut expected = Vec::new();
+    for chunk in 0..NUM_CHUNKS {
+        let data = [chunk as u8; CHUNK_SIZE];
+        writer.write_all(&data).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+        expected.extend_from_slice(&data);
+    }
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join"), expected);
+}
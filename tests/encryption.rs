@@ -0,0 +1,91 @@
+//! This test encrypts a file while it is read concurrently.
+
+use async_tempfile::TempFile;
+use std::io::{ErrorKind, SeekFrom};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use shared_files::{EncryptedFile, FilePath, SharedFile, ENCRYPTION_CHUNK_SIZE};
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 3 * ENCRYPTION_CHUNK_SIZE + 1_000;
+
+/// The size of each write.
+const CHUNK_SIZE: usize = 10_000;
+
+/// A marker that must not appear in the file on disk.
+const MARKER: &[u8] = b"plaintext marker";
+
+#[tokio::test]
+async fn encrypted_file_is_read() {
+    let data = test_data();
+
+    let temp_file = TempFile::new().await.expect("failed to create file");
+    let file = SharedFile::from(EncryptedFile::with_random_key(temp_file));
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    for chunk in data.chunks(CHUNK_SIZE) {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), data);
+
+    let raw = tokio::fs::read(file.file_path())
+        .await
+        .expect("failed to read file from disk");
+    assert!(!raw.windows(MARKER.len()).any(|w| w == MARKER));
+}
+
+#[tokio::test]
+async fn tampering_is_detected() {
+    let data = test_data();
+
+    let temp_file = TempFile::new().await.expect("failed to create file");
+    let file = SharedFile::from(EncryptedFile::new(temp_file, [42; 32]));
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(&data).await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    // Flip a bit within the first chunk, after the nonce and the length prefix.
+    let mut raw = tokio::fs::OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(file.file_path())
+        .await
+        .expect("failed to open file");
+    raw.seek(SeekFrom::Start(100))
+        .await
+        .expect("failed to seek");
+    let byte = raw.read_u8().await.expect("failed to read byte");
+    raw.seek(SeekFrom::Start(100))
+        .await
+        .expect("failed to seek");
+    raw.write_u8(byte ^ 1).await.expect("failed to write byte");
+    raw.sync_all().await.expect("failed to sync");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    let error = reader
+        .read_to_end(&mut buf)
+        .await
+        .expect_err("tampering was not detected");
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+/// Generates the test data, containing the marker.
+fn test_data() -> Vec<u8> {
+    let mut data: Vec<u8> = (0..NUM_BYTES).map(|i| (i % 251) as u8).collect();
+    data[..MARKER.len()].copy_from_slice(MARKER);
+    data
+}
@@ -0,0 +1,95 @@
+//! This test registers callbacks notified of state changes of a file and subscribes
+//! to its lifecycle events.
+
+use std::sync::{Arc, Mutex};
+use tokio::io::AsyncWriteExt;
+
+use shared_files::{FileEvent, SharedTemporaryFile};
+
+#[tokio::test]
+async fn callbacks_observe_commits_and_completion() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let events = Arc::new(Mutex::new(Vec::new()));
+    file.on_commit({
+        let events = events.clone();
+        move |committed| events.lock().unwrap().push(format!("commit {committed}"))
+    });
+    file.on_complete({
+        let events = events.clone();
+        move |len| events.lock().unwrap().push(format!("complete {len}"))
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer
+        .write_all(b" world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.complete().await.expect("failed to complete");
+
+    let events = events.lock().unwrap();
+    assert_eq!(*events, ["commit 5", "commit 11", "complete 11"]);
+}
+
+#[tokio::test]
+async fn callbacks_observe_failure() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let failure = Arc::new(Mutex::new(None));
+    file.on_failure({
+        let failure = failure.clone();
+        move |error| *failure.lock().unwrap() = Some(error.to_string())
+    });
+
+    let writer = file.writer().await.expect("failed to create writer");
+    writer.abort("connection reset");
+
+    let failure = failure.lock().unwrap();
+    assert!(failure
+        .as_deref()
+        .expect("no failure observed")
+        .contains("connection reset"));
+}
+
+#[tokio::test]
+async fn event_stream_yields_lifecycle_events() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut events = file.events();
+
+    let reader = file.reader().await.expect("failed to create reader");
+    let reader_id = reader.id();
+    drop(reader);
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.complete().await.expect("failed to complete");
+
+    assert!(matches!(events.recv().await, Some(FileEvent::Created)));
+    assert!(matches!(events.recv().await, Some(FileEvent::ReaderAttached(id)) if id == reader_id));
+    assert!(matches!(events.recv().await, Some(FileEvent::ReaderDetached(id)) if id == reader_id));
+    assert!(matches!(
+        events.recv().await,
+        Some(FileEvent::Committed(11))
+    ));
+    assert!(matches!(
+        events.recv().await,
+        Some(FileEvent::Completed(11))
+    ));
+
+    drop(file);
+    assert!(events.recv().await.is_none());
+}
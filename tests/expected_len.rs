@@ -0,0 +1,49 @@
+//! This test reports the expected total size of a file while it is being written.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FileSize, SharedTemporaryFile};
+
+/// The number of bytes to write.
+const NUM_BYTES: u64 = 10_000;
+
+#[tokio::test]
+async fn expected_total_is_reported() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::AtLeast(0));
+
+    writer
+        .set_expected_len(NUM_BYTES)
+        .await
+        .expect("failed to set expected length");
+    assert_eq!(reader.file_size(), FileSize::AtLeastWithTotal(0, NUM_BYTES));
+
+    writer
+        .write_all(&[1; NUM_BYTES as usize / 2])
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    assert_eq!(
+        reader.file_size(),
+        FileSize::AtLeastWithTotal(NUM_BYTES / 2, NUM_BYTES)
+    );
+
+    writer
+        .write_all(&[2; NUM_BYTES as usize / 2])
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(reader.file_size(), FileSize::Exactly(NUM_BYTES));
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf.len() as u64, NUM_BYTES);
+    assert!(buf[NUM_BYTES as usize / 2..].iter().all(|&b| b == 2));
+}
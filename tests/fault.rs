@@ -0,0 +1,54 @@
+//! This test injects faults into the operations of a shared file.
+
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FaultInjector, FaultOperation, FaultyFile, MemoryFile, SharedFile};
+
+#[tokio::test]
+async fn short_writes_are_retried() {
+    let injector = FaultInjector::new();
+    injector.short_writes(2);
+    injector.delay(FaultOperation::Read, Duration::from_millis(1));
+    let file = SharedFile::from(FaultyFile::new(MemoryFile::new(), injector.clone()));
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
+
+#[tokio::test]
+async fn injected_faults_fail_operations() {
+    let injector = FaultInjector::new();
+    let file = SharedFile::from(FaultyFile::new(MemoryFile::new(), injector.clone()));
+
+    injector.fail(FaultOperation::Open, ErrorKind::PermissionDenied);
+    assert!(file.reader().await.is_err());
+    injector.clear(FaultOperation::Open);
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+
+    injector.fail(FaultOperation::Write, ErrorKind::BrokenPipe);
+    let error = writer.flush().await.expect_err("flushing succeeded");
+    assert_eq!(error.kind(), ErrorKind::BrokenPipe);
+    drop(writer);
+
+    // Readers observe that writing the file failed.
+    let mut buf = Vec::new();
+    assert!(reader.read_to_end(&mut buf).await.is_err());
+}
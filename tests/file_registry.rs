@@ -0,0 +1,158 @@
+//! This test coalesces concurrent requests for the same file using a registry.
+
+use std::convert::Infallible;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::{ReadError, RegistryError};
+use shared_files::{
+    FsFile, MemoryFile, RetentionPolicy, SharedFile, SharedFileRegistry, SharedMemoryFile,
+};
+
+#[tokio::test]
+async fn concurrent_requests_share_one_writer() {
+    let registry = Arc::new(SharedFileRegistry::<String, MemoryFile>::new());
+    let created = Arc::new(AtomicUsize::new(0));
+
+    let mut requests = Vec::new();
+    for _ in 0..8 {
+        let registry = Arc::clone(&registry);
+        let created = Arc::clone(&created);
+        requests.push(tokio::spawn(async move {
+            let file = registry
+                .get_or_create(String::from("key"), || async {
+                    created.fetch_add(1, Ordering::SeqCst);
+                    let file = SharedMemoryFile::new_async().await?;
+                    let mut writer = file.writer().await?;
+                    tokio::spawn(async move {
+                        writer.write_all(b"hello").await.expect("failed to write");
+                        writer.flush().await.expect("failed to flush");
+                        tokio::task::yield_now().await;
+                        writer.write_all(b" world").await.expect("failed to write");
+                        writer.complete().await.expect("failed to complete");
+                    });
+                    Ok::<_, Infallible>(file)
+                })
+                .await
+                .expect("failed to create file");
+
+            let mut reader = file.reader().await.expect("failed to create reader");
+            let mut buf = Vec::new();
+            reader
+                .read_to_end(&mut buf)
+                .await
+                .expect("failed to read file");
+            buf
+        }));
+    }
+
+    for request in requests {
+        assert_eq!(
+            request.await.expect("failed to join request"),
+            b"hello world"
+        );
+    }
+    assert_eq!(created.load(Ordering::SeqCst), 1);
+    assert_eq!(registry.len(), 1);
+    assert!(registry.get("key").is_some());
+    assert!(registry.remove("key").is_some());
+    assert!(registry.is_empty());
+}
+
+#[tokio::test]
+async fn expired_files_are_evicted() {
+    let path = std::env::temp_dir().join(format!("shared-files-ttl-{}.bin", std::process::id()));
+    let registry = SharedFileRegistry::<u32, FsFile>::new().with_ttl(Duration::from_millis(20));
+    let file = registry
+        .get_or_create(1, || async {
+            let file = SharedFile::<FsFile>::create(&path).await?;
+            file.set_retention_policy(RetentionPolicy::DeleteOnDrop);
+            let mut writer = file.writer().await?;
+            writer.write_all(b"hello").await?;
+            writer
+                .complete()
+                .await
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            Ok::<_, std::io::Error>(file)
+        })
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    drop(file);
+
+    assert_eq!(registry.evict_expired(), 0);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert!(registry.get(&1).is_none());
+    assert_eq!(registry.evict_expired(), 1);
+    assert!(registry.is_empty());
+
+    // The file is deleted once its last reader was dropped.
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello");
+    assert!(path.exists());
+    drop(reader);
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn shutdown_waits_for_writers_then_aborts_them() {
+    let registry = SharedFileRegistry::<u32, MemoryFile>::new();
+    let completing = registry
+        .get_or_create(1, || async {
+            let file = SharedMemoryFile::new_async().await?;
+            let mut writer = file.writer().await?;
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                writer.write_all(b"done").await.expect("failed to write");
+                writer.complete().await.expect("failed to complete");
+            });
+            Ok::<_, Infallible>(file)
+        })
+        .await
+        .expect("failed to create file");
+
+    let stalled = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let writer = stalled.writer().await.expect("failed to create writer");
+    let mut reader = stalled.reader().await.expect("failed to create reader");
+    registry
+        .get_or_create(2, || async { Ok::<_, Infallible>(stalled.clone()) })
+        .await
+        .expect("failed to register file");
+
+    assert_eq!(
+        registry.shutdown_graceful(Duration::from_millis(200)).await,
+        1
+    );
+    assert!(registry.is_shut_down());
+    assert!(registry.is_empty());
+    assert_eq!(
+        completing
+            .wait_until_complete()
+            .await
+            .expect("failed to complete"),
+        4
+    );
+
+    let error = reader
+        .read_to_end(&mut Vec::new())
+        .await
+        .expect_err("reading an aborted file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Aborted(_))
+    ));
+
+    let result = registry
+        .get_or_create(3, || async { SharedMemoryFile::new_async().await })
+        .await;
+    assert!(matches!(result, Err(RegistryError::ShutDown)));
+    drop(writer);
+}
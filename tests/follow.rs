@@ -0,0 +1,46 @@
+//! This test follows a file while it is written without this crate, as by another process.
+
+use std::io::Write;
+use std::time::Duration;
+use tokio::io::AsyncReadExt;
+
+use shared_files::{FsFile, SharedFile, WatchOptions};
+
+#[tokio::test]
+async fn growth_is_committed_until_closed() {
+    let path = std::env::temp_dir().join(format!("shared-files-watch-{}.bin", std::process::id()));
+    let mut external = std::fs::File::create(&path).expect("failed to create file");
+
+    let file = SharedFile::<FsFile>::open(&path)
+        .await
+        .expect("failed to open file");
+    let options = WatchOptions::new(Duration::from_millis(20))
+        .with_complete_after_idle(Duration::from_secs(2));
+    let task = file.spawn_growth_watcher(&path, options);
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    external.write_all(b"hello").expect("failed to write");
+    let mut buf = [0; 5];
+    reader
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(&buf, b"hello");
+
+    external.write_all(b" world").expect("failed to write");
+    drop(external);
+
+    let mut rest = Vec::new();
+    reader
+        .read_to_end(&mut rest)
+        .await
+        .expect("failed to read file");
+    assert_eq!(rest, b" world");
+    task.await.expect("failed to join watcher");
+    assert_eq!(file.len(), 11);
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
@@ -0,0 +1,44 @@
+//! This test reads a frozen snapshot while the original file continues to grow.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FileSize, SharedTemporaryFile};
+
+#[tokio::test]
+async fn frozen_snapshot_is_completed() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+
+    // Only committed data is part of the snapshot.
+    let frozen = file.freeze();
+    assert_eq!(frozen.len(), 5);
+
+    writer.flush().await.expect("failed to flush");
+    let mut reader = frozen.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::Exactly(5));
+
+    // The snapshot ends while the original file is still pending.
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello");
+    assert_eq!(reader.lag(), Some(0));
+
+    writer.complete().await.expect("failed to complete");
+    let mut buf = Vec::new();
+    file.reader()
+        .await
+        .expect("failed to create reader")
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
@@ -0,0 +1,96 @@
+//! This test shares a plain file at a path controlled by the caller.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FilePath, FsFile, SharedFile};
+
+#[tokio::test]
+async fn file_at_path_is_shared() {
+    let path = std::env::temp_dir().join(format!("shared-files-fs-{}.bin", std::process::id()));
+    let file = SharedFile::<FsFile>::create(&path)
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.file_path(), &path);
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+    drop(file);
+
+    // The file is kept once it is no longer used.
+    let data = tokio::fs::read(&path).await.expect("failed to read file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[cfg(unix)]
+#[tokio::test]
+async fn file_is_created_with_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path =
+        std::env::temp_dir().join(format!("shared-files-fs-mode-{}.bin", std::process::id()));
+    let file = SharedFile::<FsFile>::create_with_mode(&path, 0o600)
+        .await
+        .expect("failed to create file");
+
+    let metadata = tokio::fs::metadata(&path)
+        .await
+        .expect("failed to get metadata");
+    assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[cfg(windows)]
+#[tokio::test]
+async fn file_is_deleted_on_close() {
+    use shared_files::WindowsOptions;
+
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-fs-delete-on-close-{}.bin",
+        std::process::id()
+    ));
+    let options = WindowsOptions {
+        delete_on_close: true,
+        ..WindowsOptions::new()
+    };
+    let file = SharedFile::<FsFile>::create_with_options(&path, options)
+        .await
+        .expect("failed to create file");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello");
+
+    drop(reader);
+    drop(file);
+    assert!(!path.exists());
+}
@@ -0,0 +1,79 @@
+//! This test shares an in-memory file using the futures-io traits, without a tokio runtime.
+
+use std::future::{poll_fn, Future};
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake};
+use std::thread::Thread;
+
+use futures_io::{AsyncRead, AsyncWrite};
+
+use shared_files::{MemoryFile, SharedMemoryFile};
+
+/// Wakes up a thread blocked on a future.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Runs the future to completion on the current thread.
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut cx) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+/// Reads until the end of the file.
+async fn read_to_end<R: AsyncRead + Unpin>(reader: &mut R) -> Vec<u8> {
+    let mut data = Vec::new();
+    let mut buf = [0; 4];
+    loop {
+        let read = poll_fn(|cx| Pin::new(&mut *reader).poll_read(cx, &mut buf))
+            .await
+            .expect("failed to read file");
+        if read == 0 {
+            return data;
+        }
+        data.extend_from_slice(&buf[..read]);
+    }
+}
+
+/// Writes the entire buffer.
+async fn write_all<W: AsyncWrite + Unpin>(writer: &mut W, mut buf: &[u8]) {
+    while !buf.is_empty() {
+        let written = poll_fn(|cx| Pin::new(&mut *writer).poll_write(cx, buf))
+            .await
+            .expect("failed to write");
+        buf = &buf[written..];
+    }
+}
+
+#[test]
+fn file_is_shared_without_tokio() {
+    let file = SharedMemoryFile::from(MemoryFile::new());
+    let mut reader = block_on(file.reader()).expect("failed to create reader");
+    let read = std::thread::spawn(move || block_on(read_to_end(&mut reader)));
+
+    block_on(async {
+        let mut writer = file.writer().await.expect("failed to create writer");
+        write_all(&mut writer, b"hello").await;
+        poll_fn(|cx| Pin::new(&mut writer).poll_flush(cx))
+            .await
+            .expect("failed to flush");
+        write_all(&mut writer, b" world").await;
+        poll_fn(|cx| Pin::new(&mut writer).poll_close(cx))
+            .await
+            .expect("failed to close");
+    });
+
+    assert_eq!(read.join().expect("failed to join reader"), b"hello world");
+}
@@ -0,0 +1,70 @@
+//! This test uses a backend with distinct reader and writer handle types.
+
+use async_tempfile::TempFile;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt, ReadBuf};
+
+use shared_files::{SharedFile, SharedFileType};
+
+/// A backend handing out read-only handles to readers.
+struct ReadOnlyBackend(TempFile);
+
+/// A handle that can only be read from.
+struct ReadOnlyFile(TempFile);
+
+impl AsyncRead for ReadOnlyFile {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+#[async_trait::async_trait]
+impl SharedFileType for ReadOnlyBackend {
+    type ReadHandle = ReadOnlyFile;
+    type WriteHandle = TempFile;
+    type OpenError = <TempFile as SharedFileType>::OpenError;
+    type SyncError = <TempFile as SharedFileType>::SyncError;
+
+    async fn open_ro(&self) -> Result<Self::ReadHandle, Self::OpenError> {
+        Ok(ReadOnlyFile(self.0.open_ro().await?))
+    }
+
+    async fn open_rw(&self) -> Result<Self::WriteHandle, Self::OpenError> {
+        self.0.open_rw().await
+    }
+
+    async fn sync_all(&self) -> Result<(), Self::SyncError> {
+        SharedFileType::sync_all(&self.0).await
+    }
+
+    async fn sync_data(&self) -> Result<(), Self::SyncError> {
+        SharedFileType::sync_data(&self.0).await
+    }
+}
+
+#[tokio::test]
+async fn distinct_handles_are_used() {
+    let temp_file = TempFile::new().await.expect("failed to create file");
+    let file = SharedFile::from(ReadOnlyBackend(temp_file));
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
@@ -0,0 +1,121 @@
+//! This test declares holes in a file while it is read concurrently.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+/// The size of the hole.
+const HOLE_SIZE: usize = 100_000;
+
+#[tokio::test]
+async fn holes_read_as_zeros() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"head").await.expect("failed to write");
+    writer
+        .write_hole(HOLE_SIZE as u64)
+        .await
+        .expect("failed to write hole");
+    writer.write_all(b"tail").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(file.holes(), vec![4..4 + HOLE_SIZE as u64]);
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf.len(), 8 + HOLE_SIZE);
+    assert_eq!(&buf[..4], b"head");
+    assert!(buf[4..4 + HOLE_SIZE].iter().all(|&b| b == 0));
+    assert_eq!(&buf[4 + HOLE_SIZE..], b"tail");
+}
+
+#[tokio::test]
+async fn holes_are_skipped() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"head").await.expect("failed to write");
+    writer
+        .write_hole(HOLE_SIZE as u64)
+        .await
+        .expect("failed to write hole");
+    writer.flush().await.expect("failed to flush");
+
+    let mut head = [0; 4];
+    reader
+        .read_exact(&mut head)
+        .await
+        .expect("failed to read head");
+    assert_eq!(&head, b"head");
+    assert_eq!(
+        reader.skip_hole().await.expect("failed to skip"),
+        HOLE_SIZE as u64
+    );
+    assert_eq!(reader.skip_hole().await.expect("failed to skip"), 0);
+
+    writer.write_all(b"tail").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"tail");
+}
+
+#[tokio::test]
+async fn large_holes_are_skipped_at_once() {
+    /// The size of the hole, far larger than can be read in reasonable time.
+    const LARGE_HOLE_SIZE: u64 = 1 << 36;
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .with_tail_cache();
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_tail_cache(1024);
+    writer.write_all(b"head").await.expect("failed to write");
+    writer
+        .write_hole(LARGE_HOLE_SIZE)
+        .await
+        .expect("failed to write hole");
+    writer.write_all(b"tail").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut head = [0; 4];
+    reader
+        .read_exact(&mut head)
+        .await
+        .expect("failed to read head");
+    assert_eq!(&head, b"head");
+    assert_eq!(
+        reader.skip_hole().await.expect("failed to skip"),
+        LARGE_HOLE_SIZE
+    );
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"tail");
+}
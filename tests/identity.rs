@@ -0,0 +1,33 @@
+//! This test correlates files with their readers and writers using the file ID.
+
+use uuid::Uuid;
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn handles_share_the_file_id() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let other = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert_ne!(file.id(), other.id());
+    assert_eq!(file.clone().id(), file.id());
+
+    let reader = file.reader().await.expect("failed to create reader");
+    let writer = file.writer().await.expect("failed to create writer");
+    assert_eq!(reader.file_id(), file.id());
+    assert_eq!(writer.file_id(), file.id());
+}
+
+#[tokio::test]
+async fn builder_assigns_the_file_id() {
+    let id = Uuid::from_u128(42);
+    let file = SharedTemporaryFile::builder()
+        .id(id)
+        .build_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.id(), id);
+}
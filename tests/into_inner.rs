@@ -0,0 +1,38 @@
+//! This test recovers the backing file once sharing is over.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn try_into_inner_requires_exclusive_completed_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let path = file.file_path().clone();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+
+    // The file is not complete yet.
+    let file = file.try_into_inner().expect_err("took pending file");
+    writer.complete().await.expect("failed to complete");
+
+    // Another handle still exists.
+    let clone = file.clone();
+    let file = file.try_into_inner().expect_err("took shared file");
+    drop(clone);
+
+    let reader = file.reader().await.expect("failed to create reader");
+    let file = file
+        .try_into_inner()
+        .expect_err("took file with active reader");
+    drop(reader);
+
+    let inner = file.try_into_inner().expect("failed to take file");
+    assert_eq!(inner.file_path(), &path);
+    assert!(path.exists());
+}
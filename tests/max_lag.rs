@@ -0,0 +1,55 @@
+//! This test bounds how far the writer may run ahead of its readers.
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 64 * 1024;
+
+/// The size of each write.
+const CHUNK_SIZE: usize = 256;
+
+/// The maximum number of bytes the writer may be ahead of the reader.
+const MAX_LAG: u64 = 4 * 1024;
+
+#[tokio::test]
+async fn writer_waits_for_slow_reader() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_max_lag(MAX_LAG);
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = [0; CHUNK_SIZE];
+        let mut total = 0;
+        loop {
+            let read = reader.read(&mut buf).await.expect("failed to read");
+            if read == 0 {
+                return total;
+            }
+            total += read;
+            tokio::time::sleep(Duration::from_micros(100)).await;
+        }
+    });
+
+    for written in (CHUNK_SIZE..=NUM_BYTES).step_by(CHUNK_SIZE) {
+        writer
+            .write_all(&[42; CHUNK_SIZE])
+            .await
+            .expect("failed to write");
+        let slowest = writer
+            .slowest_reader_position()
+            .expect("the reader is active");
+        assert!(written as u64 - slowest <= MAX_LAG + CHUNK_SIZE as u64);
+    }
+
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(read.await.expect("failed to join reader"), NUM_BYTES);
+}
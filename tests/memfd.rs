@@ -0,0 +1,46 @@
+//! This test shares a file in anonymous memory and seals it once completed.
+#![cfg(target_os = "linux")]
+
+use std::os::fd::AsRawFd;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::SealError;
+use shared_files::SharedMemfdFile;
+
+#[tokio::test]
+async fn memfd_file_is_sealed_on_completion() {
+    let file = SharedMemfdFile::new_memfd("shared-files").expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    assert!(matches!(file.seal(), Err(SealError::NotCompleted)));
+
+    writer.write_all(b" world").await.expect("failed to write");
+    writer
+        .complete_and_seal()
+        .await
+        .expect("failed to complete and seal");
+    assert!(file.memfd().is_sealed().expect("failed to get seals"));
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+
+    // Other processes open the file descriptor the same way, but cannot modify it.
+    let path = format!("/proc/self/fd/{}", file.memfd().as_raw_fd());
+    assert_eq!(
+        tokio::fs::read(&path).await.expect("failed to read by fd"),
+        b"hello world"
+    );
+    let mut other = std::fs::OpenOptions::new()
+        .write(true)
+        .open(&path)
+        .expect("failed to open by fd");
+    std::io::Write::write_all(&mut other, b"bye")
+        .expect_err("writing to the sealed file succeeded");
+}
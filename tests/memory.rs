@@ -0,0 +1,60 @@
+//! This test shares an in-memory file between a writer and concurrent readers.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FileSize, MemoryFile, SharedMemoryFile};
+
+#[tokio::test]
+async fn memory_file_is_shared() {
+    let file = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+    let reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::Exactly(11));
+}
+
+#[tokio::test]
+async fn memory_file_is_truncated() {
+    let file = SharedMemoryFile::from(MemoryFile::with_capacity(16));
+    let (mut writer, _reader) = file.clone().split().await.expect("failed to split file");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    file.truncate(5).await.expect("failed to truncate");
+    let mut writer = file
+        .append_writer()
+        .await
+        .expect("failed to create append writer");
+    writer.write_all(b" there").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    file.reader()
+        .await
+        .expect("failed to create reader")
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello there");
+}
@@ -0,0 +1,33 @@
+//! This test attaches metadata to a file and retrieves it from a reader.
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn metadata_is_visible_to_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let reader = file.reader().await.expect("failed to create reader");
+    let writer = file.writer().await.expect("failed to create writer");
+
+    assert_eq!(file.set_metadata("content-type", "text/plain"), None);
+    assert_eq!(writer.set_metadata("filename", "hello.txt"), None);
+    assert_eq!(
+        file.set_metadata("content-type", "application/json"),
+        Some("text/plain".to_string())
+    );
+
+    assert_eq!(
+        reader.metadata("content-type").as_deref(),
+        Some("application/json")
+    );
+    assert_eq!(reader.metadata("filename").as_deref(), Some("hello.txt"));
+    assert_eq!(reader.metadata("owner"), None);
+    assert_eq!(reader.all_metadata().len(), 2);
+
+    assert_eq!(
+        file.remove_metadata("filename"),
+        Some("hello.txt".to_string())
+    );
+    assert_eq!(reader.metadata("filename"), None);
+}
@@ -0,0 +1,67 @@
+//! This test mirrors a shared file to a secondary backend.
+
+use std::io::ErrorKind;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{
+    FaultInjector, FaultOperation, FaultyFile, MemoryFile, MirroredFile, SharedFile,
+    SharedFileType, TeeErrorPolicy,
+};
+
+#[tokio::test]
+async fn writes_are_mirrored() {
+    let secondary = MemoryFile::new();
+    let mirrored = secondary.open_ro().await.expect("failed to open file");
+    let file = SharedFile::from(MirroredFile::new(
+        MemoryFile::new(),
+        secondary,
+        TeeErrorPolicy::Fail,
+    ));
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+    assert_eq!(mirrored.to_vec(), b"hello world");
+}
+
+#[tokio::test]
+async fn secondary_errors_are_handled_by_policy() {
+    let injector = FaultInjector::new();
+    injector.fail(FaultOperation::Write, ErrorKind::BrokenPipe);
+
+    // Detaching the secondary file keeps writing the primary file.
+    let file = SharedFile::from(MirroredFile::new(
+        MemoryFile::new(),
+        FaultyFile::new(MemoryFile::new(), injector.clone()),
+        TeeErrorPolicy::Detach,
+    ));
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    // Failing on errors of the secondary file fails the file.
+    let file = SharedFile::from(MirroredFile::new(
+        MemoryFile::new(),
+        FaultyFile::new(MemoryFile::new(), injector),
+        TeeErrorPolicy::Fail,
+    ));
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    assert!(writer.write_all(b"hello").await.is_err());
+    drop(writer);
+
+    let mut buf = Vec::new();
+    assert!(reader.read_to_end(&mut buf).await.is_err());
+}
@@ -0,0 +1,42 @@
+//! This test shares a memory-mapped file between a writer and concurrent readers.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{MmapFile, SharedMmapFile};
+
+#[tokio::test]
+async fn mapped_file_is_shared() {
+    let path = std::env::temp_dir().join(format!("shared-files-mmap-{}.bin", std::process::id()));
+    let file = SharedMmapFile::from(
+        MmapFile::with_capacity(&path, 4)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    // Writing past the initial capacity grows the mapping.
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+    drop(file);
+
+    // Completing the file truncates it to the written length.
+    let data = tokio::fs::read(&path).await.expect("failed to read file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
@@ -0,0 +1,42 @@
+//! This test shares a file split into multiple segment files.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{MultiFile, SharedFileType, SharedMultiFile};
+
+#[tokio::test]
+async fn segments_are_read_as_one_file() {
+    let path = std::env::temp_dir().join(format!("shared-files-multi-{}.bin", std::process::id()));
+    let multi_file = MultiFile::with_segment_size(&path, 4)
+        .await
+        .expect("failed to create file");
+    let segments = multi_file.open_ro().await.expect("failed to open file");
+    let file = SharedMultiFile::from(multi_file);
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+
+    assert_eq!(segments.segment_count(), 3);
+    let data = tokio::fs::read(segments.segment_path(2))
+        .await
+        .expect("failed to read segment");
+    assert_eq!(data, b"rld");
+
+    segments.remove().await.expect("failed to remove segments");
+    assert!(!segments.segment_path(0).exists());
+}
@@ -0,0 +1,30 @@
+//! This test shares a temporary file of the tempfile crate between a writer and a reader.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedNamedTempFile;
+
+#[tokio::test]
+async fn named_temp_file_is_shared() {
+    let file = SharedNamedTempFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+}
@@ -0,0 +1,63 @@
+//! This test wakes up readers using a custom notifier.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::task::Waker;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use uuid::Uuid;
+
+use shared_files::{ReaderNotifier, ReaderPriority, SharedTemporaryFile, WakerMap};
+
+/// Counts the wakeups before delegating to the default notifier.
+#[derive(Default)]
+struct CountingNotifier {
+    inner: WakerMap,
+    notified: Arc<AtomicUsize>,
+}
+
+impl ReaderNotifier for CountingNotifier {
+    fn register(&self, id: Uuid, priority: ReaderPriority, waker: &Waker) {
+        self.inner.register(id, priority, waker);
+    }
+
+    fn remove(&self, id: &Uuid) {
+        self.inner.remove(id);
+    }
+
+    fn notify_all(&self) {
+        self.notified.fetch_add(1, Ordering::Relaxed);
+        self.inner.notify_all();
+    }
+}
+
+#[tokio::test]
+async fn custom_notifier_wakes_readers() {
+    let notifier = CountingNotifier::default();
+    let notified = notifier.notified.clone();
+    let file = SharedTemporaryFile::builder()
+        .notifier(notifier)
+        .build_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+    assert!(notified.load(Ordering::Relaxed) >= 2);
+}
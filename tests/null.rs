@@ -0,0 +1,53 @@
+//! This test commits data to a file discarding it.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{NullFile, NullRead, SharedNullFile};
+
+#[tokio::test]
+async fn readers_receive_zeros() {
+    let file = SharedNullFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(read.await.expect("failed to join reader"), [0; 11]);
+}
+
+#[tokio::test]
+async fn readers_receive_eof() {
+    let file = SharedNullFile::from(NullFile::new(NullRead::Eof));
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.complete().await.expect("failed to complete");
+
+    assert!(read.await.expect("failed to join reader").is_empty());
+    assert_eq!(file.reader_count(), 0);
+}
@@ -0,0 +1,99 @@
+//! This test uploads a shared file to an object store while it is read locally.
+
+use object_store::memory::InMemory;
+use object_store::path::Path;
+use object_store::ObjectStore;
+use std::sync::Arc;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{FsFile, ObjectStoreFile, SharedFile};
+
+#[tokio::test]
+async fn file_is_uploaded_while_written() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-object-store-{}.bin",
+        std::process::id()
+    ));
+    let store = Arc::new(InMemory::new());
+    let location = Path::from("uploads/file.bin");
+    let file = FsFile::create(&path).await.expect("failed to create file");
+    let file = SharedFile::from(
+        ObjectStoreFile::new(file, store.clone(), location.clone()).with_part_size(4),
+    );
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.shutdown().await.expect("failed to shut down");
+
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+
+    let uploaded = store
+        .get(&location)
+        .await
+        .expect("failed to get object")
+        .bytes()
+        .await
+        .expect("failed to read object");
+    assert_eq!(&uploaded[..], b"hello world");
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[tokio::test]
+async fn committed_data_is_uploaded() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-spawn-upload-{}.bin",
+        std::process::id()
+    ));
+    let store = Arc::new(InMemory::new());
+    let location = Path::from("uploads/committed.bin");
+    let file = SharedFile::<FsFile>::create(&path)
+        .await
+        .expect("failed to create file");
+
+    let upload = file
+        .spawn_upload(store.clone(), location.clone(), 4)
+        .await
+        .expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let uploaded = upload
+        .await
+        .expect("failed to join upload")
+        .expect("failed to upload file");
+    assert_eq!(uploaded, 11);
+
+    let object = store
+        .get(&location)
+        .await
+        .expect("failed to get object")
+        .bytes()
+        .await
+        .expect("failed to read object");
+    assert_eq!(&object[..], b"hello world");
+
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
@@ -0,0 +1,53 @@
+//! This test extends a read-only base file with a delta file.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{MemoryFile, OverlayFile, SharedFile};
+
+#[tokio::test]
+async fn base_file_is_extended() {
+    let path =
+        std::env::temp_dir().join(format!("shared-files-overlay-{}.bin", std::process::id()));
+    tokio::fs::write(&path, b"hello")
+        .await
+        .expect("failed to write base file");
+
+    let overlay = OverlayFile::new(&path, MemoryFile::new())
+        .await
+        .expect("failed to open base file");
+    assert_eq!(overlay.base_len(), 5);
+    let file = SharedFile::from_overlay(overlay);
+
+    // The base file is readable right away.
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.append_writer().await.expect("failed to create writer");
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+
+    // The base file is not modified.
+    let data = tokio::fs::read(&path)
+        .await
+        .expect("failed to read base file");
+    assert_eq!(data, b"hello");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove base file");
+}
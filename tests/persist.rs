@@ -0,0 +1,99 @@
+//! This test persists a completed file to its final destination.
+
+use std::path::PathBuf;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::*;
+use shared_files::SharedTemporaryFile;
+
+/// Creates a destination path next to the specified temporary file.
+fn destination(file: &SharedTemporaryFile, suffix: &str) -> PathBuf {
+    let mut path = file.file_path().clone().into_os_string();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+#[tokio::test]
+async fn complete_and_persist_moves_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let temp_path = file.file_path().clone();
+    let path = destination(&file, ".persisted");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer
+        .complete_and_persist(&path)
+        .await
+        .expect("failed to persist file");
+
+    assert_eq!(file.file_path(), &path);
+    assert_eq!(file.persisted_path(), Some(&path));
+
+    // Readers opened before and after persisting remain valid.
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+
+    drop(reader);
+    drop(file);
+
+    // The persisted file outlives the temporary file.
+    assert!(!temp_path.exists());
+    let data = tokio::fs::read(&path)
+        .await
+        .expect("failed to read persisted file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove persisted file");
+}
+
+#[tokio::test]
+async fn persist_requires_completion() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let path = destination(&file, ".persisted");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+
+    let error = file
+        .persist(&path)
+        .await
+        .expect_err("persisted pending file");
+    assert!(matches!(error, PersistError::NotCompleted));
+
+    writer.complete().await.expect("failed to complete");
+    file.persist(&path).await.expect("failed to persist file");
+
+    let error = file
+        .persist(destination(&file, ".again"))
+        .await
+        .expect_err("persisted file twice");
+    assert!(matches!(error, PersistError::AlreadyPersisted));
+
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove persisted file");
+}
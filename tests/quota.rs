@@ -0,0 +1,66 @@
+//! This test limits the total size of a set of files using a disk quota.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::prelude::QuotaError;
+use shared_files::{DiskQuota, QuotaPolicy, SharedMemoryFile};
+
+#[tokio::test]
+async fn writers_exceeding_the_budget_are_rejected() {
+    let quota = DiskQuota::new(10, QuotaPolicy::Reject);
+    let first = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = quota.writer(&first, 8).await.expect("failed to reserve");
+    assert_eq!(quota.used(), 8);
+
+    let second = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let result = quota.writer(&second, 4).await;
+    assert!(matches!(
+        result,
+        Err(QuotaError::Exceeded {
+            requested: 4,
+            available: 2
+        })
+    ));
+
+    // Completed files count with their actual size.
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(quota.used(), 5);
+    quota.writer(&second, 4).await.expect("failed to reserve");
+    assert_eq!(quota.len(), 2);
+}
+
+#[tokio::test]
+async fn least_recently_used_files_are_evicted() {
+    let quota = DiskQuota::new(10, QuotaPolicy::EvictLeastRecentlyUsed);
+    let mut files = Vec::new();
+    for _ in 0..2 {
+        let file = SharedMemoryFile::new_async()
+            .await
+            .expect("failed to create file");
+        let mut writer = quota.writer(&file, 5).await.expect("failed to reserve");
+        writer.write_all(b"hello").await.expect("failed to write");
+        writer.complete().await.expect("failed to complete");
+        files.push(file);
+    }
+    assert!(quota.touch(&files[0]));
+
+    let file = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let _writer = quota.writer(&file, 5).await.expect("failed to reserve");
+    assert_eq!(quota.len(), 2);
+    assert!(!quota.remove(&files[1]));
+    assert!(quota.remove(&files[0]));
+
+    // Files being written are never evicted.
+    let other = SharedMemoryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert!(quota.writer(&other, 6).await.is_err());
+    assert_eq!(quota.len(), 1);
+}
@@ -0,0 +1,42 @@
+//! This test limits the rate at which data is written.
+
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{RateLimit, SharedTemporaryFile};
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 32 * 1024;
+
+/// The rate limit to apply; the first half of the data is written in a burst.
+const LIMIT: RateLimit = RateLimit::new(64 * 1024, NUM_BYTES / 2);
+
+#[tokio::test]
+async fn writes_are_rate_limited() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_rate_limit(LIMIT);
+
+    let start = Instant::now();
+    writer
+        .write_all(&[42; NUM_BYTES])
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    // The remaining half takes a quarter of a second at the sustained rate.
+    assert!(start.elapsed() >= Duration::from_millis(200));
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf.len(), NUM_BYTES);
+}
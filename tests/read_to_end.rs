@@ -0,0 +1,56 @@
+//! This test reads entire files while they are being written.
+
+use bytes::BytesMut;
+use tokio::io::AsyncWriteExt;
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of chunks to write.
+const NUM_CHUNKS: usize = 50;
+
+/// The size of each chunk.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn read_to_end_when_complete() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut vec_reader = file.reader().await.expect("failed to create reader");
+    let mut bytes_reader = file.reader().await.expect("failed to create reader");
+    let vec_task = tokio::spawn(async move {
+        let mut data = Vec::new();
+        let read = vec_reader
+            .read_to_end_when_complete(&mut data)
+            .await
+            .expect("failed to read");
+        assert_eq!(read, data.len());
+        data
+    });
+    let bytes_task = tokio::spawn(async move {
+        let mut data = BytesMut::new();
+        bytes_reader
+            .read_to_end_when_complete(&mut data)
+            .await
+            .expect("failed to read");
+        data
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut expected = Vec::new();
+    for chunk in 0..NUM_CHUNKS {
+        let data = [chunk as u8; CHUNK_SIZE];
+        writer.write_all(&data).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+        expected.extend_from_slice(&data);
+        tokio::task::yield_now().await;
+    }
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(vec_task.await.expect("failed to join"), expected);
+    assert_eq!(
+        &bytes_task.await.expect("failed to join")[..],
+        &expected[..]
+    );
+}
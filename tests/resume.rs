@@ -0,0 +1,57 @@
+//! This test resumes a failed file at the last committed offset.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::*;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn resume_discards_uncommitted_data() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer
+        .write_all(b" wrong")
+        .await
+        .expect("failed to write data");
+    writer.abort("transient disk error");
+    assert_eq!(file.resume_offset(), 5);
+
+    let mut writer = file.resume_writer().await.expect("failed to resume writer");
+    writer
+        .write_all(b" world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
+
+#[tokio::test]
+async fn resume_requires_failed_writer_to_be_dropped() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let writer = file.writer().await.expect("failed to create writer");
+    file.abort("transient disk error");
+
+    let result = file.resume_writer().await;
+    assert!(matches!(result, Err(OpenWriterError::WriterExists)));
+
+    drop(writer);
+    file.resume_writer().await.expect("failed to resume writer");
+}
@@ -0,0 +1,98 @@
+//! This test applies retention policies to files that are no longer used.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{RetentionPolicy, SharedTemporaryFile};
+
+#[tokio::test]
+async fn keep_retains_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    file.set_retention_policy(RetentionPolicy::Keep);
+    assert_eq!(file.retention_policy(), RetentionPolicy::Keep);
+    let path = file.file_path().clone();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+    drop(file);
+
+    let data = tokio::fs::read(&path)
+        .await
+        .expect("failed to read kept file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove kept file");
+}
+
+#[tokio::test]
+async fn keep_on_failure_retains_failed_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    file.set_retention_policy(RetentionPolicy::KeepOnFailure);
+    let path = file.file_path().clone();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.abort("connection reset");
+    drop(file);
+
+    assert!(path.exists());
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove kept file");
+}
+
+#[tokio::test]
+async fn keep_on_failure_deletes_completed_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    file.set_retention_policy(RetentionPolicy::KeepOnFailure);
+    let path = file.file_path().clone();
+
+    let writer = file.writer().await.expect("failed to create writer");
+    writer.complete().await.expect("failed to complete");
+    drop(file);
+
+    assert!(!path.exists());
+}
+
+#[tokio::test]
+async fn delete_after_last_reader() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    file.set_retention_policy(RetentionPolicy::DeleteAfterLastReader);
+    let path = file.file_path().clone();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+    assert!(path.exists());
+
+    // The file is deleted while the shared file is still alive.
+    drop(reader);
+    assert!(!path.exists());
+    drop(file);
+}
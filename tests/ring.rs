@@ -0,0 +1,199 @@
+//! This test shares a file retaining only the most recent data written to it.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::{FileEvent, RingFile, SharedRingFile};
+
+#[tokio::test]
+async fn readers_behind_the_window_are_evicted() {
+    let path = std::env::temp_dir().join(format!("shared-files-ring-{}.bin", std::process::id()));
+    let file = SharedRingFile::from(
+        RingFile::create(&path, 8)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut lagging = file.reader().await.expect("failed to create reader");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    let mut buf = [0; 4];
+    for chunk in [b"abcd", b"efgh", b"ijkl"] {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+        reader
+            .read_exact(&mut buf)
+            .await
+            .expect("failed to read file");
+        assert_eq!(&buf, chunk);
+    }
+
+    let error = lagging
+        .read_exact(&mut buf)
+        .await
+        .expect_err("reading evicted data succeeded");
+    let error = error
+        .get_ref()
+        .and_then(|e| e.downcast_ref::<ReadError>())
+        .expect("the error is not a read error");
+    assert!(matches!(
+        error,
+        ReadError::Evicted {
+            position: 0,
+            lagged: 4
+        }
+    ));
+
+    writer.complete().await.expect("failed to complete");
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[tokio::test]
+async fn readers_observe_their_eviction_headroom() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-ring-headroom-{}.bin",
+        std::process::id()
+    ));
+    let file = SharedRingFile::from(
+        RingFile::create(&path, 8)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    assert_eq!(reader.eviction_headroom(), 8);
+
+    writer.write_all(b"abcdef").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    assert_eq!(reader.lag(), Some(6));
+    assert_eq!(reader.eviction_headroom(), 2);
+    assert!(!reader.is_evicted());
+
+    let mut buf = [0; 4];
+    reader
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(reader.eviction_headroom(), 6);
+
+    writer
+        .write_all(b"ghijklmn")
+        .await
+        .expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    assert_eq!(reader.eviction_headroom(), 0);
+    assert!(reader.is_evicted());
+
+    writer.complete().await.expect("failed to complete");
+    drop(reader);
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[tokio::test]
+async fn evicted_readers_resume_at_the_oldest_data() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-ring-resume-{}.bin",
+        std::process::id()
+    ));
+    let file = SharedRingFile::from(
+        RingFile::create(&path, 8)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"abcdefghijkl")
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let error = reader
+        .read_exact(&mut [0; 4])
+        .await
+        .expect_err("reading evicted data succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Evicted {
+            position: 0,
+            lagged: 4
+        })
+    ));
+
+    assert_eq!(reader.seek_to_oldest().await.expect("failed to seek"), 4);
+    assert_eq!(reader.seek_to_oldest().await.expect("failed to seek"), 0);
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"efghijkl");
+
+    drop(reader);
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
+
+#[tokio::test]
+async fn lagging_readers_are_reported() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-ring-lagging-{}.bin",
+        std::process::id()
+    ));
+    let file = SharedRingFile::from(
+        RingFile::create(&path, 16)
+            .await
+            .expect("failed to create file"),
+    );
+    let mut events = file.events();
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .with_lag_warning(4);
+    let mut writer = file.writer().await.expect("failed to create writer");
+
+    for chunk in [&b"abc"[..], b"def", b"g"] {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    let mut buf = [0; 7];
+    reader
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read file");
+
+    // Catching up resets the warning.
+    for chunk in [&b"h"[..], b"ijklm"] {
+        writer.write_all(chunk).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    writer.complete().await.expect("failed to complete");
+
+    let mut lagging = Vec::new();
+    loop {
+        match events.recv().await.expect("the file was dropped") {
+            FileEvent::ReaderLagging(id, lag) => {
+                assert_eq!(id, reader.id());
+                lagging.push(lag);
+            }
+            FileEvent::Completed(_) => break,
+            _ => {}
+        }
+    }
+    assert_eq!(lagging, vec![6, 6]);
+
+    drop(reader);
+    drop(file);
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
@@ -0,0 +1,154 @@
+//! This test writes a file in disjoint segments while it is read concurrently.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::OpenWriterError;
+use shared_files::{FileSize, SharedTemporaryFile};
+
+/// The number of segments to write.
+const NUM_SEGMENTS: u64 = 8;
+
+/// The size of each segment.
+const SEGMENT_SIZE: u64 = 10_000;
+
+#[tokio::test(flavor = "multi_thread")]
+async fn segments_are_written_in_parallel() {
+    let len = NUM_SEGMENTS * SEGMENT_SIZE;
+    let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let writer = file
+        .segmented_writer(len)
+        .expect("failed to create segmented writer");
+
+    // Write the segments in reverse order, such that the file is only complete at the end.
+    let mut tasks = Vec::new();
+    for index in (0..NUM_SEGMENTS).rev() {
+        let range = index * SEGMENT_SIZE..(index + 1) * SEGMENT_SIZE;
+        let mut segment = writer
+            .segment(range.clone())
+            .await
+            .expect("failed to create segment writer");
+        let data = data[range.start as usize..range.end as usize].to_vec();
+        tasks.push(tokio::spawn(async move {
+            segment.write_all(&data).await.expect("failed to write");
+            segment.complete().await.expect("failed to complete");
+        }));
+    }
+
+    // Read the last segment while earlier segments may still be pending.
+    let mut last = vec![0; SEGMENT_SIZE as usize];
+    let mut random_reader = file.reader().await.expect("failed to create reader");
+    random_reader
+        .read_exact_at(len - SEGMENT_SIZE, &mut last)
+        .await
+        .expect("failed to read segment");
+    assert_eq!(last, data[(len - SEGMENT_SIZE) as usize..]);
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, data);
+    assert_eq!(reader.file_size(), FileSize::Exactly(len));
+
+    for task in tasks {
+        task.await.expect("failed to join writer");
+    }
+}
+
+#[tokio::test]
+async fn overlapping_segments_are_rejected() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let writer = file
+        .segmented_writer(100)
+        .expect("failed to create segmented writer");
+
+    let segment = writer
+        .segment(10..50)
+        .await
+        .expect("failed to create segment writer");
+    assert!(matches!(
+        writer.segment(40..60).await,
+        Err(OpenWriterError::InvalidRange)
+    ));
+    assert!(matches!(
+        writer.segment(90..110).await,
+        Err(OpenWriterError::InvalidRange)
+    ));
+    assert!(matches!(
+        file.try_writer().await,
+        Err(OpenWriterError::WriterExists)
+    ));
+
+    // Dropping the writer releases its segment.
+    drop(segment);
+    writer
+        .segment(40..60)
+        .await
+        .expect("failed to create segment writer");
+}
+
+#[tokio::test]
+async fn range_is_awaited() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let reader = file.reader().await.expect("failed to create reader");
+    let writer = file
+        .segmented_writer(10)
+        .expect("failed to create segmented writer");
+
+    let mut segment = writer
+        .segment(5..10)
+        .await
+        .expect("failed to create segment writer");
+    segment.write_all(b"world").await.expect("failed to write");
+    segment.flush().await.expect("failed to flush");
+
+    reader
+        .wait_for_range(5..10)
+        .await
+        .expect("failed to wait for range");
+    assert_eq!(reader.file_size(), FileSize::AtLeastWithTotal(0, 10));
+}
+
+#[tokio::test]
+async fn segments_outlive_their_coordinator() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let writer = file
+        .segmented_writer(100)
+        .expect("failed to create segmented writer");
+    let segment = writer
+        .segment(10..50)
+        .await
+        .expect("failed to create segment writer");
+    drop(writer);
+
+    // The segment of the remaining writer must not be handed out again.
+    assert!(matches!(
+        file.segmented_writer(200),
+        Err(OpenWriterError::WriterExists)
+    ));
+    let writer = file
+        .segmented_writer(100)
+        .expect("failed to create segmented writer");
+    assert!(matches!(
+        writer.segment(40..60).await,
+        Err(OpenWriterError::InvalidRange)
+    ));
+
+    drop(segment);
+    writer
+        .segment(40..60)
+        .await
+        .expect("failed to create segment writer");
+}
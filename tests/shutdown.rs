@@ -0,0 +1,83 @@
+//! This test completes files by shutting down the writer.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::{FileSize, SharedTemporaryFile, ShutdownBehavior};
+
+#[tokio::test]
+async fn shutdown_syncs_then_completes() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_shutdown_behavior(ShutdownBehavior::SyncThenComplete);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.shutdown().await.expect("failed to shut down");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::Exactly(11));
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
+
+#[tokio::test]
+async fn shutdown_fails_if_uncommitted() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_shutdown_behavior(ShutdownBehavior::FailIfUncommitted);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer
+        .shutdown()
+        .await
+        .expect_err("shutting down with uncommitted data succeeded");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let error = reader
+        .read_to_end(&mut Vec::new())
+        .await
+        .expect_err("reading a failed file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::UncommittedData)
+    ));
+}
+
+#[tokio::test]
+async fn shutdown_completes_if_committed() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_shutdown_behavior(ShutdownBehavior::FailIfUncommitted);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.shutdown().await.expect("failed to shut down");
+
+    let reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size(), FileSize::Exactly(11));
+}
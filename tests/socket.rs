@@ -0,0 +1,48 @@
+//! This test follows the state of a file through a Unix domain socket.
+#![cfg(unix)]
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::prelude::*;
+use shared_files::{FileEvent, FsFile, SocketEvents};
+
+#[tokio::test]
+async fn commits_are_published_over_socket() {
+    let path = std::env::temp_dir().join(format!("shared-files-uds-{}.bin", std::process::id()));
+    let socket = path.with_extension("sock");
+    let file = SharedFile::<FsFile>::create(&path)
+        .await
+        .expect("failed to create file");
+    let task = file
+        .spawn_socket_notifier(&socket)
+        .expect("failed to bind socket");
+    let mut events = SocketEvents::connect(&socket)
+        .await
+        .expect("failed to connect");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    let event = events.recv().await.expect("failed to receive event");
+    assert!(matches!(event, Some(FileEvent::Committed(5))));
+
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+    let mut event = events.recv().await.expect("failed to receive event");
+    if let Some(FileEvent::Committed(_)) = event {
+        event = events.recv().await.expect("failed to receive event");
+    }
+    assert!(matches!(event, Some(FileEvent::Completed(11))));
+    assert!(events
+        .recv()
+        .await
+        .expect("failed to receive event")
+        .is_none());
+
+    drop(file);
+    task.await.expect("failed to join notifier");
+    assert!(!socket.exists());
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+}
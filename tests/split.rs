@@ -0,0 +1,49 @@
+//! This test streams data from a writer to a reader created using `split` and `pipe`.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn split_writer_and_reader_are_connected() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let (mut writer, mut reader) = file.split().await.expect("failed to split file");
+
+    let write = tokio::spawn(async move {
+        writer
+            .write_all(b"hello world")
+            .await
+            .expect("failed to write data");
+        writer.complete().await.expect("failed to complete");
+    });
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    write.await.expect("failed to join writer");
+    assert_eq!(buf, b"hello world");
+}
+
+#[tokio::test]
+async fn pipe_writer_and_reader_are_connected() {
+    let (mut writer, mut reader) = SharedTemporaryFile::pipe()
+        .await
+        .expect("failed to create pipe");
+
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
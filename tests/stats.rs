@@ -0,0 +1,39 @@
+//! This test tracks the throughput counters of writers.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::{SharedTemporaryFile, WriterStats};
+
+#[tokio::test]
+async fn stats_count_writes_commits_and_syncs() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.writer_stats(), WriterStats::default());
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer
+        .write_all(b" world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.sync_data().await.expect("failed to sync");
+
+    let stats = writer.stats();
+    assert_eq!(stats.bytes_written, 11);
+    assert_eq!(stats.writes, 2);
+    assert_eq!(stats.commits, 1);
+    assert_eq!(stats.syncs, 1);
+
+    writer.complete().await.expect("failed to complete");
+
+    // Completing syncs the file once more.
+    let stats = file.writer_stats();
+    assert_eq!(stats.bytes_written, 11);
+    assert_eq!(stats.commits, 1);
+    assert_eq!(stats.syncs, 2);
+}
@@ -0,0 +1,165 @@
+//! This test inspects, awaits and subscribes to the state of a file without creating a reader.
+
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+
+use shared_files::{SharedTemporaryFile, WriteStatus};
+
+#[tokio::test]
+async fn status_tracks_the_writer() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(
+        file.status(),
+        WriteStatus::Pending {
+            committed: 0,
+            written: 0
+        }
+    );
+    assert!(file.is_empty());
+    assert!(!file.is_complete());
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    assert_eq!(
+        file.status(),
+        WriteStatus::Pending {
+            committed: 0,
+            written: 11
+        }
+    );
+
+    writer.flush().await.expect("failed to flush");
+    assert_eq!(file.len(), 11);
+    assert!(!file.is_complete());
+
+    writer.complete().await.expect("failed to complete");
+    assert_eq!(file.status(), WriteStatus::Completed { len: 11 });
+    assert_eq!(file.len(), 11);
+    assert!(file.is_complete());
+}
+
+#[tokio::test]
+async fn status_reports_failure() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    writer.abort("connection reset");
+
+    assert_eq!(file.status(), WriteStatus::Failed);
+    assert_eq!(file.len(), 5);
+    assert!(!file.is_complete());
+}
+
+#[tokio::test]
+async fn wait_until_complete_resolves_with_size() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let coordinator = tokio::spawn({
+        let file = file.clone();
+        async move { file.wait_until_complete().await }
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    assert!(!coordinator.is_finished());
+
+    writer.complete().await.expect("failed to complete");
+    let len = coordinator
+        .await
+        .expect("failed to join task")
+        .expect("writing failed");
+    assert_eq!(len, 11);
+}
+
+#[tokio::test]
+async fn wait_until_complete_reports_failure() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let coordinator = tokio::spawn({
+        let file = file.clone();
+        async move { file.wait_until_complete().await }
+    });
+
+    let writer = file.writer().await.expect("failed to create writer");
+    writer.abort("connection reset");
+
+    let error = coordinator
+        .await
+        .expect("failed to join task")
+        .expect_err("writing succeeded");
+    assert!(error.to_string().contains("connection reset"));
+}
+
+#[tokio::test]
+async fn completion_is_selectable() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut completion = file.completion();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    tokio::select! {
+        _ = &mut completion => panic!("the file was completed early"),
+        _ = tokio::time::sleep(Duration::from_millis(10)) => {}
+    }
+
+    writer.complete().await.expect("failed to complete");
+    let len = completion.await.expect("writing failed");
+    assert_eq!(len, 11);
+}
+
+#[tokio::test]
+async fn subscribe_observes_transitions() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut status = file.subscribe();
+    assert_eq!(
+        *status.borrow_and_update(),
+        WriteStatus::Pending {
+            committed: 0,
+            written: 0
+        }
+    );
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    status.changed().await.expect("failed to observe change");
+    assert_eq!(
+        *status.borrow_and_update(),
+        WriteStatus::Pending {
+            committed: 11,
+            written: 11
+        }
+    );
+
+    writer.complete().await.expect("failed to complete");
+    status.changed().await.expect("failed to observe change");
+    assert_eq!(*status.borrow(), WriteStatus::Completed { len: 11 });
+}
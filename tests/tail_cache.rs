@@ -0,0 +1,90 @@
+//! This test serves readers from the in-memory tail cache of the writer,
+//! including readers falling behind the cached data.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{SharedTemporaryFile, SharedTemporaryFileReader};
+
+/// The number of chunks to write.
+const NUM_CHUNKS: usize = 100;
+
+/// The size of each chunk.
+const CHUNK_SIZE: usize = 1_000;
+
+/// The capacity of the tail cache.
+const CACHE_CAPACITY: usize = 4 * CHUNK_SIZE;
+
+#[tokio::test]
+async fn readers_are_served_from_tail_cache() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let follower = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .with_tail_cache()
+        .verify_trailer();
+    let bytes_reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .with_tail_cache();
+    let follower = tokio::spawn(read_all(follower));
+    let bytes_reader = tokio::spawn(read_all_bytes(bytes_reader));
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_checksum()
+        .with_tail_cache(CACHE_CAPACITY);
+    let mut expected = Vec::new();
+    for chunk in 0..NUM_CHUNKS {
+        let data = [chunk as u8; CHUNK_SIZE];
+        writer.write_all(&data).await.expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+        expected.extend_from_slice(&data);
+
+        // Let the readers catch up to exercise the cache.
+        tokio::task::yield_now().await;
+    }
+
+    // This reader starts after the beginning of the file was evicted from the cache.
+    let late = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .with_tail_cache()
+        .verify_trailer();
+    let late = tokio::spawn(read_all(late));
+
+    writer
+        .complete_with_trailer()
+        .await
+        .expect("failed to complete");
+
+    assert_eq!(follower.await.expect("failed to join"), expected);
+    assert_eq!(bytes_reader.await.expect("failed to join"), expected);
+    assert_eq!(late.await.expect("failed to join"), expected);
+}
+
+/// Reads the reader to the end.
+async fn read_all(mut reader: SharedTemporaryFileReader) -> Vec<u8> {
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await.expect("failed to read");
+    data
+}
+
+/// Reads the reader to the end using zero-copy reads.
+async fn read_all_bytes(mut reader: SharedTemporaryFileReader) -> Vec<u8> {
+    let mut data = Vec::new();
+    loop {
+        let bytes = reader.read_bytes(CHUNK_SIZE).await.expect("failed to read");
+        if bytes.is_empty() {
+            return data;
+        }
+        data.extend_from_slice(&bytes);
+    }
+}
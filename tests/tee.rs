@@ -0,0 +1,116 @@
+//! This test forwards written data to a secondary writer.
+
+use std::io::{Error, ErrorKind};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::{SharedTemporaryFile, TeeErrorPolicy};
+
+/// A secondary writer that always fails.
+struct BrokenWriter;
+
+impl AsyncWrite for BrokenWriter {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        _buf: &[u8],
+    ) -> Poll<Result<usize, Error>> {
+        Poll::Ready(Err(Error::from(ErrorKind::BrokenPipe)))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Reads the entire file.
+async fn read_file<R: AsyncRead + Unpin>(reader: &mut R) -> Vec<u8> {
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    buf
+}
+
+#[tokio::test]
+async fn written_data_is_forwarded() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .tee(Vec::new(), TeeErrorPolicy::Fail);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+    assert_eq!(
+        writer.secondary().map(Vec::as_slice),
+        Some(&b"hello world"[..])
+    );
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(read_file(&mut reader).await, b"hello world");
+}
+
+#[tokio::test]
+async fn failing_secondary_fails_file() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .tee(BrokenWriter, TeeErrorPolicy::Fail);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect_err("writing to a broken secondary succeeded");
+    drop(writer);
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let error = reader
+        .read_to_end(&mut Vec::new())
+        .await
+        .expect_err("reading a failed file succeeded");
+    match error.get_ref().and_then(|e| e.downcast_ref()) {
+        Some(ReadError::WriteFailed(cause)) => assert_eq!(cause.kind(), ErrorKind::BrokenPipe),
+        _ => panic!("unexpected error: {error}"),
+    }
+}
+
+#[tokio::test]
+async fn failing_secondary_is_detached() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .tee(BrokenWriter, TeeErrorPolicy::Detach);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    assert!(writer.secondary().is_none());
+    writer.complete().await.expect("failed to complete");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(read_file(&mut reader).await, b"hello world");
+}
@@ -0,0 +1,30 @@
+//! This test chooses the directory and name of temporary files.
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn file_is_named_using_affixes() {
+    let dir = std::env::temp_dir();
+    let file = SharedTemporaryFile::new_with_affixes_in("upload-", ".json", &dir)
+        .await
+        .expect("failed to create file");
+
+    let path = file.file_path();
+    assert_eq!(path.parent(), Some(dir.as_path()));
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("invalid file name");
+    assert!(name.starts_with("upload-"));
+    assert!(name.ends_with(".json"));
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn file_is_created_in_directory() {
+    let dir = std::env::temp_dir();
+    let file = SharedTemporaryFile::new_in(&dir)
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.file_path().parent(), Some(dir.as_path()));
+}
@@ -0,0 +1,25 @@
+//! This test selects directories on memory-backed file systems.
+
+use shared_files::{fastest_dir, is_memory_backed};
+
+#[tokio::test]
+async fn missing_directories_are_skipped() {
+    let missing = std::env::temp_dir().join("shared-files-missing-dir");
+    let temp = std::env::temp_dir();
+    assert_eq!(fastest_dir(&[&missing, &temp]).await, Some(temp.clone()));
+    assert_eq!(fastest_dir(&[&missing]).await, None);
+    assert!(is_memory_backed(&missing).await.is_err());
+}
+
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn memory_backed_directory_is_preferred() {
+    let shm = std::path::Path::new("/dev/shm");
+    if !is_memory_backed(shm).await.unwrap_or(false) {
+        // Not every system mounts tmpfs at /dev/shm.
+        return;
+    }
+
+    let temp = std::env::temp_dir();
+    assert_eq!(fastest_dir(&[&temp, shm]).await.as_deref(), Some(shm));
+}
@@ -0,0 +1,46 @@
+//! This test shares an already open file between a writer and concurrent readers.
+#![cfg(any(target_os = "linux", target_os = "android"))]
+
+use tokio::fs::File;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedFile;
+
+#[tokio::test]
+async fn open_file_is_shared() {
+    let path = std::env::temp_dir().join(format!(
+        "shared-files-tokio-file-{}.bin",
+        std::process::id()
+    ));
+    let file = File::create(&path).await.expect("failed to create file");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove file");
+
+    // The file is reopened even though it was unlinked.
+    let file = SharedFile::from(file);
+    let mut first = file.reader().await.expect("failed to create reader");
+    let mut second = file.reader().await.expect("failed to create reader");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+
+    // Readers have their own position.
+    let mut buf = [0; 5];
+    first
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read data");
+    assert_eq!(&buf, b"hello");
+
+    writer.write_all(b" world").await.expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    second
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"hello world");
+}
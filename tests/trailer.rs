@@ -0,0 +1,113 @@
+//! This test completes a file with a checksum trailer and verifies it
+//! while reading, both for intact and corrupted content.
+
+use std::io::{ErrorKind, SeekFrom};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use shared_files::{SharedTemporaryFile, TRAILER_LEN};
+
+/// The number of bytes to write.
+const NUM_BYTES: usize = 100_000;
+
+#[tokio::test]
+async fn trailer_is_verified() {
+    let file = write_file(true).await;
+
+    // Regular readers do not see the trailer.
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await.expect("failed to read");
+    assert_eq!(data.len(), NUM_BYTES);
+
+    // Verifying readers succeed on intact data.
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .verify_trailer();
+    let mut data = Vec::new();
+    reader
+        .read_to_end(&mut data)
+        .await
+        .expect("failed to verify");
+    assert_eq!(data.len(), NUM_BYTES);
+
+    // The trailer is physically present.
+    let metadata = tokio::fs::metadata(file.file_path())
+        .await
+        .expect("failed to get metadata");
+    assert_eq!(metadata.len(), (NUM_BYTES + TRAILER_LEN) as u64);
+}
+
+#[tokio::test]
+async fn corruption_is_detected() {
+    let file = write_file(true).await;
+
+    // Corrupt a single byte of the content.
+    let mut raw = tokio::fs::OpenOptions::new()
+        .write(true)
+        .open(file.file_path())
+        .await
+        .expect("failed to open file");
+    raw.seek(SeekFrom::Start(1234))
+        .await
+        .expect("failed to seek");
+    raw.write_all(&[0xFF]).await.expect("failed to write");
+    raw.sync_all().await.expect("failed to sync");
+
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .verify_trailer();
+    let mut data = Vec::new();
+    let error = reader
+        .read_to_end(&mut data)
+        .await
+        .expect_err("corruption was not detected");
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+#[tokio::test]
+async fn missing_trailer_is_detected() {
+    let file = write_file(false).await;
+
+    let mut reader = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .verify_trailer();
+    let mut data = Vec::new();
+    let error = reader
+        .read_to_end(&mut data)
+        .await
+        .expect_err("missing trailer was not detected");
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+}
+
+/// Writes the file, optionally appending a trailer.
+async fn write_file(with_trailer: bool) -> SharedTemporaryFile {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .with_checksum();
+    for i in 0..NUM_BYTES {
+        writer.write_u8(i as u8).await.expect("failed to write");
+    }
+
+    if with_trailer {
+        writer
+            .complete_with_trailer()
+            .await
+            .expect("failed to complete write");
+    } else {
+        writer.complete().await.expect("failed to complete write");
+    }
+
+    file
+}
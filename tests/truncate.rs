@@ -0,0 +1,64 @@
+//! This test truncates a completed file and writes it again.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn truncated_file_can_be_rewritten() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"0123456789")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    let mut ahead = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    ahead
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+
+    let mut behind = file.reader().await.expect("failed to create reader");
+    let mut buf = [0; 2];
+    behind
+        .read_exact(&mut buf)
+        .await
+        .expect("failed to read data");
+
+    file.truncate(4).await.expect("failed to truncate file");
+
+    // The reader that already read past the new end of the file fails.
+    let error = ahead
+        .read(&mut [0; 1])
+        .await
+        .expect_err("reading a truncated file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::Truncated)
+    ));
+
+    let mut writer = file
+        .append_writer()
+        .await
+        .expect("failed to create append writer");
+    writer
+        .write_all(b"abc")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    // The reader before the new end of the file continues with the new data.
+    let mut buf = Vec::new();
+    behind
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf, b"23abc");
+}
@@ -0,0 +1,46 @@
+//! This test ensures only one writer can be created using `try_writer`.
+
+use shared_files::prelude::OpenWriterError;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn only_one_writer_is_active() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let writer = file.try_writer().await.expect("failed to create writer");
+    assert!(matches!(
+        file.try_writer().await,
+        Err(OpenWriterError::WriterExists)
+    ));
+
+    drop(writer);
+    file.try_writer()
+        .await
+        .expect("failed to create writer after the previous one was dropped");
+}
+
+#[tokio::test]
+async fn writers_are_counted() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let first = file.writer().await.expect("failed to create writer");
+    let second = file.writer().await.expect("failed to create writer");
+
+    // The second writer is still active after the first one was dropped.
+    drop(first);
+    assert!(file.has_active_writer());
+    assert!(matches!(
+        file.try_writer().await,
+        Err(OpenWriterError::WriterExists)
+    ));
+
+    drop(second);
+    assert!(!file.has_active_writer());
+    file.try_writer()
+        .await
+        .expect("failed to create writer after all writers were dropped");
+}
@@ -0,0 +1,117 @@
+//! This test shares an unnamed temporary file and persists it.
+#![cfg(target_os = "linux")]
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::{Crc32, SharedUnnamedTempFile};
+
+#[tokio::test]
+async fn unnamed_temp_file_is_persisted() {
+    let dir = std::env::temp_dir();
+    let file = SharedUnnamedTempFile::new_in(&dir)
+        .await
+        .expect("failed to create file");
+    let mut reader = file.reader().await.expect("failed to create reader");
+
+    let read = tokio::spawn(async move {
+        let mut buf = Vec::new();
+        reader
+            .read_to_end(&mut buf)
+            .await
+            .expect("failed to read file");
+        buf
+    });
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer.write_all(b"hello").await.expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+    writer.write_all(b" world").await.expect("failed to write");
+
+    let path = dir.join(format!("shared-files-unnamed-{}.bin", std::process::id()));
+    writer
+        .complete_and_persist(&path)
+        .await
+        .expect("failed to persist file");
+    assert_eq!(read.await.expect("failed to join reader"), b"hello world");
+    drop(file);
+
+    let data = tokio::fs::read(&path)
+        .await
+        .expect("failed to read persisted file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_file(&path)
+        .await
+        .expect("failed to remove persisted file");
+}
+
+#[tokio::test]
+async fn unnamed_temp_file_is_persisted_content_addressed() {
+    let dir = std::env::temp_dir().join(format!("shared-files-cas-{}", std::process::id()));
+    tokio::fs::create_dir_all(&dir)
+        .await
+        .expect("failed to create directory");
+
+    for _ in 0..2 {
+        let file = SharedUnnamedTempFile::new_in(&dir)
+            .await
+            .expect("failed to create file");
+        let mut writer = file
+            .writer()
+            .await
+            .expect("failed to create writer")
+            .with_digest(Crc32::new());
+        writer
+            .write_all(b"hello world")
+            .await
+            .expect("failed to write");
+        let path = writer
+            .complete_and_persist_content_addressed(&dir)
+            .await
+            .expect("failed to persist file");
+
+        // Identical contents are stored at the same address.
+        assert_eq!(path, dir.join("0d4a1185"));
+        assert_eq!(file.persisted_path(), Some(&path));
+    }
+
+    let data = tokio::fs::read(dir.join("0d4a1185"))
+        .await
+        .expect("failed to read persisted file");
+    assert_eq!(data, b"hello world");
+    tokio::fs::remove_dir_all(&dir)
+        .await
+        .expect("failed to remove directory");
+}
+
+#[tokio::test]
+async fn adapters_use_the_file_handles() {
+    let file = SharedUnnamedTempFile::new_in(std::env::temp_dir())
+        .await
+        .expect("failed to create file");
+    let mut chunks = file
+        .reader()
+        .await
+        .expect("failed to create reader")
+        .chunked(4, 1);
+    let read = tokio::spawn(async move {
+        let mut data = Vec::new();
+        while let Some(chunk) = chunks.next_chunk().await.expect("failed to read chunk") {
+            data.push(chunk);
+        }
+        data
+    });
+
+    let mut writer = file
+        .writer()
+        .await
+        .expect("failed to create writer")
+        .buffered(16);
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write");
+    writer.complete().await.expect("failed to complete");
+
+    let chunks = read.await.expect("failed to join reader");
+    assert_eq!(chunks, [&b"hell"[..], b"o wo", b"rld"]);
+}
@@ -0,0 +1,63 @@
+//! This test fails a file whose writer stalls.
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+/// The duration after which the writer is considered stalled.
+const TIMEOUT: Duration = Duration::from_millis(100);
+
+#[tokio::test]
+async fn stalled_writer_fails_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let watchdog = file.spawn_watchdog(TIMEOUT);
+
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    writer.flush().await.expect("failed to flush");
+
+    let error = reader
+        .read_to_end(&mut Vec::new())
+        .await
+        .expect_err("reading a stalled file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::WriterStalled(TIMEOUT))
+    ));
+    watchdog.await.expect("failed to join watchdog");
+}
+
+#[tokio::test]
+async fn progressing_writer_is_not_failed() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let mut writer = file.writer().await.expect("failed to create writer");
+    let watchdog = file.spawn_watchdog(TIMEOUT);
+
+    for _ in 0..5 {
+        tokio::time::sleep(TIMEOUT / 2).await;
+        writer
+            .write_all(b"hello")
+            .await
+            .expect("failed to write data");
+    }
+    writer.complete().await.expect("failed to complete");
+    watchdog.await.expect("failed to join watchdog");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf.len(), 25);
+}
@@ -0,0 +1,139 @@
+//! These tests track the active readers of a file, e.g. to throttle
+//! the writer to the slowest reader using the reader watermark.
+
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of chunks to write.
+const NUM_CHUNKS: usize = 20;
+
+/// The size of each chunk.
+const CHUNK_SIZE: usize = 1_000;
+
+#[tokio::test]
+async fn writer_waits_for_slowest_reader() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.slowest_reader_position(), None);
+
+    let fast = file.reader().await.expect("failed to create reader");
+    let slow = file.reader().await.expect("failed to create reader");
+    assert_eq!(file.slowest_reader_position(), Some(0));
+
+    let fast = tokio::spawn(read_all(fast, Duration::ZERO));
+    let slow = tokio::spawn(read_all(slow, Duration::from_millis(1)));
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    for chunk in 0..NUM_CHUNKS {
+        // Never run more than one chunk ahead of the slowest reader.
+        writer.wait_for_readers((chunk * CHUNK_SIZE) as u64).await;
+        let slowest = writer
+            .slowest_reader_position()
+            .expect("readers are still active");
+        assert!(slowest >= (chunk * CHUNK_SIZE) as u64);
+
+        writer
+            .write_all(&[chunk as u8; CHUNK_SIZE])
+            .await
+            .expect("failed to write");
+        writer.flush().await.expect("failed to flush");
+    }
+    writer.complete().await.expect("failed to complete");
+
+    assert_eq!(fast.await.expect("failed to join"), NUM_CHUNKS * CHUNK_SIZE);
+    assert_eq!(slow.await.expect("failed to join"), NUM_CHUNKS * CHUNK_SIZE);
+
+    // Dropped readers no longer hold back the writer.
+    assert_eq!(file.slowest_reader_position(), None);
+}
+
+#[tokio::test]
+async fn active_readers_are_listed() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(&[0; CHUNK_SIZE])
+        .await
+        .expect("failed to write");
+    writer.flush().await.expect("failed to flush");
+
+    let mut first = file.reader().await.expect("failed to create reader");
+    let second = file.reader().await.expect("failed to create reader");
+    let mut buf = [0u8; 100];
+    first.read_exact(&mut buf).await.expect("failed to read");
+
+    let readers = file.readers();
+    assert_eq!(readers.len(), 2);
+    assert_eq!(readers[0].id, first.id());
+    assert_eq!(readers[0].position, buf.len() as u64);
+    assert_eq!(readers[1].id, second.id());
+    assert_eq!(readers[1].position, 0);
+    assert!(readers[0].created <= readers[1].created);
+
+    drop(first);
+    let readers = file.readers();
+    assert_eq!(readers.len(), 1);
+    assert_eq!(readers[0].id, second.id());
+}
+
+#[tokio::test]
+async fn readers_and_writers_are_counted() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    assert_eq!(file.reader_count(), 0);
+    assert!(!file.has_active_writer());
+
+    let writer = file.writer().await.expect("failed to create writer");
+    let first = file.reader().await.expect("failed to create reader");
+    let second = file.reader().await.expect("failed to create reader");
+    assert_eq!(file.reader_count(), 2);
+    assert!(file.has_active_writer());
+
+    writer.complete().await.expect("failed to complete");
+    drop(first);
+    assert_eq!(file.reader_count(), 1);
+    assert!(!file.has_active_writer());
+
+    drop(second);
+    assert_eq!(file.reader_count(), 0);
+}
+
+#[tokio::test]
+async fn all_readers_done_waits_for_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    file.all_readers_done().await;
+
+    let reader = file.reader().await.expect("failed to create reader");
+    let waiter = tokio::spawn({
+        let file = file.clone();
+        async move { file.all_readers_done().await }
+    });
+    tokio::task::yield_now().await;
+    assert!(!waiter.is_finished());
+
+    drop(reader);
+    waiter.await.expect("failed to join task");
+}
+
+/// Reads the reader to the end in small steps, pausing between reads.
+async fn read_all<R: AsyncReadExt + Unpin>(mut reader: R, pause: Duration) -> usize {
+    let mut buf = [0u8; 100];
+    let mut total = 0;
+    loop {
+        let read = reader.read(&mut buf).await.expect("failed to read");
+        if read == 0 {
+            return total;
+        }
+        total += read;
+        tokio::time::sleep(pause).await;
+    }
+}
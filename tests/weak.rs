@@ -0,0 +1,30 @@
+//! This test references a file using weak handles.
+
+use tokio::io::AsyncWriteExt;
+
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn weak_handles_do_not_keep_file_alive() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+    let path = file.file_path().clone();
+    let weak = file.downgrade();
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    drop(file);
+
+    // The writer keeps the file alive.
+    let file = weak.upgrade().expect("failed to upgrade handle");
+    assert_eq!(file.file_path(), &path);
+    drop(file);
+
+    writer
+        .write_all(b"hello world")
+        .await
+        .expect("failed to write data");
+    writer.complete().await.expect("failed to complete");
+
+    assert!(weak.upgrade().is_none());
+}
@@ -0,0 +1,52 @@
+//! This test writes chunks of a file out of order.
+
+use tokio::io::AsyncReadExt;
+
+use shared_files::SharedTemporaryFile;
+
+/// The size of each chunk.
+const CHUNK_SIZE: usize = 1024;
+
+#[tokio::test]
+async fn out_of_order_writes_commit_contiguous_prefix() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_at(2 * CHUNK_SIZE as u64, &[2; CHUNK_SIZE])
+        .await
+        .expect("failed to write third chunk");
+    writer
+        .write_at(0, &[0; CHUNK_SIZE])
+        .await
+        .expect("failed to write first chunk");
+    writer.sync_data().await.expect("failed to sync");
+
+    // Only the first chunk is visible to readers until the hole is filled.
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(reader.file_size().minimum_size(), Some(CHUNK_SIZE as u64));
+
+    writer
+        .write_at(CHUNK_SIZE as u64, &[1; CHUNK_SIZE])
+        .await
+        .expect("failed to write second chunk");
+    writer.sync_data().await.expect("failed to sync");
+    assert_eq!(
+        reader.file_size().minimum_size(),
+        Some(3 * CHUNK_SIZE as u64)
+    );
+
+    writer.complete().await.expect("failed to complete");
+
+    let mut buf = Vec::new();
+    reader
+        .read_to_end(&mut buf)
+        .await
+        .expect("failed to read file");
+    assert_eq!(buf.len(), 3 * CHUNK_SIZE);
+    for (i, chunk) in buf.chunks(CHUNK_SIZE).enumerate() {
+        assert!(chunk.iter().all(|&b| b == i as u8));
+    }
+}
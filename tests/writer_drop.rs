@@ -0,0 +1,30 @@
+//! This test drops a writer without committing the written data.
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use shared_files::prelude::ReadError;
+use shared_files::SharedTemporaryFile;
+
+#[tokio::test]
+async fn dropping_uncommitted_writer_fails_readers() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(b"hello")
+        .await
+        .expect("failed to write data");
+    drop(writer);
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    let error = reader
+        .read_to_end(&mut Vec::new())
+        .await
+        .expect_err("reading an uncommitted file succeeded");
+    assert!(matches!(
+        error.get_ref().and_then(|e| e.downcast_ref()),
+        Some(ReadError::UncommittedData)
+    ));
+}
@@ -0,0 +1,52 @@
+//! This test backpatches a header after writing the payload.
+
+use std::io::SeekFrom;
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+
+use shared_files::SharedTemporaryFile;
+
+/// The number of payload bytes to write.
+const NUM_BYTES: usize = 10_000;
+
+#[tokio::test]
+async fn header_is_backpatched() {
+    let file = SharedTemporaryFile::new_async()
+        .await
+        .expect("failed to create file");
+
+    let mut writer = file.writer().await.expect("failed to create writer");
+    writer
+        .write_all(&[0; 4])
+        .await
+        .expect("failed to write header");
+    writer
+        .write_all(&[0xAB; NUM_BYTES])
+        .await
+        .expect("failed to write payload");
+
+    // Overwriting the header does not change the file size.
+    writer
+        .seek(SeekFrom::Start(0))
+        .await
+        .expect("failed to seek");
+    writer
+        .write_all(&(NUM_BYTES as u32).to_le_bytes())
+        .await
+        .expect("failed to write header");
+    writer.flush().await.expect("failed to flush");
+
+    let mut reader = file.reader().await.expect("failed to create reader");
+    assert_eq!(
+        reader.file_size().minimum_size(),
+        Some(4 + NUM_BYTES as u64)
+    );
+
+    writer.seek(SeekFrom::End(0)).await.expect("failed to seek");
+    writer.complete().await.expect("failed to complete");
+
+    let mut data = Vec::new();
+    reader.read_to_end(&mut data).await.expect("failed to read");
+    assert_eq!(data.len(), 4 + NUM_BYTES);
+    assert_eq!(&data[..4], &(NUM_BYTES as u32).to_le_bytes());
+    assert!(data[4..].iter().all(|&b| b == 0xAB));
+}